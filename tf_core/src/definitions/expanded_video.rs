@@ -18,22 +18,36 @@
  */
 
 use std::hash::Hash;
+use std::time::Duration;
 
 use async_trait::async_trait;
 
-use crate::Video;
+use crate::{Transport, Video, VideoId, VideoMetadata, WatchProgress};
 use tf_observer::{Observable, Observer, ObserverList};
 
+/// The prefix of the serialized [WatchProgress] in front of the serialized [Video].
+const PROGRESS_PREFIX: &str = "progress:";
+/// The prefix of the serialized seen-status in front of the serialized [Video].
+const SEEN_PREFIX: &str = "seen:";
+
 /// A [Video] with a expanded feature set.
 ///
 /// A [ExpandedVideo] implements [Observable] and will notify using [VideoEvent].
 /// You can set the playing-status of the [ExpandedVideo] using [ExpandedVideo::play]
-/// and [ExpandedVideo::stop].
+/// and [ExpandedVideo::stop], the [WatchProgress] is reported using
+/// [ExpandedVideo::report_progress] and [ExpandedVideo::set_watched]. Whether the user
+/// has already seen the video in the feed is set using [ExpandedVideo::set_seen].
+///
+/// The [WatchProgress] is part of the serialization of the [ExpandedVideo] if the
+/// [Video] was ever played, such that it is persisted in the [VideoStoreBackend][crate::VideoStoreBackend].
+/// The same holds for the seen-status if the [Video] was seen.
 #[derive(Clone)]
 pub struct ExpandedVideo<V> {
     observers: ObserverList<VideoEvent>,
     video: V,
     playing: bool,
+    progress: WatchProgress,
+    seen: bool,
 }
 
 impl<V> PartialEq for ExpandedVideo<V>
@@ -59,14 +73,46 @@ where
 impl<V: Video> std::convert::TryFrom<Vec<String>> for ExpandedVideo<V> {
     type Error = <V as std::convert::TryFrom<Vec<String>>>::Error;
 
-    fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        V::try_from(strings).map(|v| ExpandedVideo::from(v))
+    fn try_from(mut strings: Vec<String>) -> Result<Self, Self::Error> {
+        let mut progress = None;
+        let mut seen = None;
+        loop {
+            let first = strings.first();
+            if let Some(p) = first
+                .and_then(|s| s.strip_prefix(PROGRESS_PREFIX))
+                .and_then(WatchProgress::from_string)
+            {
+                progress = Some(p);
+            } else if let Some(s) = first
+                .and_then(|s| s.strip_prefix(SEEN_PREFIX))
+                .and_then(|s| s.parse::<bool>().ok())
+            {
+                seen = Some(s);
+            } else {
+                break;
+            }
+            strings.remove(0);
+        }
+        V::try_from(strings).map(|v| {
+            let mut video = ExpandedVideo::from(v);
+            video.progress = progress.unwrap_or_default();
+            video.seen = seen.unwrap_or_default();
+            video
+        })
     }
 }
 
 impl<V: Video> From<ExpandedVideo<V>> for Vec<String> {
     fn from(video: ExpandedVideo<V>) -> Self {
-        video.video.into()
+        let mut result = vec![];
+        if !video.progress.is_empty() {
+            result.push(format!("{}{}", PROGRESS_PREFIX, video.progress));
+        }
+        if video.seen {
+            result.push(format!("{}{}", SEEN_PREFIX, video.seen));
+        }
+        result.append(&mut video.video.into());
+        result
     }
 }
 
@@ -94,7 +140,26 @@ impl<V: Video> Video for ExpandedVideo<V> {
         self.video.thumbnail_url()
     }
 
-    async fn thumbnail_with_client(&self, client: &reqwest::Client) -> image::DynamicImage {
+    fn metadata(&self) -> VideoMetadata {
+        self.video.metadata()
+    }
+
+    fn id(&self) -> VideoId {
+        self.video.id()
+    }
+
+    /// Update the internal [Video], keeping the playing-status, [WatchProgress], seen-status and observers.
+    ///
+    /// If anything changed, the observers will be notified using [VideoEvent::Update].
+    fn update(&mut self, other: &Self) -> bool {
+        let changed = self.video.update(&other.video);
+        if changed {
+            self.observers.notify(VideoEvent::Update);
+        }
+        changed
+    }
+
+    async fn thumbnail_with_client(&self, client: &dyn Transport) -> image::DynamicImage {
         self.video.thumbnail_with_client(client).await
     }
 }
@@ -107,6 +172,8 @@ where
         ExpandedVideo {
             video,
             playing: false,
+            progress: WatchProgress::default(),
+            seen: false,
             observers: ObserverList::new(),
         }
     }
@@ -132,6 +199,66 @@ impl<V: Video> ExpandedVideo<V> {
         self.playing
     }
 
+    /// Get the [WatchProgress] of the video.
+    pub fn progress(&self) -> WatchProgress {
+        self.progress.clone()
+    }
+
+    /// Set the [WatchProgress] of the video, e.g. after loading it from storage.
+    ///
+    /// In contrast to [ExpandedVideo::report_progress], this does not notify the observers.
+    pub fn with_progress(mut self, progress: WatchProgress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Report that the video was played until `position`, watching it for `watched_for` since
+    /// the last report, and notify the observers using [VideoEvent::Progress].
+    ///
+    /// If the `threshold` fraction of the [duration][VideoMetadata::duration] was reached,
+    /// the video will be marked as watched, notifying the observers using [VideoEvent::Watched],
+    /// see [WatchProgress::report].
+    pub fn report_progress(&mut self, position: Duration, watched_for: Duration, threshold: f64) {
+        let duration = self.video.metadata().duration();
+        let became_watched = self
+            .progress
+            .report(position, watched_for, duration, threshold);
+        self.observers.notify(VideoEvent::Progress);
+        if became_watched {
+            self.observers.notify(VideoEvent::Watched(true));
+        }
+    }
+
+    /// Mark the video as watched or unwatched and notify the observers using
+    /// [VideoEvent::Watched] if this changed anything.
+    pub fn set_watched(&mut self, watched: bool) {
+        if self.progress.is_watched() == watched {
+            return;
+        }
+        self.progress = self.progress.clone().with_watched(watched);
+        self.observers.notify(VideoEvent::Watched(watched));
+    }
+
+    /// Whether the video was watched, see [WatchProgress::is_watched].
+    pub fn watched(&self) -> bool {
+        self.progress.is_watched()
+    }
+
+    /// Mark the video as seen or unseen in the feed and notify the observers using
+    /// [VideoEvent::Seen] if this changed anything.
+    pub fn set_seen(&mut self, seen: bool) {
+        if self.seen == seen {
+            return;
+        }
+        self.seen = seen;
+        self.observers.notify(VideoEvent::Seen(seen));
+    }
+
+    /// Whether the video was already seen in the feed.
+    pub fn seen(&self) -> bool {
+        self.seen
+    }
+
     /// Get a clone of the internal video.
     pub fn internal(&self) -> V {
         self.video.clone()
@@ -145,6 +272,16 @@ pub enum VideoEvent {
     Play,
     /// The [ExpandedVideo] was stopped, see [ExpandedVideo::stop].
     Stop,
+    /// The information of the [ExpandedVideo], e.g. the title, was updated, see [Video::update].
+    Update,
+    /// The [WatchProgress] of the [ExpandedVideo] was reported, see [ExpandedVideo::report_progress].
+    Progress,
+    /// The [ExpandedVideo] was marked as watched (`true`) or unwatched (`false`),
+    /// see [ExpandedVideo::set_watched].
+    Watched(bool),
+    /// The [ExpandedVideo] was marked as seen (`true`) or unseen (`false`),
+    /// see [ExpandedVideo::set_seen].
+    Seen(bool),
 }
 
 impl<V: Video> Observable<VideoEvent> for ExpandedVideo<V> {
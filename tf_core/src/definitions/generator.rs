@@ -18,8 +18,10 @@
  */
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 
-use crate::ErrorStore;
+use crate::{ErrorStore, RefreshState, Transport};
 
 #[cfg(test)]
 use {crate::mock::MockVideo, mockall::predicate::*, mockall::*};
@@ -37,7 +39,7 @@ pub trait Generator {
     async fn generate(&self, errors: &ErrorStore) -> Self::Iterator;
 }
 
-/// Generate a [Generator::Iterator] of [Generator::Item] given a [reqwest::Client] asyncronously.
+/// Generate a [Generator::Iterator] of [Generator::Item] given a [Transport] asyncronously.
 #[async_trait]
 pub trait GeneratorWithClient {
     /// The item being generated.
@@ -46,12 +48,138 @@ pub trait GeneratorWithClient {
     /// The outcoming [Iterator].
     type Iterator: Iterator<Item = Self::Item>;
 
-    /// Generate [Self::Item] asyncronously and putting all [Error][crate::Error]s into the given [ErrorStore].
+    /// Generate [Self::Item] asyncronously using the given [Transport] and putting all [Error][crate::Error]s into the given [ErrorStore].
     async fn generate_with_client(
         &self,
         errors: &ErrorStore,
-        client: &reqwest::Client,
+        client: &dyn Transport,
     ) -> Self::Iterator;
+
+    /// Generate [Self::Item] asyncronously given the [RefreshState] of the last generation.
+    ///
+    /// This gives back the generated items together with the updated [RefreshState].
+    /// Implementations may use the [RefreshState] to send conditional requests and give back
+    /// no items if nothing changed since the last generation.
+    ///
+    /// When not overwritten, this will generate all items using [GeneratorWithClient::generate_with_client]
+    /// and not change the [RefreshState].
+    async fn generate_incremental_with_client(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        state: &RefreshState,
+    ) -> (Self::Iterator, RefreshState) {
+        (
+            self.generate_with_client(errors, client).await,
+            state.clone(),
+        )
+    }
+}
+
+/// A opaque cursor pointing to a page of older items, see [PagedGenerator].
+///
+/// The content of the cursor is specific to the [PagedGenerator] creating it, e.g. a token of
+/// the api or a offset.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PageCursor(String);
+
+impl PageCursor {
+    /// Create a new [PageCursor] from the given token.
+    pub fn new<S: AsRef<str>>(token: S) -> Self {
+        PageCursor(token.as_ref().to_string())
+    }
+
+    /// The token of the [PageCursor].
+    pub fn token(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A page of items generated by a [PagedGenerator].
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+    items: Vec<T>,
+    next: Option<PageCursor>,
+}
+
+impl<T> Page<T> {
+    /// Create a new [Page] with the given items and the [PageCursor] to the next page, if there is one.
+    pub fn new(items: Vec<T>, next: Option<PageCursor>) -> Self {
+        Page { items, next }
+    }
+
+    /// The items of the [Page].
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// The [PageCursor] to the next, older page.
+    ///
+    /// This is `None` if this is the last page.
+    pub fn next(&self) -> Option<&PageCursor> {
+        self.next.as_ref()
+    }
+
+    /// Split the [Page] into its items and the [PageCursor] to the next page.
+    pub fn into_parts(self) -> (Vec<T>, Option<PageCursor>) {
+        (self.items, self.next)
+    }
+
+    /// Map the items of the [Page], keeping the [PageCursor].
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next: self.next,
+        }
+    }
+}
+
+/// A [GeneratorWithClient] that can also generate older items page by page.
+///
+/// The first page (`cursor` being `None`) contains the newest items, following the
+/// [Page::next] cursors gives older and older items.
+#[async_trait]
+pub trait PagedGenerator: GeneratorWithClient {
+    /// Generate the page at the given [PageCursor], or the first page if it is `None`,
+    /// putting all [Error][crate::Error]s into the given [ErrorStore].
+    async fn generate_page_with_client(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        cursor: Option<&PageCursor>,
+    ) -> Page<Self::Item>;
+}
+
+/// Generate batches of [StreamGenerator::Item] as a [Stream][futures::Stream].
+///
+/// In contrast to [Generator], the items do not have to be generated completely before
+/// the first can be used. For example, a batch may be emitted as soon as one
+/// [Subscription][crate::Subscription] was fetched.
+#[async_trait]
+pub trait StreamGenerator {
+    /// The item being generated.
+    type Item: std::marker::Send;
+
+    /// Generate batches of [Self::Item] as a [Stream][futures::Stream] and putting all
+    /// [Error][crate::Error]s into the given [ErrorStore].
+    ///
+    /// There is no ordering guarantee in between the batches, use
+    /// [StreamGenerator::generate_merged_by_key] if a ordering of all items is needed.
+    fn generate_stream<'a>(&'a self, errors: &'a ErrorStore) -> BoxStream<'a, Vec<Self::Item>>;
+
+    /// Generate all [Self::Item]s and sort them by the given key.
+    ///
+    /// This will wait until all batches of [StreamGenerator::generate_stream] were generated.
+    async fn generate_merged_by_key<K, F>(&self, errors: &ErrorStore, key: F) -> Vec<Self::Item>
+    where
+        Self: std::marker::Sync,
+        K: Ord,
+        F: FnMut(&Self::Item) -> K + std::marker::Send,
+    {
+        let mut items = self.generate_stream(errors).concat().await;
+        items.sort_by_key(key);
+        items
+    }
 }
 
 #[async_trait]
@@ -64,7 +192,7 @@ where
     type Iterator = <T as GeneratorWithClient>::Iterator;
 
     async fn generate(&self, errors: &ErrorStore) -> Self::Iterator {
-        self.generate_with_client(errors, &reqwest::Client::new())
+        self.generate_with_client(errors, crate::default_transport().as_ref())
             .await
     }
 }
@@ -23,10 +23,20 @@
 //!
 //! - [`ExpandedVideo`][expanded_video::ExpandedVideo]
 //! - [`Generator`][generator::Generator]
+//! - [`RefreshState`][refresh_state::RefreshState]
 //! - [`Subscription`][subscription::Subscription]
+//! - [`Transport`][transport::Transport]
 //! - [`Video`][video::Video]
+//! - [`VideoId`][video_id::VideoId]
+//! - [`VideoMetadata`][video_metadata::VideoMetadata]
+//! - [`WatchProgress`][watch_progress::WatchProgress]
 
 pub mod expanded_video;
 pub mod generator;
+pub mod refresh_state;
 pub mod subscription;
+pub mod transport;
 pub mod video;
+pub mod video_id;
+pub mod video_metadata;
+pub mod watch_progress;
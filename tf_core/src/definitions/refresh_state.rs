@@ -0,0 +1,189 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+
+use crate::{PageCursor, VideoId};
+
+/// The state of a [Subscription][crate::Subscription] remembered in between two refreshes.
+///
+/// This is used by the [Merger][crate::Merger] to only yield new [Video][crate::Video]s
+/// and by [GeneratorWithClient][crate::GeneratorWithClient]s to send conditional requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshState {
+    /// The upload time of the newest [Video][crate::Video] seen so far.
+    newest: Option<chrono::NaiveDateTime>,
+    /// The [VideoId]s of the [Video][crate::Video]s in the feed from the last response.
+    seen: HashSet<VideoId>,
+    /// The `ETag` of the feed from the last response.
+    etag: Option<String>,
+    /// The `Last-Modified` of the feed from the last response.
+    last_modified: Option<String>,
+    /// The [PageCursor] to the page after the feed, see [RefreshState::next_page].
+    next_page: Option<Option<PageCursor>>,
+}
+
+impl RefreshState {
+    /// Create a new [RefreshState] of a [Subscription][crate::Subscription] that was never refreshed.
+    pub fn new() -> Self {
+        RefreshState::default()
+    }
+
+    /// Get the upload time of the newest [Video][crate::Video] seen so far.
+    pub fn newest(&self) -> Option<chrono::NaiveDateTime> {
+        self.newest
+    }
+
+    /// Get the `ETag` of the feed from the last response.
+    pub fn etag(&self) -> Option<String> {
+        self.etag.clone()
+    }
+
+    /// Get the `Last-Modified` of the feed from the last response.
+    pub fn last_modified(&self) -> Option<String> {
+        self.last_modified.clone()
+    }
+
+    /// Get the [PageCursor] to the page of older [Video][crate::Video]s following the feed from
+    /// the last response.
+    ///
+    /// This is only known if the feed is the first page of a [PagedGenerator][crate::PagedGenerator],
+    /// `Some(None)` meaning that there are no older [Video][crate::Video]s.
+    pub fn next_page(&self) -> Option<Option<PageCursor>> {
+        self.next_page.clone()
+    }
+
+    /// Whether any validator of the feed, i.e. `ETag` or `Last-Modified`, is known.
+    pub fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+
+    /// Whether the [Video][crate::Video] with the given [VideoId] was not yet seen.
+    ///
+    /// In contrast to comparing upload times, this also finds [Video][crate::Video]s uploaded
+    /// at the same time as the newest one and [Video][crate::Video]s published late with an
+    /// older date.
+    pub fn is_new(&self, id: &VideoId) -> bool {
+        !self.seen.contains(id)
+    }
+
+    /// Give a copy of the [RefreshState] with the given newest upload time,
+    /// if it is newer than the one already stored.
+    pub fn with_newest(&self, uploaded: chrono::NaiveDateTime) -> Self {
+        RefreshState {
+            newest: Some(self.newest.map_or(uploaded, |n| n.max(uploaded))),
+            ..self.clone()
+        }
+    }
+
+    /// Give a copy of the [RefreshState] remembering the [VideoId]s of the feed from the last response.
+    ///
+    /// This replaces the previously seen [VideoId]s, such that only the current feed is remembered.
+    pub fn with_seen<I: IntoIterator<Item = VideoId>>(&self, ids: I) -> Self {
+        RefreshState {
+            seen: ids.into_iter().collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Give a copy of the [RefreshState] with the [PageCursor] to the page after the feed,
+    /// if the feed is the first page of a [PagedGenerator][crate::PagedGenerator].
+    pub fn with_next_page(&self, next_page: Option<PageCursor>) -> Self {
+        RefreshState {
+            next_page: Some(next_page),
+            ..self.clone()
+        }
+    }
+
+    /// Give a copy of the [RefreshState] with the given `ETag` and `Last-Modified` of a response.
+    pub fn with_validators(&self, etag: Option<String>, last_modified: Option<String>) -> Self {
+        RefreshState {
+            etag,
+            last_modified,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    #[test]
+    fn refresh_state_new_is_new() {
+        assert!(RefreshState::new().is_new(&VideoId::Url("a".to_owned())));
+    }
+
+    #[test]
+    fn refresh_state_with_seen() {
+        let state = RefreshState::new()
+            .with_seen(vec![VideoId::Url("a".to_owned())])
+            .with_seen(vec![VideoId::Url("b".to_owned())]);
+
+        assert!(state.is_new(&VideoId::Url("a".to_owned())));
+        assert!(!state.is_new(&VideoId::Url("b".to_owned())));
+    }
+
+    #[test]
+    fn refresh_state_with_newest() {
+        let date_old = NaiveDate::from_ymd_opt(2021, 8, 11)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_new = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        let state = RefreshState::new()
+            .with_newest(date_new)
+            .with_newest(date_old);
+
+        assert_eq!(state.newest(), Some(date_new));
+    }
+
+    #[test]
+    fn refresh_state_with_validators() {
+        let date = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        let state = RefreshState::new()
+            .with_newest(date)
+            .with_validators(Some("\"etag\"".to_string()), None);
+
+        assert_eq!(state.newest(), Some(date));
+        assert_eq!(state.etag(), Some("\"etag\"".to_string()));
+        assert_eq!(state.last_modified(), None);
+        assert!(state.has_validators());
+    }
+
+    #[test]
+    fn refresh_state_with_next_page() {
+        let state = RefreshState::new();
+        assert_eq!(state.next_page(), None);
+
+        let state = state.with_next_page(Some(PageCursor::new("2")));
+        assert_eq!(state.next_page(), Some(Some(PageCursor::new("2"))));
+        assert_eq!(state.with_next_page(None).next_page(), Some(None));
+    }
+}
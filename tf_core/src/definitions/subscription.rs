@@ -53,11 +53,22 @@ pub trait Subscription:
     type Video: Video;
 
     fn name(&self) -> Option<String>;
+
+    /// The host the [Video]s of this [Subscription] are fetched from, e.g. `odysee.com`.
+    ///
+    /// This is used to limit the rate of requests per host, see [FetchPolicy][crate::FetchPolicy].
+    /// When not overwritten, the [Subscription] is not rate limited.
+    fn host(&self) -> Option<String> {
+        None
+    }
 }
 
 #[cfg(test)]
 mock! {
-    pub(crate) Subscription {}
+    pub(crate) Subscription {
+        // Mocked outside of the trait, as mockall cannot mock the non-static `dyn Transport` argument.
+        pub(crate) fn generate_with_client(&self, errors: &crate::ErrorStore) -> std::vec::IntoIter<MockVideo>;
+    }
 
     impl Clone for Subscription {
         fn clone(&self) -> Self;
@@ -100,12 +111,20 @@ mock! {
         type Video = MockVideo;
         fn name(&self) -> Option<String>;
     }
+}
 
-    #[async_trait]
-    impl crate::GeneratorWithClient for Subscription {
-        type Item = MockVideo;
-        type Iterator = std::vec::IntoIter<MockVideo>;
-        async fn generate_with_client(&self, errors: &crate::ErrorStore, client: &reqwest::Client) -> <Self as crate::GeneratorWithClient>::Iterator;
+#[cfg(test)]
+#[async_trait]
+impl crate::GeneratorWithClient for MockSubscription {
+    type Item = MockVideo;
+    type Iterator = std::vec::IntoIter<MockVideo>;
+
+    async fn generate_with_client(
+        &self,
+        errors: &crate::ErrorStore,
+        _client: &dyn crate::Transport,
+    ) -> Self::Iterator {
+        MockSubscription::generate_with_client(self, errors)
     }
 }
 
@@ -0,0 +1,204 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use async_trait::async_trait;
+
+use crate::NetworkError;
+
+/// A HTTP `GET` request sent using a [Transport].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// The requested url.
+    url: String,
+    /// The headers of the request.
+    headers: Vec<(String, String)>,
+}
+
+impl HttpRequest {
+    /// Create a new [HttpRequest] to the given url without any headers.
+    pub fn new<S: AsRef<str>>(url: S) -> Self {
+        HttpRequest {
+            url: url.as_ref().to_string(),
+            headers: vec![],
+        }
+    }
+
+    /// Add the header with the given name and value to the [HttpRequest].
+    pub fn with_header<N: AsRef<str>, V: AsRef<str>>(mut self, name: N, value: V) -> Self {
+        self.headers
+            .push((name.as_ref().to_string(), value.as_ref().to_string()));
+        self
+    }
+
+    /// The requested url.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// All headers of the [HttpRequest] in the order they were added.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// The value of the first header with the given name, ignoring the case of the name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// The response to a [HttpRequest] received using a [Transport].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// The HTTP status.
+    status: u16,
+    /// The headers of the response.
+    headers: Vec<(String, String)>,
+    /// The body of the response.
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Create a new [HttpResponse] with the given status and body but without any headers.
+    pub fn new<B: Into<Vec<u8>>>(status: u16, body: B) -> Self {
+        HttpResponse {
+            status,
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    /// Add the header with the given name and value to the [HttpResponse].
+    pub fn with_header<N: AsRef<str>, V: AsRef<str>>(mut self, name: N, value: V) -> Self {
+        self.headers
+            .push((name.as_ref().to_string(), value.as_ref().to_string()));
+        self
+    }
+
+    /// The HTTP status of the [HttpResponse].
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Whether the HTTP status signals a success, i.e. is in the range `200..300`.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// All headers of the [HttpResponse].
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// The value of the first header with the given name, ignoring the case of the name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// The raw body of the [HttpResponse].
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// The body of the [HttpResponse] as text, replacing invalid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+/// The way [HttpRequest]s are sent, e.g. by a [reqwest::Client].
+///
+/// Everything requesting the web, like a [GeneratorWithClient][crate::GeneratorWithClient] or
+/// [Video::thumbnail_with_client][crate::Video::thumbnail_with_client], uses a [Transport].
+/// This allows e.g. replaying recorded responses in tests or using a client with a custom proxy.
+#[async_trait]
+pub trait Transport: std::marker::Send + std::marker::Sync {
+    /// Send the given [HttpRequest].
+    ///
+    /// This should only give back a [NetworkError] if no response was received at all,
+    /// a response with a failure status is still a [HttpResponse].
+    async fn get(&self, request: HttpRequest) -> Result<HttpResponse, NetworkError>;
+}
+
+#[async_trait]
+impl Transport for reqwest::Client {
+    async fn get(&self, request: HttpRequest) -> Result<HttpResponse, NetworkError> {
+        let mut builder = reqwest::Client::get(self, request.url());
+        for (name, value) in request.headers() {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .send()
+            .await
+            .map_err(|_| NetworkError(request.url().to_string()))?;
+
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(n, v)| Some((n.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|_| NetworkError(request.url().to_string()))?;
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: body.to_vec(),
+        })
+    }
+}
+
+/// The default [Transport] used when no other [Transport] was given.
+///
+/// This is the [Transport] built from the default [NetworkSettings][crate::NetworkSettings].
+pub fn default_transport() -> std::sync::Arc<dyn Transport> {
+    crate::NetworkSettings::new()
+        .build_transport()
+        .expect("The default network settings to be valid")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn transport_headers() {
+        let request = HttpRequest::new("https://example.com")
+            .with_header("If-None-Match", "\"etag\"")
+            .with_header("User-Agent", "test");
+        assert_eq!(request.url(), "https://example.com");
+        assert_eq!(request.header("if-none-match"), Some("\"etag\""));
+        assert_eq!(request.header("Accept"), None);
+
+        let response = HttpResponse::new(304, "").with_header("etag", "\"etag\"");
+        assert!(!response.is_success());
+        assert_eq!(response.header("ETag"), Some("\"etag\""));
+        assert!(HttpResponse::new(200, "body").is_success());
+        assert_eq!(HttpResponse::new(200, "body").text(), "body");
+    }
+}
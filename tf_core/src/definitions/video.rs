@@ -17,7 +17,7 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::Subscription;
+use crate::{HttpRequest, Subscription, Transport, VideoId, VideoMetadata};
 
 use async_trait::async_trait;
 use image::DynamicImage;
@@ -31,7 +31,7 @@ use {crate::mock::MockSubscription, mockall::predicate::*, mockall::*};
 /// - [std::clone::Clone]: Should just be derived.
 /// - [std::marker::Sync]: Already implemented by default if you do not make anything weird.
 /// - [std::marker::Send]: Similar to Sync probably also implemented by default.
-/// - [std::cmp::Eq]: Do only compare identifying information, e.g. id but not name if that can be reused.
+/// - [std::cmp::Eq]: Do only compare identifying information, e.g. the [Video::id] but not the title which can be edited.
 /// - [std::hash::Hash]: Hash only identifying information, similar to [std::cmp::PartialEq].
 /// - [std::convert::Into<Vec<String>>]: Serialize into a vec of strings. Do only serialize identifying information.
 /// - [std::convert::TryFrom<Vec<String>>]: Deserialize information similar to serialization.
@@ -63,37 +63,59 @@ pub trait Video:
     /// The url of the [Videos](Video) thumbnail.
     fn thumbnail_url(&self) -> String;
 
+    /// Additional information about the [Video] like the duration or the number of views.
+    ///
+    /// When not overwritten no additional information is available.
+    fn metadata(&self) -> VideoMetadata {
+        VideoMetadata::default()
+    }
+
+    /// The stable identifier of the [Video].
+    ///
+    /// When not overwritten, the [Video] will be identified by its [Video::url].
+    fn id(&self) -> VideoId {
+        VideoId::Url(self.url())
+    }
+
+    /// Update the [Video] with the information of `other`, the same [Video] fetched again.
+    ///
+    /// This is used when e.g. the title of a [Video] was edited.
+    /// Gives back whether anything changed.
+    ///
+    /// When not overwritten, the [Video] will be replaced if the [Video::title], [Video::thumbnail_url]
+    /// or [Video::metadata] changed.
+    fn update(&mut self, other: &Self) -> bool {
+        let changed = self.title() != other.title()
+            || self.thumbnail_url() != other.thumbnail_url()
+            || self.metadata() != other.metadata();
+        if changed {
+            *self = other.clone();
+        }
+        changed
+    }
+
     /// Get the thumbnail of the [Video].
     ///
-    /// The image should be fetched using the given [reqwest::Client].
+    /// The image should be fetched using the given [Transport].
     ///
     /// When not overwritten it will fetch the thumbnail from [Video::thumbnail_url] and guess the format.
-    async fn thumbnail_with_client(&self, client: &reqwest::Client) -> image::DynamicImage {
+    async fn thumbnail_with_client(&self, client: &dyn Transport) -> image::DynamicImage {
         let thumbnail_url = self.thumbnail_url();
         log::debug!("Getting thumbnail from url {}", thumbnail_url);
-        let response = client.get(&thumbnail_url).send().await;
-
-        if response.is_err() {
-            log::error!(
-                "Failed getting thumbnail for url {}, use default",
-                thumbnail_url
-            );
-            return self.default_thumbnail();
-        }
-
-        let parsed = response.unwrap().bytes().await;
-
-        if parsed.is_err() {
-            log::error!(
-                "Failed getting thumbnail for url {}, use default",
-                thumbnail_url
-            );
-            return self.default_thumbnail();
-        }
-
-        let parsed_bytes = parsed.unwrap();
-
-        if let Some(image) = <Self as Video>::convert_image(&parsed_bytes) {
+        let response = client.get(HttpRequest::new(&thumbnail_url)).await;
+
+        let response = match response {
+            Ok(response) if response.is_success() => response,
+            _ => {
+                log::error!(
+                    "Failed getting thumbnail for url {}, use default",
+                    thumbnail_url
+                );
+                return self.default_thumbnail();
+            }
+        };
+
+        if let Some(image) = <Self as Video>::convert_image(response.body()) {
             image
         } else {
             self.default_thumbnail()
@@ -104,7 +126,7 @@ pub trait Video:
     ///
     /// By default the format will be guessed.
     fn convert_image(data: &[u8]) -> Option<DynamicImage> {
-        image::load_from_memory(&data).ok()
+        image::load_from_memory(data).ok()
     }
 
     /// Get the default thumbnail, if not overwritten a transparent 1 by 1 pixel image.
@@ -114,11 +136,12 @@ pub trait Video:
 
     /// Get the thumbnail of the [Video].
     ///
-    /// The image will be fetched using [reqwest::Client::new].
+    /// The image will be fetched using the [default_transport][crate::default_transport].
     ///
     /// When not overwritten it will default to create a transparent picture.
     async fn thumbnail(&self) -> DynamicImage {
-        self.thumbnail_with_client(&reqwest::Client::new()).await
+        self.thumbnail_with_client(crate::default_transport().as_ref())
+            .await
     }
 }
 
@@ -0,0 +1,53 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// A stable identifier of a [Video][crate::Video].
+///
+/// In contrast to the url or title of a [Video][crate::Video], this does not change when the
+/// creator edits the [Video][crate::Video]. It is used to decide whether two
+/// [Video][crate::Video]s are the same, see [Video::id][crate::Video::id].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum VideoId {
+    /// The id of a YouTube video.
+    Youtube(String),
+    /// A PeerTube video on the given host, identified by its short UUID.
+    Peertube {
+        /// The host of the PeerTube instance.
+        host: String,
+        /// The short UUID of the video.
+        short_uuid: String,
+    },
+    /// The claim id of a LBRY video.
+    Lbry(String),
+    /// A video only identified by its url, used if no better identifier is known.
+    Url(String),
+}
+
+impl std::fmt::Display for VideoId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoId::Youtube(id) => write!(f, "youtube:{}", id),
+            VideoId::Peertube { host, short_uuid } => {
+                write!(f, "peertube:{}@{}", short_uuid, host)
+            }
+            VideoId::Lbry(id) => write!(f, "lbry:{}", id),
+            VideoId::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+/// Additional, optional information about a [Video][crate::Video].
+///
+/// Not every platform provides every information, missing information is `None` or `false`.
+/// Get it using [Video::metadata][crate::Video::metadata].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct VideoMetadata {
+    /// The duration in seconds.
+    duration: Option<u64>,
+    /// The number of views.
+    views: Option<u64>,
+    /// The description.
+    description: Option<String>,
+    /// Whether this is a livestream.
+    live: bool,
+    /// Whether this is a short.
+    short: bool,
+}
+
+impl VideoMetadata {
+    /// Create new [VideoMetadata] without any information.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the duration.
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration.map(|d| d.as_secs());
+        self
+    }
+
+    /// Set the number of views.
+    pub fn with_views(mut self, views: Option<u64>) -> Self {
+        self.views = views;
+        self
+    }
+
+    /// Set the description, an empty description will be ignored.
+    pub fn with_description<S: AsRef<str>>(mut self, description: Option<S>) -> Self {
+        self.description = description
+            .map(|d| d.as_ref().to_string())
+            .filter(|d| !d.is_empty());
+        self
+    }
+
+    /// Set whether this is a livestream.
+    pub fn with_live(mut self, live: bool) -> Self {
+        self.live = live;
+        self
+    }
+
+    /// Set whether this is a short.
+    pub fn with_short(mut self, short: bool) -> Self {
+        self.short = short;
+        self
+    }
+
+    /// The duration if known.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration.map(Duration::from_secs)
+    }
+
+    /// The number of views if known.
+    pub fn views(&self) -> Option<u64> {
+        self.views
+    }
+
+    /// The description if known.
+    pub fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    /// Whether this is a livestream.
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    /// Whether this is a short.
+    pub fn is_short(&self) -> bool {
+        self.short
+    }
+
+    /// Whether no information is available.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Parse the [VideoMetadata] from the strings given by [VideoMetadata::into_strings].
+    ///
+    /// This is meant to be used by [Video][crate::Video]s appending the [VideoMetadata] to their
+    /// serialization. Missing or invalid entries are ignored such that older serializations can
+    /// still be read.
+    pub fn from_strings(strings: &[String]) -> Self {
+        let get = |i: usize| strings.get(i).filter(|s| !s.is_empty());
+        VideoMetadata {
+            duration: get(0).and_then(|s| s.parse().ok()),
+            views: get(1).and_then(|s| s.parse().ok()),
+            live: get(2).map(|s| s == "true").unwrap_or_default(),
+            short: get(3).map(|s| s == "true").unwrap_or_default(),
+            description: get(4).cloned(),
+        }
+    }
+
+    /// Serialize the [VideoMetadata] into strings, see [VideoMetadata::from_strings].
+    ///
+    /// Empty [VideoMetadata] will be serialized into no strings at all.
+    pub fn into_strings(self) -> Vec<String> {
+        if self.is_empty() {
+            return vec![];
+        }
+        vec![
+            self.duration.map(|d| d.to_string()).unwrap_or_default(),
+            self.views.map(|v| v.to_string()).unwrap_or_default(),
+            self.live.to_string(),
+            self.short.to_string(),
+            self.description.unwrap_or_default(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn video_metadata_strings() {
+        let metadata = VideoMetadata::new()
+            .with_duration(Some(Duration::from_secs(90)))
+            .with_views(Some(1000))
+            .with_description(Some("Description, with comma"))
+            .with_short(true);
+        let strings = metadata.clone().into_strings();
+
+        assert_eq!(
+            strings,
+            vec!["90", "1000", "false", "true", "Description, with comma"]
+        );
+        assert_eq!(VideoMetadata::from_strings(&strings), metadata);
+        assert_eq!(metadata.duration(), Some(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn video_metadata_empty() {
+        assert!(VideoMetadata::new().into_strings().is_empty());
+        assert!(VideoMetadata::from_strings(&[]).is_empty());
+        assert!(VideoMetadata::new()
+            .with_description(Some(""))
+            .with_views(None)
+            .is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::time::Duration;
+
+/// The default fraction of the duration of a [Video][crate::Video] that has to be reached to
+/// mark it as watched, see [WatchProgress::report].
+pub const DEFAULT_WATCHED_THRESHOLD: f64 = 0.9;
+
+/// How far a [Video][crate::Video] was watched.
+///
+/// Get it using [ExpandedVideo::progress][crate::ExpandedVideo::progress].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WatchProgress {
+    /// The position to resume playing at in seconds.
+    position: u64,
+    /// The total time the [Video][crate::Video] was watched in seconds.
+    watched_time: u64,
+    /// Whether the [Video][crate::Video] was watched.
+    watched: bool,
+}
+
+impl WatchProgress {
+    /// Create a new [WatchProgress] of a unwatched [Video][crate::Video].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the position to resume playing at.
+    pub fn with_position(mut self, position: Duration) -> Self {
+        self.position = position.as_secs();
+        self
+    }
+
+    /// Set the total time the [Video][crate::Video] was watched.
+    pub fn with_watched_time(mut self, watched_time: Duration) -> Self {
+        self.watched_time = watched_time.as_secs();
+        self
+    }
+
+    /// Set whether the [Video][crate::Video] was watched.
+    pub fn with_watched(mut self, watched: bool) -> Self {
+        self.watched = watched;
+        self
+    }
+
+    /// The position to resume playing at.
+    pub fn position(&self) -> Duration {
+        Duration::from_secs(self.position)
+    }
+
+    /// The total time the [Video][crate::Video] was watched.
+    pub fn watched_time(&self) -> Duration {
+        Duration::from_secs(self.watched_time)
+    }
+
+    /// Whether the [Video][crate::Video] was watched.
+    pub fn is_watched(&self) -> bool {
+        self.watched
+    }
+
+    /// Whether the [Video][crate::Video] was never started nor marked as watched.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Report that the [Video][crate::Video] was played until `position`, watching it for
+    /// `watched_for` since the last report.
+    ///
+    /// If the [Video][crate::Video] has the given `duration` and the `position` reached the
+    /// `threshold` fraction of it (e.g. [DEFAULT_WATCHED_THRESHOLD]), it will be marked as watched.
+    /// A [Video][crate::Video] is never marked as unwatched by a report.
+    ///
+    /// Gives back whether the [Video][crate::Video] became watched by this report.
+    pub fn report(
+        &mut self,
+        position: Duration,
+        watched_for: Duration,
+        duration: Option<Duration>,
+        threshold: f64,
+    ) -> bool {
+        self.position = position.as_secs();
+        self.watched_time = self.watched_time.saturating_add(watched_for.as_secs());
+
+        let reached = duration
+            .filter(|d| !d.is_zero())
+            .map(|d| position.as_secs_f64() >= d.as_secs_f64() * threshold)
+            .unwrap_or_default();
+        let became_watched = reached && !self.watched;
+        self.watched |= reached;
+        became_watched
+    }
+
+    /// Parse the [WatchProgress] from the string given by [WatchProgress::to_string].
+    pub(crate) fn from_string(string: &str) -> Option<Self> {
+        let mut parts = string.split(':');
+        let progress = WatchProgress {
+            position: parts.next()?.parse().ok()?,
+            watched_time: parts.next()?.parse().ok()?,
+            watched: parts.next()?.parse().ok()?,
+        };
+        parts.next().is_none().then_some(progress)
+    }
+}
+
+impl std::fmt::Display for WatchProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.position, self.watched_time, self.watched
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn watch_progress_report() {
+        let duration = Some(Duration::from_secs(100));
+        let mut progress = WatchProgress::new();
+
+        assert!(!progress.report(
+            Duration::from_secs(50),
+            Duration::from_secs(50),
+            duration,
+            DEFAULT_WATCHED_THRESHOLD
+        ));
+        assert!(!progress.is_watched());
+        assert_eq!(progress.position(), Duration::from_secs(50));
+
+        assert!(progress.report(
+            Duration::from_secs(95),
+            Duration::from_secs(45),
+            duration,
+            DEFAULT_WATCHED_THRESHOLD
+        ));
+        assert!(progress.is_watched());
+        assert_eq!(progress.watched_time(), Duration::from_secs(95));
+
+        assert!(!progress.report(
+            Duration::from_secs(10),
+            Duration::from_secs(10),
+            duration,
+            DEFAULT_WATCHED_THRESHOLD
+        ));
+        assert!(progress.is_watched());
+        assert_eq!(progress.position(), Duration::from_secs(10));
+
+        let mut unknown = WatchProgress::new();
+        assert!(!unknown.report(
+            Duration::from_secs(1000),
+            Duration::from_secs(1000),
+            None,
+            DEFAULT_WATCHED_THRESHOLD
+        ));
+        assert!(!unknown.is_watched());
+    }
+
+    #[test]
+    fn watch_progress_string() {
+        let progress = WatchProgress::new()
+            .with_position(Duration::from_secs(12))
+            .with_watched_time(Duration::from_secs(30))
+            .with_watched(true);
+        assert_eq!(progress.to_string(), "12:30:true");
+        assert_eq!(
+            WatchProgress::from_string(&progress.to_string()),
+            Some(progress)
+        );
+        assert_eq!(WatchProgress::from_string("12:30"), None);
+        assert_eq!(WatchProgress::from_string("12:30:true:1"), None);
+        assert_eq!(WatchProgress::from_string("https://example.com"), None);
+    }
+}
@@ -20,14 +20,20 @@
 //! The Errors used in this crate.
 //!
 //! Errors can currently only occur when something can not be parsed or a url on the web cannot be reached.
+//! Every [Error] in a [ErrorStore] is accompanied by a [ErrorContext] describing where and when it occured.
 
 use std::{
+    any::Any,
+    collections::HashMap,
     fmt,
     sync::{Arc, Mutex},
 };
 
+use chrono::NaiveDateTime;
 use tf_observer::{Observable, Observer, ObserverList};
 
+use crate::Subscription;
+
 /// The collection of all errors that can occur.
 #[derive(Debug, Clone)]
 pub enum Error {
@@ -68,6 +74,23 @@ impl fmt::Display for NetworkError {
     }
 }
 
+impl Error {
+    /// Whether the request leading to this [Error] may succeed when retried.
+    ///
+    /// [ParseError]s are never retryable. [NetworkError]s are retryable if no response was
+    /// received or the HTTP status of the [ErrorContext] signals a temporary failure,
+    /// e.g. `429 Too Many Requests` or any server error.
+    pub fn is_retryable(&self, context: &ErrorContext) -> bool {
+        match self {
+            Error::ParseError(_) => false,
+            Error::NetworkError(_) => match context.status() {
+                None => true,
+                Some(status) => status == 408 || status == 429 || status >= 500,
+            },
+        }
+    }
+}
+
 impl std::error::Error for Error {}
 impl std::error::Error for ParseError {}
 impl std::error::Error for NetworkError {}
@@ -84,14 +107,125 @@ impl From<NetworkError> for Error {
     }
 }
 
+/// Information about where and when a [Error] occured.
+#[derive(Clone)]
+pub struct ErrorContext {
+    /// The [Subscription] that failed to generate, stored type-erased.
+    subscription: Option<Arc<dyn Any + Send + Sync>>,
+    /// The url that was requested.
+    url: Option<String>,
+    /// The HTTP status of the response, if any response was received.
+    status: Option<u16>,
+    /// When the [Error] occured.
+    time: NaiveDateTime,
+}
+
+impl ErrorContext {
+    /// Create a new [ErrorContext] occuring now without any further information.
+    pub fn new() -> Self {
+        ErrorContext {
+            subscription: None,
+            url: None,
+            status: None,
+            time: chrono::Local::now().naive_local(),
+        }
+    }
+
+    /// Set the [Subscription] the [Error] originated from.
+    pub fn with_subscription<S: Subscription + 'static>(mut self, subscription: &S) -> Self {
+        self.subscription = Some(Arc::new(subscription.clone()));
+        self
+    }
+
+    /// Set the url that was requested.
+    pub fn with_url<U: AsRef<str>>(mut self, url: U) -> Self {
+        self.url = Some(url.as_ref().to_string());
+        self
+    }
+
+    /// Set the HTTP status of the response.
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Get the [Subscription] the [Error] originated from, if it is of type `S`.
+    pub fn subscription<S: Subscription + 'static>(&self) -> Option<S> {
+        self.subscription
+            .as_ref()
+            .and_then(|s| s.downcast_ref::<S>())
+            .cloned()
+    }
+
+    /// Whether the [Error] originated from the given [Subscription].
+    pub fn is_subscription<S: Subscription + 'static>(&self, subscription: &S) -> bool {
+        self.subscription
+            .as_ref()
+            .and_then(|s| s.downcast_ref::<S>())
+            .map(|s| s == subscription)
+            .unwrap_or(false)
+    }
+
+    /// Whether any [Subscription] is known for the [Error].
+    pub fn has_subscription(&self) -> bool {
+        self.subscription.is_some()
+    }
+
+    /// Get the url that was requested.
+    pub fn url(&self) -> Option<String> {
+        self.url.clone()
+    }
+
+    /// Get the HTTP status of the response.
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// Get when the [Error] occured.
+    pub fn time(&self) -> NaiveDateTime {
+        self.time
+    }
+}
+
+impl Default for ErrorContext {
+    fn default() -> Self {
+        ErrorContext::new()
+    }
+}
+
+impl fmt::Debug for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorContext")
+            .field("subscription", &self.subscription.is_some())
+            .field("url", &self.url)
+            .field("status", &self.status)
+            .field("time", &self.time)
+            .finish()
+    }
+}
+
+/// The number of consecutive failed refreshes of a [Subscription].
+struct FailureStreak {
+    /// The [Subscription], stored type-erased.
+    subscription: Arc<dyn Any + Send + Sync>,
+    /// The number of consecutive failed refreshes.
+    failures: usize,
+}
+
 /// A [Observable] holding a list of [Error]s.
+///
+/// Besides the [Error]s, the [ErrorStore] keeps track of how many consecutive refreshes of a
+/// [Subscription] failed (see [ErrorStore::record_refresh]). This is not reset by [ErrorStore::clear].
 #[derive(Clone)]
 pub struct ErrorStore {
     /// The observers.
     observers: ObserverList<ErrorEvent>,
 
     /// The errors.
-    errors: Arc<Mutex<Vec<Error>>>,
+    errors: Arc<Mutex<Vec<(Error, ErrorContext)>>>,
+
+    /// The consecutive failures of the [Subscription]s.
+    streaks: Arc<Mutex<Vec<FailureStreak>>>,
 }
 
 impl ErrorStore {
@@ -99,14 +233,25 @@ impl ErrorStore {
     pub fn new() -> Self {
         ErrorStore {
             errors: Arc::new(Mutex::new(vec![])),
+            streaks: Arc::new(Mutex::new(vec![])),
             observers: ObserverList::new(),
         }
     }
 
-    /// Add the given [Error] to the store.
+    /// Add the given [Error] to the store with a [ErrorContext] only holding the current time.
     /// Will notify the observers using [ErrorEvent::Add].
     pub fn add(&self, error: Error) {
-        self.errors.lock().unwrap().push(error.clone());
+        self.add_with_context(error, ErrorContext::new())
+    }
+
+    /// Add the given [Error] with the given [ErrorContext] to the store.
+    /// Will notify the observers using [ErrorEvent::Add], the [ErrorContext] can be queried
+    /// using [ErrorStore::last_with_context].
+    pub fn add_with_context(&self, error: Error, context: ErrorContext) {
+        self.errors
+            .lock()
+            .unwrap()
+            .push((error.clone(), context.clone()));
         self.observers.notify(ErrorEvent::Add(error))
     }
 
@@ -119,9 +264,87 @@ impl ErrorStore {
 
     /// Iterate over a copy of all errors.
     pub fn iter(&self) -> impl Iterator<Item = Error> {
+        self.iter_with_context().map(|(e, _)| e)
+    }
+
+    /// Iterate over a copy of all errors together with their [ErrorContext].
+    pub fn iter_with_context(&self) -> impl Iterator<Item = (Error, ErrorContext)> {
         self.errors.lock().unwrap().clone().into_iter()
     }
 
+    /// Get the last added error together with its [ErrorContext].
+    pub fn last_with_context(&self) -> Option<(Error, ErrorContext)> {
+        self.errors.lock().unwrap().last().cloned()
+    }
+
+    /// Get all errors originating from the given [Subscription].
+    pub fn errors_of<S: Subscription + 'static>(
+        &self,
+        subscription: &S,
+    ) -> Vec<(Error, ErrorContext)> {
+        self.iter_with_context()
+            .filter(|(_, c)| c.is_subscription(subscription))
+            .collect()
+    }
+
+    /// Count the errors per [Subscription] of type `S`.
+    pub fn errors_per_subscription<S: Subscription + 'static>(&self) -> HashMap<S, usize> {
+        let mut result = HashMap::new();
+        for (_, context) in self.iter_with_context() {
+            if let Some(subscription) = context.subscription::<S>() {
+                *result.entry(subscription).or_insert(0) += 1;
+            }
+        }
+        result
+    }
+
+    /// Record the outcome of refreshing the given [Subscription].
+    ///
+    /// A failed refresh will increase the number of consecutive failures of the [Subscription],
+    /// a successful one will reset it.
+    pub fn record_refresh<S: Subscription + 'static>(&self, subscription: &S, failed: bool) {
+        let mut streaks = self.streaks.lock().unwrap();
+        let position = streaks
+            .iter()
+            .position(|s| s.subscription.downcast_ref::<S>() == Some(subscription));
+        match (position, failed) {
+            (Some(i), true) => streaks[i].failures += 1,
+            (Some(i), false) => {
+                streaks.remove(i);
+            }
+            (None, true) => streaks.push(FailureStreak {
+                subscription: Arc::new(subscription.clone()),
+                failures: 1,
+            }),
+            (None, false) => {}
+        }
+    }
+
+    /// Get the number of consecutive failed refreshes of the given [Subscription].
+    pub fn consecutive_failures<S: Subscription + 'static>(&self, subscription: &S) -> usize {
+        self.streaks
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|s| s.subscription.downcast_ref::<S>() == Some(subscription))
+            .map(|s| s.failures)
+            .unwrap_or(0)
+    }
+
+    /// Get all [Subscription]s of type `S` whose last `n` refreshes failed.
+    ///
+    /// This can be used to flag dead channels.
+    pub fn failing_subscriptions<S: Subscription + 'static>(&self, n: usize) -> Vec<S> {
+        self.streaks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.failures >= n)
+            .filter_map(|s| s.subscription.downcast_ref::<S>())
+            .cloned()
+            .collect()
+    }
+
     /// Give a [ErrorSummary] of the [ErrorStore], e.g. how many of each [Error]-type are inside the store.
     pub fn summary(&self) -> ErrorSummary {
         let parse = self
@@ -186,6 +409,8 @@ impl Observable<ErrorEvent> for ErrorStore {
 mod test {
     use super::*;
 
+    use crate::mock::MockSubscription;
+
     fn add_network_error(store: &ErrorStore) {
         store.add(NetworkError("Url".to_owned()).into())
     }
@@ -213,6 +438,78 @@ mod test {
         assert_eq!(store.iter().count(), 0);
     }
 
+    #[test]
+    fn errorstore_errors_of() {
+        let mut subscription1 = MockSubscription::default();
+        let subscription2 = MockSubscription::default();
+        subscription1.expect_clone().returning(|| {
+            let mut s = MockSubscription::default();
+            s.expect_eq().returning(|_| true);
+            s
+        });
+
+        let store = ErrorStore::new();
+        add_network_error(&store);
+        store.add_with_context(
+            ParseError("Parse".to_owned()).into(),
+            ErrorContext::new()
+                .with_subscription(&subscription1)
+                .with_url("https://example.com")
+                .with_status(404),
+        );
+
+        let (_, context) = store.last_with_context().unwrap();
+        assert_eq!(context.status(), Some(404));
+
+        let errors = store.errors_of(&subscription2);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1.url(), Some("https://example.com".to_owned()));
+        assert_eq!(errors[0].1.status(), Some(404));
+    }
+
+    fn make_subscription() -> MockSubscription {
+        let mut subscription = MockSubscription::default();
+        subscription.expect_eq().returning(|_| true);
+        subscription.expect_clone().returning(make_subscription);
+        subscription
+    }
+
+    #[test]
+    fn errorstore_failing_subscriptions() {
+        let subscription = make_subscription();
+
+        let store = ErrorStore::new();
+        store.record_refresh(&subscription, true);
+        store.record_refresh(&subscription, true);
+        store.clear();
+        store.record_refresh(&subscription, true);
+
+        assert_eq!(store.consecutive_failures(&subscription), 3);
+        assert_eq!(store.failing_subscriptions::<MockSubscription>(3).len(), 1);
+        assert!(store
+            .failing_subscriptions::<MockSubscription>(4)
+            .is_empty());
+
+        store.record_refresh(&subscription, false);
+
+        assert_eq!(store.consecutive_failures(&subscription), 0);
+        assert!(store
+            .failing_subscriptions::<MockSubscription>(1)
+            .is_empty());
+    }
+
+    #[test]
+    fn error_retryable() {
+        let network: Error = NetworkError("Url".to_owned()).into();
+        let parse: Error = ParseError("Parse".to_owned()).into();
+
+        assert!(network.is_retryable(&ErrorContext::new()));
+        assert!(network.is_retryable(&ErrorContext::new().with_status(503)));
+        assert!(network.is_retryable(&ErrorContext::new().with_status(429)));
+        assert!(!network.is_retryable(&ErrorContext::new().with_status(404)));
+        assert!(!parse.is_retryable(&ErrorContext::new()));
+    }
+
     #[test]
     fn errorstore_summary() {
         let store = ErrorStore::new();
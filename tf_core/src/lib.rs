@@ -24,6 +24,8 @@
 //!
 //! - [Video]
 //! - [ExpandedVideo]
+//! - [VideoMetadata]
+//! - [WatchProgress]
 //! - [Subscription]
 //! - [SubscriptionList]
 //!
@@ -35,16 +37,31 @@ mod pipeline;
 
 pub use definitions::expanded_video::ExpandedVideo;
 pub use definitions::expanded_video::VideoEvent;
-pub use definitions::generator::{Generator, GeneratorWithClient};
+pub use definitions::generator::{
+    Generator, GeneratorWithClient, Page, PageCursor, PagedGenerator, StreamGenerator,
+};
+pub use definitions::refresh_state::RefreshState;
 pub use definitions::subscription::Subscription;
+pub use definitions::transport::{default_transport, HttpRequest, HttpResponse, Transport};
 pub use definitions::video::Video;
-pub use error::{Error, ErrorEvent, ErrorStore, ErrorSummary, NetworkError, ParseError};
+pub use definitions::video_id::VideoId;
+pub use definitions::video_metadata::VideoMetadata;
+pub use definitions::watch_progress::{WatchProgress, DEFAULT_WATCHED_THRESHOLD};
+pub use error::{
+    Error, ErrorContext, ErrorEvent, ErrorStore, ErrorSummary, NetworkError, ParseError,
+};
 pub use pipeline::expander::Expander;
+pub use pipeline::fetch_policy::FetchPolicy;
+pub use pipeline::file_backend::FileVideoStoreBackend;
 pub use pipeline::merger::Merger;
+pub use pipeline::network_settings::{NetworkSettings, Proxy};
 pub use pipeline::pipe::Pipeline;
+pub use pipeline::scheduler::{SchedulePolicy, Scheduler};
 pub use pipeline::store_access::StoreAccess;
 pub use pipeline::subscription_list::SubscriptionList;
-pub use pipeline::video_store::VideoStore;
+pub use pipeline::thumbnail_cache::{ThumbnailCache, ThumbnailSize};
+pub use pipeline::video_store::{VideoStore, VideoStoreBackend};
+pub use pipeline::window::{Window, WindowCursor};
 
 #[cfg(test)]
 mod mock {
@@ -54,7 +71,7 @@ mod mock {
 }
 
 pub mod prelude {
-    pub use crate::{Generator, Subscription, Video};
+    pub use crate::{Generator, StreamGenerator, Subscription, Video};
 }
 
 /// The date format that has to be used when serializing or deserializing [Videos](Video) or [Subscriptions](Subscription).
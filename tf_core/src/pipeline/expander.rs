@@ -17,11 +17,13 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{ErrorStore, ExpandedVideo, Generator, Video};
+use crate::{ErrorStore, ExpandedVideo, Generator, StreamGenerator, Video};
 
 use std::marker::PhantomData;
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 
 /// A [Pipeline][crate::Pipeline]-component expanding [Video]s `V` into
 /// [ExpandedVideo]s.
@@ -68,3 +70,18 @@ where
         mapped_iterator
     }
 }
+
+impl<V, G> StreamGenerator for Expander<V, G>
+where
+    G: StreamGenerator<Item = V> + std::marker::Sync + std::marker::Send,
+    V: Video,
+{
+    type Item = ExpandedVideo<V>;
+
+    fn generate_stream<'a>(&'a self, errors: &'a ErrorStore) -> BoxStream<'a, Vec<Self::Item>> {
+        self.generator
+            .generate_stream(errors)
+            .map(|batch| batch.into_iter().map(ExpandedVideo::from).collect())
+            .boxed()
+    }
+}
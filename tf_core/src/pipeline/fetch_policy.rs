@@ -0,0 +1,209 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+/// The default maximum amount of [Subscription][crate::Subscription]s fetched at once.
+const DEFAULT_CONCURRENCY: usize = 10;
+/// The default amount of retries of a [Subscription][crate::Subscription] failing with a [NetworkError][crate::NetworkError].
+const DEFAULT_RETRIES: u32 = 2;
+/// The default backoff before the first retry.
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The policy of the [Merger][crate::Merger] on how to fetch the [Subscription][crate::Subscription]s.
+///
+/// This configures:
+///
+/// - The maximum amount of [Subscription][crate::Subscription]s fetched at once.
+/// - The minimum interval in between two requests to the same host, either for all hosts or per host.
+/// - The amount of retries with exponential backoff when a [NetworkError][crate::NetworkError] occurs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchPolicy {
+    /// The maximum amount of [Subscription][crate::Subscription]s fetched at once.
+    concurrency: usize,
+    /// The minimum interval in between two requests to the same host.
+    host_interval: Duration,
+    /// The minimum interval in between two requests to a specific host, overwriting `host_interval`.
+    host_intervals: HashMap<String, Duration>,
+    /// The amount of retries.
+    retries: u32,
+    /// The backoff before the first retry, doubled for every further retry.
+    backoff: Duration,
+}
+
+impl FetchPolicy {
+    /// Create a new [FetchPolicy] with the default values.
+    pub fn new() -> Self {
+        FetchPolicy {
+            concurrency: DEFAULT_CONCURRENCY,
+            host_interval: Duration::ZERO,
+            host_intervals: HashMap::new(),
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+
+    /// Set the maximum amount of [Subscription][crate::Subscription]s fetched at once.
+    ///
+    /// A value of `0` will be treated as `1`.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the minimum interval in between two requests to the same host.
+    pub fn with_host_interval(mut self, interval: Duration) -> Self {
+        self.host_interval = interval;
+        self
+    }
+
+    /// Set the minimum interval in between two requests to the given host, e.g. `odysee.com`.
+    pub fn with_interval_for_host<S: AsRef<str>>(mut self, host: S, interval: Duration) -> Self {
+        self.host_intervals
+            .insert(host.as_ref().to_lowercase(), interval);
+        self
+    }
+
+    /// Set the amount of retries when a [NetworkError][crate::NetworkError] occurs.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set the backoff before the first retry. Every further retry will double the backoff.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Get the maximum amount of [Subscription][crate::Subscription]s fetched at once.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Get the minimum interval in between two requests to the given host.
+    pub fn host_interval<S: AsRef<str>>(&self, host: S) -> Duration {
+        self.host_intervals
+            .get(&host.as_ref().to_lowercase())
+            .copied()
+            .unwrap_or(self.host_interval)
+    }
+
+    /// Get the amount of retries when a [NetworkError][crate::NetworkError] occurs.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Get the backoff before the retry with the given number, starting at `0`.
+    ///
+    /// This is the exponential backoff plus a random jitter of up to half the exponential backoff.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self.backoff.saturating_mul(2u32.saturating_pow(retry));
+        let jitter = rand::thread_rng().gen_range(0.0..=0.5);
+        exponential + exponential.mul_f64(jitter)
+    }
+}
+
+impl Default for FetchPolicy {
+    fn default() -> Self {
+        FetchPolicy::new()
+    }
+}
+
+/// Limit the rate of requests per host.
+///
+/// Cloning the [RateLimiter] will still share the same limits.
+#[derive(Clone, Default)]
+pub(crate) struct RateLimiter {
+    /// The next time a request to the host is allowed.
+    next: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl RateLimiter {
+    /// Wait until a request to the given host is allowed and reserve the next slot
+    /// after the given interval.
+    pub(crate) async fn wait(&self, host: &str, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+
+        let wait = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            let slot = next.get(host).copied().filter(|n| n > &now).unwrap_or(now);
+            next.insert(host.to_owned(), slot + interval);
+            slot - now
+        };
+
+        if !wait.is_zero() {
+            log::debug!("Waiting {:?} for host {}", wait, host);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fetch_policy_host_interval() {
+        let policy = FetchPolicy::new()
+            .with_host_interval(Duration::from_secs(1))
+            .with_interval_for_host("Odysee.com", Duration::from_secs(2));
+
+        assert_eq!(policy.host_interval("odysee.com"), Duration::from_secs(2));
+        assert_eq!(policy.host_interval("example.com"), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn fetch_policy_backoff() {
+        let policy = FetchPolicy::new().with_backoff(Duration::from_millis(100));
+
+        for retry in 0..4 {
+            let exponential = Duration::from_millis(100 * 2u64.pow(retry));
+            let backoff = policy.backoff(retry);
+            assert!(backoff >= exponential);
+            assert!(backoff <= exponential.mul_f64(1.5));
+        }
+    }
+
+    #[test]
+    fn fetch_policy_concurrency_not_zero() {
+        assert_eq!(FetchPolicy::new().with_concurrency(0).concurrency(), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_spacing() {
+        let limiter = RateLimiter::default();
+        let interval = Duration::from_millis(50);
+
+        let start = Instant::now();
+        limiter.wait("example.com", interval).await;
+        limiter.wait("other.com", interval).await;
+        assert!(start.elapsed() < interval);
+
+        limiter.wait("example.com", interval).await;
+        assert!(start.elapsed() >= interval);
+    }
+}
@@ -0,0 +1,169 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::convert::TryFrom;
+use std::fs::OpenOptions;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::pipeline::video_store::VideoStoreBackend;
+use crate::DATE_FORMAT;
+
+/// A [VideoStoreBackend] storing the [Video][crate::Video]s in a append-only csv-file.
+///
+/// Every row consists of the time the [Video][crate::Video] was first seen followed by the
+/// serialized [Video][crate::Video] (see [Into<Vec<String>>]).
+/// The file is only rewritten completely when [VideoStoreBackend::replace] is called, e.g. when
+/// pruning or when the [VideoStore][crate::VideoStore] compacts the outdated rows of updated
+/// [Video][crate::Video]s.
+pub struct FileVideoStoreBackend<V> {
+    /// The path of the file.
+    path: PathBuf,
+
+    /// Phantom data.
+    _phantom: PhantomData<fn() -> V>,
+}
+
+impl<V> FileVideoStoreBackend<V> {
+    /// Create a new [FileVideoStoreBackend] using the file at the given path.
+    ///
+    /// The file will be created when the first [Video][crate::Video] is inserted.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        FileVideoStoreBackend {
+            path: path.as_ref().to_path_buf(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the path of the file.
+    pub fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// Convert a [Video][crate::Video] with the time it was first seen into a row.
+fn to_row<V: Clone + Into<Vec<String>>>(
+    video: &V,
+    first_seen: chrono::NaiveDateTime,
+) -> Vec<String> {
+    let mut row = vec![first_seen.format(DATE_FORMAT).to_string()];
+    row.append(&mut video.clone().into());
+    row
+}
+
+/// Convert a row into a [Video][crate::Video] with the time it was first seen.
+fn from_row<V: TryFrom<Vec<String>>>(mut row: Vec<String>) -> Option<(V, chrono::NaiveDateTime)> {
+    if row.is_empty() {
+        return None;
+    }
+    let first_seen = chrono::NaiveDateTime::parse_from_str(&row.remove(0), DATE_FORMAT).ok()?;
+    V::try_from(row).ok().map(|v| (v, first_seen))
+}
+
+impl<V> VideoStoreBackend<V> for FileVideoStoreBackend<V>
+where
+    V: Clone + Into<Vec<String>> + TryFrom<Vec<String>>,
+{
+    fn load(&mut self) -> Vec<(V, chrono::NaiveDateTime)> {
+        if !self.path.exists() {
+            return vec![];
+        }
+
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(&self.path);
+
+        if let Err(e) = &reader {
+            log::error!("Failed reading video store at {:?}: {}", self.path, e);
+            return vec![];
+        }
+
+        reader
+            .unwrap()
+            .records()
+            .filter_map(|r| {
+                if let Err(e) = &r {
+                    log::error!(
+                        "Failed reading row of video store at {:?}: {}",
+                        self.path,
+                        e
+                    );
+                }
+                r.ok()
+            })
+            .filter_map(|r| {
+                let row = r.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+                let parsed = from_row(row);
+                if parsed.is_none() {
+                    log::warn!("Failed parsing row of video store at {:?}", self.path);
+                }
+                parsed
+            })
+            .collect()
+    }
+
+    fn insert(&mut self, video: &V, first_seen: chrono::NaiveDateTime) {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path);
+
+        if let Err(e) = &file {
+            log::error!("Failed opening video store at {:?}: {}", self.path, e);
+            return;
+        }
+
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_writer(file.unwrap());
+
+        if let Err(e) = writer
+            .write_record(to_row(video, first_seen))
+            .and_then(|_| writer.flush().map_err(|e| e.into()))
+        {
+            log::error!("Failed writing video store at {:?}: {}", self.path, e);
+        }
+    }
+
+    fn replace(&mut self, videos: &[(V, chrono::NaiveDateTime)]) {
+        let writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(&self.path);
+
+        if let Err(e) = &writer {
+            log::error!("Failed opening video store at {:?}: {}", self.path, e);
+            return;
+        }
+
+        let mut writer = writer.unwrap();
+        for (video, first_seen) in videos {
+            if let Err(e) = writer.write_record(to_row(video, *first_seen)) {
+                log::error!("Failed writing video store at {:?}: {}", self.path, e);
+                return;
+            }
+        }
+
+        if let Err(e) = writer.flush() {
+            log::error!("Failed writing video store at {:?}: {}", self.path, e);
+        }
+    }
+}
@@ -17,36 +17,449 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::{ErrorStore, Generator, GeneratorWithClient};
+use crate::pipeline::fetch_policy::RateLimiter;
+use crate::{
+    ErrorContext, ErrorStore, FetchPolicy, Generator, GeneratorWithClient, Page, PageCursor,
+    PagedGenerator, RefreshState, StreamGenerator, Transport,
+};
 use crate::{Subscription, SubscriptionList, Video};
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+
+/// The last fetched feed of a [Subscription].
+struct Feed<V> {
+    /// The [RefreshState] after the last fetch.
+    state: RefreshState,
+    /// The [Video]s of the last fetch.
+    videos: Vec<V>,
+}
 
 /// Merge the [Video]s generated from the [Subscription]s in the [SubscriptionList]
 /// into one [Iterator][std::iter::Iterator].
+///
+/// For every [Subscription], the [Merger] remembers the last fetched feed together with its
+/// [RefreshState]. This is used to send conditional requests (if supported by the [Subscription])
+/// when [generating][Generator::generate] the [Video]s and to [refresh][Merger::refresh]
+/// incrementally, only yielding [Video]s that were not seen before.
+///
+/// How the [Subscription]s are fetched (concurrency, rate limits and retries) is
+/// configured using the [FetchPolicy], the requests are sent using the [Transport]
+/// (see [Merger::set_transport]).
 #[derive(Clone)]
 pub struct Merger<S, V> {
     /// The list of [Subscription]s.
     subscription_list: Arc<Mutex<SubscriptionList<S>>>,
 
-    /// Phantom data.
-    _phantom: std::marker::PhantomData<V>,
+    /// The last fetched [Feed]s of the [Subscription]s.
+    feeds: Arc<Mutex<HashMap<S, Feed<V>>>>,
+
+    /// The [FetchPolicy] used to fetch the [Subscription]s.
+    fetch_policy: Arc<Mutex<FetchPolicy>>,
+
+    /// The [RateLimiter] limiting the requests per host.
+    rate_limiter: RateLimiter,
+
+    /// The [Transport] used to fetch the [Subscription]s.
+    transport: Arc<Mutex<Arc<dyn Transport>>>,
 }
 
 impl<S, V> Merger<S, V>
 where
-    S: Subscription<Video = V> + GeneratorWithClient<Item = V>,
+    S: 'static + Subscription<Video = V> + GeneratorWithClient<Item = V>,
     V: Video<Subscription = S>,
     <S as GeneratorWithClient>::Iterator: 'static + std::marker::Send,
 {
     /// Create a new [Merger] using the given [SubscriptionList].
     pub fn new(subscriptions: Arc<Mutex<SubscriptionList<S>>>) -> Self {
+        Merger::new_with_fetch_policy(subscriptions, Arc::new(Mutex::new(FetchPolicy::new())))
+    }
+
+    /// Create a new [Merger] using the given [SubscriptionList] and [FetchPolicy].
+    ///
+    /// Modifying the [FetchPolicy] will also alter how the [Merger] fetches the [Subscription]s.
+    pub fn new_with_fetch_policy(
+        subscriptions: Arc<Mutex<SubscriptionList<S>>>,
+        fetch_policy: Arc<Mutex<FetchPolicy>>,
+    ) -> Self {
         Merger {
             subscription_list: subscriptions,
-            _phantom: std::marker::PhantomData,
+            feeds: Arc::new(Mutex::new(HashMap::new())),
+            fetch_policy,
+            rate_limiter: RateLimiter::default(),
+            transport: Arc::new(Mutex::new(crate::default_transport())),
+        }
+    }
+
+    /// Get the [FetchPolicy] used to fetch the [Subscription]s.
+    pub fn fetch_policy(&self) -> Arc<Mutex<FetchPolicy>> {
+        self.fetch_policy.clone()
+    }
+
+    /// Get the [Transport] used to fetch the [Subscription]s.
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        self.transport.lock().unwrap().clone()
+    }
+
+    /// Set the [Transport] used to fetch the [Subscription]s.
+    ///
+    /// This will also alter the [Transport] of all clones of this [Merger].
+    pub fn set_transport(&self, transport: Arc<dyn Transport>) {
+        *self.transport.lock().unwrap() = transport;
+    }
+
+    /// Fetch the [Feed]s of all given [Subscription]s according to the [FetchPolicy],
+    /// see [Merger::fetch_feed].
+    ///
+    /// The results are in the same order as the given [Subscription]s.
+    async fn fetch_feeds(&self, errors: &ErrorStore, subscriptions: &[S]) -> Vec<(Vec<V>, Vec<V>)> {
+        let policy = self.fetch_policy.lock().unwrap().clone();
+        let client = self.transport();
+        let fetches = subscriptions
+            .iter()
+            .map(|s| self.fetch_feed(errors, client.as_ref(), &policy, s))
+            .collect::<Vec<_>>();
+        futures::stream::iter(fetches)
+            .buffered(policy.concurrency())
+            .collect()
+            .await
+    }
+
+    /// Fetch the feed of one [Subscription] according to the [FetchPolicy].
+    ///
+    /// Gives back all [Video]s of the feed together with the ones that were not seen in the
+    /// previous fetch. If the [Subscription] reports that its feed was not modified since the
+    /// previous fetch, i.e. it gives back no [Video]s without changing the validators of the
+    /// [RefreshState], the [Video]s of the previous fetch are given back.
+    async fn fetch_feed(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        policy: &FetchPolicy,
+        subscription: &S,
+    ) -> (Vec<V>, Vec<V>) {
+        let state = self.refresh_state(subscription).unwrap_or_default();
+        let (iter, new_state, failed) = self
+            .fetch_one(errors, client, policy, subscription, &state)
+            .await;
+        let videos = iter.collect::<Vec<_>>();
+
+        if failed {
+            return (videos, vec![]);
+        }
+        if videos.is_empty() && state.has_validators() && new_state == state {
+            log::debug!("Feed of subscription {} was not modified", subscription);
+            let feeds = self.feeds.lock().unwrap();
+            let videos = feeds
+                .get(subscription)
+                .map(|f| f.videos.clone())
+                .unwrap_or_default();
+            return (videos, vec![]);
+        }
+
+        let new = self.record(subscription, &videos, new_state);
+        (videos, new)
+    }
+
+    /// Fetch one [Subscription] given its [RefreshState] according to the [FetchPolicy].
+    ///
+    /// When fetching fails with a retryable [Error] (see [Error::is_retryable][crate::Error::is_retryable]), it will be retried
+    /// with exponential backoff. Only the [Error]s of the last try will be put into the [ErrorStore],
+    /// with the [Subscription] added to their [ErrorContext][crate::ErrorContext].
+    /// The outcome of the fetch is recorded using [ErrorStore::record_refresh] and given back
+    /// as the last value, `true` meaning the fetch failed.
+    async fn fetch_one(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        policy: &FetchPolicy,
+        subscription: &S,
+        state: &RefreshState,
+    ) -> (<S as GeneratorWithClient>::Iterator, RefreshState, bool) {
+        let mut retry = 0;
+        loop {
+            if let Some(host) = subscription.host() {
+                self.rate_limiter
+                    .wait(&host, policy.host_interval(&host))
+                    .await;
+            }
+
+            let try_errors = ErrorStore::new();
+            let (iter, state) = subscription
+                .generate_incremental_with_client(&try_errors, client, state)
+                .await;
+            let retryable = try_errors
+                .iter_with_context()
+                .any(|(e, c)| e.is_retryable(&c));
+
+            if retryable && retry < policy.retries() {
+                let backoff = policy.backoff(retry);
+                log::debug!(
+                    "Failed fetching subscription {}, retrying in {:?}",
+                    subscription,
+                    backoff
+                );
+                tokio::time::sleep(backoff).await;
+                retry += 1;
+                continue;
+            }
+
+            let mut failed = false;
+            for (error, context) in try_errors.iter_with_context() {
+                let context = if context.has_subscription() {
+                    context
+                } else {
+                    context.with_subscription(subscription)
+                };
+                errors.add_with_context(error, context);
+                failed = true;
+            }
+            errors.record_refresh(subscription, failed);
+            return (iter, state, failed);
+        }
+    }
+
+    /// Fetch the [Page]s of older [Video]s of the given [Subscription]s at the given [PageCursor]s,
+    /// or their first [Page] without a [PageCursor], according to the [FetchPolicy].
+    ///
+    /// Like when fetching the feeds, requests to the same host are rate limited and retryable
+    /// [Error][crate::Error]s are retried with exponential backoff. This does not count as a refresh.
+    /// Gives back, in the same order, the [Page]s together with whether fetching them failed.
+    pub async fn fetch_pages(
+        &self,
+        errors: &ErrorStore,
+        pages: &[(S, Option<PageCursor>)],
+    ) -> Vec<(Page<V>, bool)>
+    where
+        S: PagedGenerator,
+    {
+        let policy = self.fetch_policy.lock().unwrap().clone();
+        let client = self.transport();
+        let fetches = pages
+            .iter()
+            .map(|(s, cursor)| {
+                self.fetch_page(errors, client.as_ref(), &policy, s, cursor.as_ref())
+            })
+            .collect::<Vec<_>>();
+        futures::stream::iter(fetches)
+            .buffered(policy.concurrency())
+            .collect()
+            .await
+    }
+
+    /// Fetch one [Page] of a [Subscription] according to the [FetchPolicy], see [Merger::fetch_pages].
+    async fn fetch_page(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        policy: &FetchPolicy,
+        subscription: &S,
+        cursor: Option<&PageCursor>,
+    ) -> (Page<V>, bool)
+    where
+        S: PagedGenerator,
+    {
+        let mut retry = 0;
+        loop {
+            if let Some(host) = subscription.host() {
+                self.rate_limiter
+                    .wait(&host, policy.host_interval(&host))
+                    .await;
+            }
+
+            let try_errors = ErrorStore::new();
+            let page = subscription
+                .generate_page_with_client(&try_errors, client, cursor)
+                .await;
+            let retryable = try_errors
+                .iter_with_context()
+                .any(|(e, c)| e.is_retryable(&c));
+
+            if retryable && retry < policy.retries() {
+                let backoff = policy.backoff(retry);
+                log::debug!(
+                    "Failed fetching page of subscription {}, retrying in {:?}",
+                    subscription,
+                    backoff
+                );
+                tokio::time::sleep(backoff).await;
+                retry += 1;
+                continue;
+            }
+
+            let mut failed = false;
+            for (error, context) in try_errors.iter_with_context() {
+                let context = if context.has_subscription() {
+                    context
+                } else {
+                    context.with_subscription(subscription)
+                };
+                errors.add_with_context(error, context);
+                failed = true;
+            }
+            return (page, failed);
+        }
+    }
+
+    /// Send a request that is not part of a single [Subscription], e.g. a bulk request for many
+    /// [Subscription]s at once, according to the [FetchPolicy].
+    ///
+    /// Like when fetching a [Subscription], requests to the given host are rate limited and
+    /// retryable [Error][crate::Error]s are retried with exponential backoff.
+    /// Gives back the result of the last try.
+    pub async fn fetch_with_policy<T, F, Fut>(
+        &self,
+        host: Option<&str>,
+        request: F,
+    ) -> Result<T, (crate::Error, ErrorContext)>
+    where
+        F: Fn(Arc<dyn Transport>) -> Fut + std::marker::Send,
+        Fut: std::future::Future<Output = Result<T, (crate::Error, ErrorContext)>>
+            + std::marker::Send,
+    {
+        let policy = self.fetch_policy.lock().unwrap().clone();
+        let mut retry = 0;
+        loop {
+            if let Some(host) = host {
+                self.rate_limiter
+                    .wait(host, policy.host_interval(host))
+                    .await;
+            }
+
+            match request(self.transport()).await {
+                Err((error, context))
+                    if error.is_retryable(&context) && retry < policy.retries() =>
+                {
+                    let backoff = policy.backoff(retry);
+                    log::debug!("Failed request, retrying in {:?}", backoff);
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Remember the [Video]s of the feed of the [Subscription] together with the [RefreshState]
+    /// after fetching it, giving back the [Video]s not seen before.
+    fn record(&self, subscription: &S, videos: &[V], state: RefreshState) -> Vec<V> {
+        let new = videos
+            .iter()
+            .filter(|v| state.is_new(&v.id()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut state = state;
+        // A empty feed, e.g. of a failed bulk request, should not make all videos new again.
+        if !videos.is_empty() {
+            state = state.with_seen(videos.iter().map(|v| v.id()));
+        }
+        for video in &new {
+            state = state.with_newest(video.uploaded());
         }
+        self.feeds.lock().unwrap().insert(
+            subscription.clone(),
+            Feed {
+                state,
+                videos: videos.to_vec(),
+            },
+        );
+        new
+    }
+
+    /// Remember the [Video]s of the feed of the [Subscription] that were fetched outside of the
+    /// [Merger], e.g. using a bulk request for many [Subscription]s at once.
+    ///
+    /// Gives back the [Video]s that were not seen before, like [Merger::refresh].
+    pub fn record_feed(&self, subscription: &S, videos: &[V]) -> Vec<V> {
+        let state = self.refresh_state(subscription).unwrap_or_default();
+        self.record(subscription, videos, state)
+    }
+
+    /// Forget about the [Feed]s of [Subscription]s that are not in the given list anymore.
+    fn retain_feeds(&self, subscriptions: &[S]) {
+        self.feeds
+            .lock()
+            .unwrap()
+            .retain(|s, _| subscriptions.contains(s));
+    }
+
+    /// Generate only the [Video]s that were not seen in a previous refresh.
+    ///
+    /// For every [Subscription], the [VideoId][crate::VideoId]s of its feed as well as the
+    /// validators of the feed are remembered in a [RefreshState]. These are used to send
+    /// conditional requests (if supported by the [Subscription]) and to drop [Video]s
+    /// that were already yielded. The first refresh of a [Subscription] will yield all of its [Video]s.
+    ///
+    /// Generating the [Video]s using [Generator::generate] also counts as a refresh.
+    pub async fn refresh(&self, errors: &ErrorStore) -> std::vec::IntoIter<V> {
+        let subscriptions = self.subscription_list.lock().unwrap().subscriptions();
+        self.retain_feeds(&subscriptions);
+        let mut videos = self
+            .refresh_subscriptions(errors, &subscriptions)
+            .await
+            .into_iter()
+            .flat_map(|(_feed, new)| new)
+            .collect::<Vec<_>>();
+
+        videos.sort_by_key(|v| std::cmp::Reverse(v.uploaded()));
+        videos.into_iter()
+    }
+
+    /// Refresh only the given [Subscription]s, e.g. the ones that are due.
+    ///
+    /// Gives back for every [Subscription], in the same order, all [Video]s of its feed together
+    /// with the [Video]s that were not seen before, see [Merger::refresh].
+    /// The [Subscription]s do not need to be part of the [SubscriptionList].
+    pub async fn refresh_subscriptions(
+        &self,
+        errors: &ErrorStore,
+        subscriptions: &[S],
+    ) -> Vec<(Vec<V>, Vec<V>)> {
+        log::debug!("Starting refreshing subscriptions");
+        let results = self.fetch_feeds(errors, subscriptions).await;
+        log::debug!("Finished refreshing subscriptions");
+        results
+    }
+
+    /// Generate the [Video]s of only the given [Subscription]s, e.g. of a group of them.
+    ///
+    /// This works like [Generator::generate], the [Subscription]s do not need to be part of the [SubscriptionList].
+    pub async fn generate_subscriptions(
+        &self,
+        errors: &ErrorStore,
+        subscriptions: &[S],
+    ) -> std::vec::IntoIter<V> {
+        log::debug!("Starting getting subscriptions");
+        let results = self.fetch_feeds(errors, subscriptions).await;
+        log::debug!("Finished getting subscriptions");
+
+        // TODO: More efficient (e.g. with Heap)
+        let mut videos = results
+            .into_iter()
+            .flat_map(|(feed, _new)| feed)
+            .collect::<Vec<_>>();
+        videos.sort_by_key(|v| std::cmp::Reverse(v.uploaded()));
+        videos.into_iter()
+    }
+
+    /// Get the [RefreshState] of the given [Subscription] from the last fetch.
+    pub fn refresh_state(&self, subscription: &S) -> Option<RefreshState> {
+        self.feeds
+            .lock()
+            .unwrap()
+            .get(subscription)
+            .map(|f| f.state.clone())
+    }
+
+    /// Forget all fetched feeds and their [RefreshState]s, the next [refresh][Merger::refresh]
+    /// will yield all [Video]s again.
+    pub fn reset_refresh_states(&self) {
+        self.feeds.lock().unwrap().clear();
     }
 }
 
@@ -61,30 +474,51 @@ where
 
     type Iterator = std::vec::IntoIter<V>;
 
+    /// Generate all [Video]s of the [Subscription]s, sorted newest first.
+    ///
+    /// The [Subscription]s are fetched using conditional requests like in [Merger::refresh],
+    /// for feeds that were not modified the [Video]s of the last fetch are used.
     async fn generate(&self, errors: &ErrorStore) -> Self::Iterator {
         let subscriptions = self.subscription_list.lock().unwrap().subscriptions();
-        log::debug!("Starting getting subscriptions");
-        let client = reqwest::Client::builder()
-            .tcp_keepalive(Some(std::time::Duration::from_secs(10)))
-            .build()
-            .unwrap();
-        let results = futures::future::join_all(
-            subscriptions
-                .iter()
-                .map(|s| s.generate_with_client(errors, &client)),
-        )
-        .await;
-        log::debug!("Finished getting subscriptions");
+        self.retain_feeds(&subscriptions);
+        self.generate_subscriptions(errors, &subscriptions).await
+    }
+}
 
-        // TODO: More efficient (e.g. with Heap)
-        let mut videos = results
+impl<S, V> StreamGenerator for Merger<S, V>
+where
+    S: 'static + Subscription<Video = V> + GeneratorWithClient<Item = V>,
+    V: Video<Subscription = S>,
+    <S as GeneratorWithClient>::Iterator: 'static + std::marker::Send,
+{
+    type Item = V;
+
+    /// Generate one batch per [Subscription] as soon as the [Subscription] was fetched.
+    ///
+    /// Every batch is sorted newest first.
+    fn generate_stream<'a>(&'a self, errors: &'a ErrorStore) -> BoxStream<'a, Vec<V>> {
+        let subscriptions = self.subscription_list.lock().unwrap().subscriptions();
+        self.retain_feeds(&subscriptions);
+        let policy = Arc::new(self.fetch_policy.lock().unwrap().clone());
+        let client = self.transport();
+        let concurrency = policy.concurrency();
+
+        let fetches = subscriptions
             .into_iter()
-            .map(|res| res.collect::<Vec<_>>())
-            .collect::<Vec<_>>()
-            .concat();
-        videos.sort_unstable_by_key(|v| v.uploaded());
-        videos.reverse();
-        videos.into_iter()
+            .map(|s| {
+                let client = client.clone();
+                let policy = policy.clone();
+                async move { self.fetch_feed(errors, client.as_ref(), &policy, &s).await }
+            })
+            .collect::<Vec<_>>();
+
+        futures::stream::iter(fetches)
+            .buffer_unordered(concurrency)
+            .map(|(mut videos, _new)| {
+                videos.sort_by_key(|v| std::cmp::Reverse(v.uploaded()));
+                videos
+            })
+            .boxed()
     }
 }
 
@@ -115,14 +549,15 @@ mod test {
         let mut subscription1 = MockSubscription::new();
         subscription1
             .expect_generate_with_client()
-            .returning(move |_e, _c| {
+            .returning(move |_e| {
                 dates_clone
                     .clone()
                     .into_iter()
-                    .map(|d| make_video(d))
+                    .map(make_video)
                     .collect::<Vec<_>>()
                     .into_iter()
             });
+        subscription1.expect_eq().returning(|_| false);
         subscription1
             .expect_clone()
             .returning(move || make_subscription(dates.clone()));
@@ -131,13 +566,26 @@ mod test {
     }
 
     fn make_video(datetime: NaiveDateTime) -> MockVideo {
-        let datetime_clone = datetime.clone();
+        let datetime_clone = datetime;
         let mut video = MockVideo::new();
         video.expect_uploaded().returning(move || datetime_clone);
+        video
+            .expect_url()
+            .returning(move || datetime_clone.to_string());
         video.expect_clone().returning(move || make_video(datetime));
         video
     }
 
+    fn make_video_with_url(datetime: NaiveDateTime, url: &'static str) -> MockVideo {
+        let mut video = MockVideo::new();
+        video.expect_uploaded().returning(move || datetime);
+        video.expect_url().returning(move || url.to_string());
+        video
+            .expect_clone()
+            .returning(move || make_video_with_url(datetime, url));
+        video
+    }
+
     #[tokio::test]
     async fn merger_one_subscription() {
         let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
@@ -209,4 +657,339 @@ mod test {
         assert_eq!(result.next().unwrap().uploaded(), date_video4);
         assert!(result.next().is_none());
     }
+
+    fn make_refreshing_subscription(
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+        dates: Vec<Vec<NaiveDateTime>>,
+    ) -> MockSubscription {
+        let calls_clone = calls.clone();
+        let dates_clone = dates.clone();
+
+        let mut subscription = MockSubscription::new();
+        subscription
+            .expect_generate_with_client()
+            .returning(move |_e| {
+                let call = calls_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                dates_clone[call.min(dates_clone.len() - 1)]
+                    .clone()
+                    .into_iter()
+                    .map(make_video)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            });
+        subscription.expect_eq().returning(|_| true);
+        subscription
+            .expect_clone()
+            .returning(move || make_refreshing_subscription(calls.clone(), dates.clone()));
+
+        subscription
+    }
+
+    #[tokio::test]
+    async fn merger_refresh_only_new() {
+        let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
+            Arc::new(Mutex::new(SubscriptionList::new()));
+        let merger: Merger<MockSubscription, MockVideo> = Merger::new(subscriptions.clone());
+
+        let date_video1 = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_video2 = NaiveDate::from_ymd_opt(2021, 8, 11)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_video3 = NaiveDate::from_ymd_opt(2021, 8, 10)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        subscriptions
+            .lock()
+            .unwrap()
+            .add(make_refreshing_subscription(
+                Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+                vec![
+                    vec![date_video2, date_video3],
+                    vec![date_video1, date_video2, date_video3],
+                ],
+            ));
+
+        let errors = ErrorStore::new();
+        let mut result = merger.refresh(&errors).await;
+
+        assert_eq!(result.next().unwrap().uploaded(), date_video2);
+        assert_eq!(result.next().unwrap().uploaded(), date_video3);
+        assert!(result.next().is_none());
+
+        let mut result = merger.refresh(&errors).await;
+
+        assert_eq!(result.next().unwrap().uploaded(), date_video1);
+        assert!(result.next().is_none());
+
+        let mut result = merger.refresh(&errors).await;
+
+        assert!(result.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn merger_refresh_reset() {
+        let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
+            Arc::new(Mutex::new(SubscriptionList::new()));
+        let merger: Merger<MockSubscription, MockVideo> = Merger::new(subscriptions.clone());
+
+        let date_video1 = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        subscriptions
+            .lock()
+            .unwrap()
+            .add(make_refreshing_subscription(
+                Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+                vec![vec![date_video1]],
+            ));
+
+        let errors = ErrorStore::new();
+        assert_eq!(merger.refresh(&errors).await.count(), 1);
+        assert_eq!(merger.refresh(&errors).await.count(), 0);
+
+        merger.reset_refresh_states();
+
+        assert_eq!(merger.refresh(&errors).await.count(), 1);
+    }
+
+    fn make_refreshing_subscription_with_urls(
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+        videos: Vec<Vec<(NaiveDateTime, &'static str)>>,
+    ) -> MockSubscription {
+        let calls_clone = calls.clone();
+        let videos_clone = videos.clone();
+
+        let mut subscription = MockSubscription::new();
+        subscription
+            .expect_generate_with_client()
+            .returning(move |_e| {
+                let call = calls_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                videos_clone[call.min(videos_clone.len() - 1)]
+                    .iter()
+                    .map(|(d, u)| make_video_with_url(*d, u))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            });
+        subscription.expect_eq().returning(|_| true);
+        subscription.expect_clone().returning(move || {
+            make_refreshing_subscription_with_urls(calls.clone(), videos.clone())
+        });
+
+        subscription
+    }
+
+    #[tokio::test]
+    async fn merger_refresh_same_time_and_late() {
+        let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
+            Arc::new(Mutex::new(SubscriptionList::new()));
+        let merger: Merger<MockSubscription, MockVideo> = Merger::new(subscriptions.clone());
+
+        let date_new = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_old = NaiveDate::from_ymd_opt(2021, 8, 10)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        subscriptions
+            .lock()
+            .unwrap()
+            .add(make_refreshing_subscription_with_urls(
+                Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+                vec![
+                    vec![(date_new, "a")],
+                    // Uploaded at the same time as the newest video and published late with an older date.
+                    vec![(date_new, "a"), (date_new, "b"), (date_old, "c")],
+                ],
+            ));
+
+        let errors = ErrorStore::new();
+        assert_eq!(merger.refresh(&errors).await.count(), 1);
+
+        let result = merger.refresh(&errors).await;
+        let mut urls = result.map(|v| v.url()).collect::<Vec<_>>();
+        urls.sort();
+        assert_eq!(urls, vec!["b", "c"]);
+
+        assert_eq!(merger.refresh(&errors).await.count(), 0);
+    }
+
+    fn make_failing_subscription(
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+        failures: usize,
+        date: NaiveDateTime,
+    ) -> MockSubscription {
+        let calls_clone = calls.clone();
+
+        let mut subscription = MockSubscription::new();
+        subscription
+            .expect_generate_with_client()
+            .returning(move |e| {
+                let call = calls_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                if call < failures {
+                    e.add(crate::NetworkError("Url".to_owned()).into());
+                    vec![].into_iter()
+                } else {
+                    vec![make_video(date)].into_iter()
+                }
+            });
+        subscription.expect_eq().returning(|_| false);
+        subscription
+            .expect_clone()
+            .returning(move || make_failing_subscription(calls.clone(), failures, date));
+
+        subscription
+    }
+
+    #[tokio::test]
+    async fn merger_retry_network_error() {
+        let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
+            Arc::new(Mutex::new(SubscriptionList::new()));
+        let policy = FetchPolicy::new()
+            .with_retries(2)
+            .with_backoff(std::time::Duration::from_millis(1));
+        let merger: Merger<MockSubscription, MockVideo> =
+            Merger::new_with_fetch_policy(subscriptions.clone(), Arc::new(Mutex::new(policy)));
+
+        let date_video1 = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        subscriptions
+            .lock()
+            .unwrap()
+            .add(make_failing_subscription(calls.clone(), 2, date_video1));
+
+        let errors = ErrorStore::new();
+        let mut result = merger.generate(&errors).await;
+
+        assert_eq!(result.next().unwrap().uploaded(), date_video1);
+        assert!(result.next().is_none());
+        assert_eq!(errors.iter().count(), 0);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn merger_retry_give_up() {
+        let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
+            Arc::new(Mutex::new(SubscriptionList::new()));
+        let policy = FetchPolicy::new()
+            .with_retries(1)
+            .with_backoff(std::time::Duration::from_millis(1));
+        let merger: Merger<MockSubscription, MockVideo> =
+            Merger::new_with_fetch_policy(subscriptions.clone(), Arc::new(Mutex::new(policy)));
+
+        let date_video1 = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        subscriptions
+            .lock()
+            .unwrap()
+            .add(make_failing_subscription(calls.clone(), 5, date_video1));
+
+        let errors = ErrorStore::new();
+        let mut result = merger.generate(&errors).await;
+
+        assert!(result.next().is_none());
+        assert_eq!(errors.summary().network(), 1);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn merger_fetch_with_policy() {
+        let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
+            Arc::new(Mutex::new(SubscriptionList::new()));
+        let policy = FetchPolicy::new()
+            .with_retries(2)
+            .with_backoff(std::time::Duration::from_millis(1));
+        let merger: Merger<MockSubscription, MockVideo> =
+            Merger::new_with_fetch_policy(subscriptions, Arc::new(Mutex::new(policy)));
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let request = |failures: usize| {
+            let calls = calls.clone();
+            move |_client: Arc<dyn Transport>| {
+                let call = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                async move {
+                    if call < failures {
+                        Err((
+                            crate::NetworkError("bulk".to_string()).into(),
+                            ErrorContext::new().with_status(503),
+                        ))
+                    } else {
+                        Ok(call)
+                    }
+                }
+            }
+        };
+
+        assert_eq!(merger.fetch_with_policy(None, request(2)).await.unwrap(), 2);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        calls.store(0, std::sync::atomic::Ordering::SeqCst);
+        assert!(merger.fetch_with_policy(None, request(5)).await.is_err());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn merger_generate_stream() {
+        let subscriptions: Arc<Mutex<SubscriptionList<MockSubscription>>> =
+            Arc::new(Mutex::new(SubscriptionList::new()));
+        let merger: Merger<MockSubscription, MockVideo> = Merger::new(subscriptions.clone());
+
+        let date_video1 = NaiveDate::from_ymd_opt(2021, 8, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_video2 = NaiveDate::from_ymd_opt(2021, 8, 11)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_video3 = NaiveDate::from_ymd_opt(2021, 8, 10)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        subscriptions
+            .lock()
+            .unwrap()
+            .add(make_subscription(vec![date_video3, date_video1]));
+        subscriptions
+            .lock()
+            .unwrap()
+            .add(make_subscription(vec![date_video2]));
+
+        let errors = ErrorStore::new();
+        let mut batches = merger.generate_stream(&errors).collect::<Vec<_>>().await;
+        batches.sort_by_key(|b| b.len());
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0][0].uploaded(), date_video2);
+        assert_eq!(batches[1][0].uploaded(), date_video1);
+        assert_eq!(batches[1][1].uploaded(), date_video3);
+
+        let merged = merger
+            .generate_merged_by_key(&errors, |v| std::cmp::Reverse(v.uploaded()))
+            .await;
+
+        assert_eq!(
+            merged.iter().map(|v| v.uploaded()).collect::<Vec<_>>(),
+            vec![date_video1, date_video2, date_video3]
+        );
+    }
 }
@@ -22,10 +22,19 @@
 //!
 //! - [`Pipeline`][pipe::Pipeline]
 //! - [`SubscriptionList`][subscription_list::SubscriptionList]
+//! - [`Scheduler`][scheduler::Scheduler]
+//! - [`ThumbnailCache`][thumbnail_cache::ThumbnailCache]
+//! - [`Window`][window::Window]
 
 pub(crate) mod expander;
+pub(crate) mod fetch_policy;
+pub(crate) mod file_backend;
 pub(crate) mod merger;
+pub(crate) mod network_settings;
 pub mod pipe;
+pub(crate) mod scheduler;
 pub(crate) mod store_access;
 pub mod subscription_list;
+pub(crate) mod thumbnail_cache;
 pub(crate) mod video_store;
+pub(crate) mod window;
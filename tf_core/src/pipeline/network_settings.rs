@@ -0,0 +1,285 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{HttpRequest, HttpResponse, NetworkError, ParseError, Transport};
+
+/// The interval of TCP keepalive packets of the [Transport]s built from [NetworkSettings].
+const TCP_KEEPALIVE: Duration = Duration::from_secs(10);
+
+/// The default address of the SOCKS5 proxy of a local Tor daemon.
+const TOR_ADDRESS: &str = "127.0.0.1:9050";
+
+/// A proxy all requests are sent through, see [NetworkSettings::with_proxy].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Proxy {
+    /// A HTTP proxy at the given url, e.g. `http://proxy.example.com:8080`.
+    Http(String),
+    /// A SOCKS5 proxy at the given address, e.g. `127.0.0.1:9050`.
+    ///
+    /// Host names will also be resolved by the proxy, which is needed e.g. for Tor.
+    Socks5(String),
+}
+
+impl Proxy {
+    /// The [Proxy] of a local Tor daemon listening on the default port.
+    pub fn tor() -> Self {
+        Proxy::Socks5(TOR_ADDRESS.to_string())
+    }
+
+    /// The url of the [Proxy] as understood by [reqwest::Proxy].
+    fn url(&self) -> String {
+        match self {
+            Proxy::Http(url) if url.contains("://") => url.clone(),
+            Proxy::Http(url) => format!("http://{}", url),
+            Proxy::Socks5(address) => {
+                let address = address
+                    .strip_prefix("socks5h://")
+                    .or_else(|| address.strip_prefix("socks5://"))
+                    .unwrap_or(address);
+                format!("socks5h://{}", address)
+            }
+        }
+    }
+}
+
+/// The settings of the network connection used to request the web, e.g. the [Proxy].
+///
+/// This configures:
+///
+/// - The [Proxy] all requests are sent through.
+/// - The user agent, overwriting the one a request may set itself.
+/// - Extra headers sent with every request.
+/// - The timeouts of the connection and of the whole request.
+///
+/// The settings are applied by building a [Transport] using [NetworkSettings::build_transport].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkSettings {
+    /// The [Proxy] all requests are sent through.
+    proxy: Option<Proxy>,
+    /// The user agent of all requests.
+    user_agent: Option<String>,
+    /// Extra headers sent with every request.
+    headers: Vec<(String, String)>,
+    /// The timeout of the whole request.
+    timeout: Option<Duration>,
+    /// The timeout of connecting to the host.
+    connect_timeout: Option<Duration>,
+}
+
+impl NetworkSettings {
+    /// Create new [NetworkSettings] connecting directly without any timeouts.
+    pub fn new() -> Self {
+        NetworkSettings::default()
+    }
+
+    /// Send all requests through the given [Proxy].
+    pub fn with_proxy(mut self, proxy: Option<Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Set the user agent of all requests.
+    pub fn with_user_agent<S: AsRef<str>>(mut self, user_agent: Option<S>) -> Self {
+        self.user_agent = user_agent.map(|u| u.as_ref().to_string());
+        self
+    }
+
+    /// Add the header with the given name and value to every request.
+    pub fn with_header<N: AsRef<str>, V: AsRef<str>>(mut self, name: N, value: V) -> Self {
+        self.headers
+            .push((name.as_ref().to_string(), value.as_ref().to_string()));
+        self
+    }
+
+    /// Set the timeout of the whole request, from connecting until the body was received.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the timeout of connecting to the host.
+    pub fn with_connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Get the [Proxy] all requests are sent through.
+    pub fn proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Get the user agent of all requests.
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Get the extra headers sent with every request.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Get the timeout of the whole request.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Get the timeout of connecting to the host.
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Build a [Transport] sending the requests according to the [NetworkSettings].
+    ///
+    /// This fails with a [ParseError] if the [Proxy] is not a valid url.
+    pub fn build_transport(&self) -> Result<Arc<dyn Transport>, ParseError> {
+        let mut builder = reqwest::Client::builder().tcp_keepalive(Some(TCP_KEEPALIVE));
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy.url()).map_err(|_| ParseError(proxy.url()))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| ParseError(format!("network settings: {}", e)))?;
+
+        if self.user_agent.is_none() && self.headers.is_empty() {
+            return Ok(Arc::new(client));
+        }
+        Ok(Arc::new(HeaderTransport {
+            inner: client,
+            user_agent: self.user_agent.clone(),
+            headers: self.headers.clone(),
+        }))
+    }
+}
+
+/// A [Transport] adding the headers of the [NetworkSettings] to every request.
+struct HeaderTransport<T> {
+    /// The [Transport] actually sending the requests.
+    inner: T,
+    /// The user agent, overwriting the one of the request.
+    user_agent: Option<String>,
+    /// Extra headers added to the request.
+    headers: Vec<(String, String)>,
+}
+
+impl<T> HeaderTransport<T> {
+    /// Add the headers to the given [HttpRequest].
+    fn apply(&self, request: HttpRequest) -> HttpRequest {
+        let mut result = HttpRequest::new(request.url());
+        for (name, value) in request.headers() {
+            if self.user_agent.is_none() || !name.eq_ignore_ascii_case("User-Agent") {
+                result = result.with_header(name, value);
+            }
+        }
+        for (name, value) in &self.headers {
+            result = result.with_header(name, value);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            result = result.with_header("User-Agent", user_agent);
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for HeaderTransport<T> {
+    async fn get(&self, request: HttpRequest) -> Result<HttpResponse, NetworkError> {
+        self.inner.get(self.apply(request)).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    /// A [Transport] recording the requests it was given.
+    #[derive(Default)]
+    struct RecordingTransport(Mutex<Vec<HttpRequest>>);
+
+    #[async_trait]
+    impl Transport for RecordingTransport {
+        async fn get(&self, request: HttpRequest) -> Result<HttpResponse, NetworkError> {
+            self.0.lock().unwrap().push(request);
+            Ok(HttpResponse::new(200, ""))
+        }
+    }
+
+    #[test]
+    fn network_settings_proxy() {
+        assert_eq!(Proxy::tor().url(), "socks5h://127.0.0.1:9050");
+        assert_eq!(
+            Proxy::Socks5("socks5://localhost:1080".to_string()).url(),
+            "socks5h://localhost:1080"
+        );
+        assert_eq!(
+            Proxy::Http("proxy.example.com:8080".to_string()).url(),
+            "http://proxy.example.com:8080"
+        );
+
+        assert!(NetworkSettings::new()
+            .with_proxy(Some(Proxy::tor()))
+            .with_timeout(Some(Duration::from_secs(30)))
+            .build_transport()
+            .is_ok());
+        assert!(NetworkSettings::new()
+            .with_proxy(Some(Proxy::Http("http://in valid".to_string())))
+            .build_transport()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn network_settings_headers() {
+        let transport = HeaderTransport {
+            inner: RecordingTransport::default(),
+            user_agent: Some("Custom".to_string()),
+            headers: vec![("X-Extra".to_string(), "1".to_string())],
+        };
+
+        let request = HttpRequest::new("https://example.com")
+            .with_header("User-Agent", "Default")
+            .with_header("If-None-Match", "etag");
+        transport.get(request).await.unwrap();
+
+        let requests = transport.inner.0.lock().unwrap();
+        assert_eq!(requests[0].header("User-Agent"), Some("Custom"));
+        assert_eq!(requests[0].header("If-None-Match"), Some("etag"));
+        assert_eq!(requests[0].header("X-Extra"), Some("1"));
+        assert_eq!(
+            requests[0]
+                .headers()
+                .iter()
+                .filter(|(n, _)| n == "User-Agent")
+                .count(),
+            1
+        );
+    }
+}
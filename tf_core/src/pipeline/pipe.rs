@@ -18,13 +18,15 @@
  */
 
 use crate::{
-    ErrorStore, ExpandedVideo, Expander, Generator, GeneratorWithClient, Merger, StoreAccess,
-    Subscription, SubscriptionList, Video, VideoStore,
+    ErrorStore, ExpandedVideo, Expander, FetchPolicy, Generator, GeneratorWithClient, Merger, Page,
+    PageCursor, PagedGenerator, StoreAccess, StreamGenerator, Subscription, SubscriptionList,
+    Transport, Video, VideoStore, VideoStoreBackend, Window,
 };
 
 use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 
 /// The [Pipeline] generating [Video]s `V` from the [Subscription]s `S`.
 #[derive(Clone)]
@@ -33,6 +35,10 @@ pub struct Pipeline<S, V> {
     subscription_list: Arc<Mutex<SubscriptionList<S>>>,
     /// The [VideoStore] used in the [Expander].
     video_store: Arc<Mutex<VideoStore<ExpandedVideo<V>>>>,
+    /// The [FetchPolicy] used in the [Merger].
+    fetch_policy: Arc<Mutex<FetchPolicy>>,
+    /// The [Merger] fetching the [Subscription]s, sharing its state with the one in the [Expander].
+    merger: Merger<S, V>,
 
     /// The [Generator] to get the [Video]s from.
     store_access: StoreAccess<ExpandedVideo<V>, Expander<V, Merger<S, V>>>,
@@ -46,16 +52,25 @@ where
 {
     /// Create a new [Pipeline] with no [Subscription]s.
     pub fn new() -> Self {
+        Pipeline::new_with_fetch_policy(Arc::new(Mutex::new(FetchPolicy::new())))
+    }
+
+    /// Create a new [Pipeline] with no [Subscription]s using the given [FetchPolicy].
+    ///
+    /// Modifying the [FetchPolicy] will also alter how the [Pipeline] fetches the [Subscription]s.
+    pub fn new_with_fetch_policy(fetch_policy: Arc<Mutex<FetchPolicy>>) -> Self {
         let subscription_list = Arc::new(Mutex::new(SubscriptionList::new()));
         let video_store = Arc::new(Mutex::new(VideoStore::new()));
 
-        let merger = Merger::new(subscription_list.clone());
-        let expander = Expander::new(merger);
+        let merger = Merger::new_with_fetch_policy(subscription_list.clone(), fetch_policy.clone());
+        let expander = Expander::new(merger.clone());
         let store_access = StoreAccess::new(video_store.clone(), expander);
 
         Pipeline {
             subscription_list,
             video_store,
+            fetch_policy,
+            merger,
 
             store_access,
         }
@@ -69,10 +84,174 @@ where
         self.subscription_list.clone()
     }
 
+    /// Get the [FetchPolicy] used to fetch the [Subscription]s.
+    ///
+    /// Modifying this [FetchPolicy] will also alter how the [Pipeline] fetches the [Subscription]s.
+    pub fn fetch_policy(&self) -> Arc<Mutex<FetchPolicy>> {
+        self.fetch_policy.clone()
+    }
+
+    /// Get the [Transport] used to fetch the [Subscription]s.
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        self.merger.transport()
+    }
+
+    /// Set the [Transport] used to fetch the [Subscription]s, see [Merger::set_transport].
+    pub fn set_transport(&self, transport: Arc<dyn Transport>) {
+        self.merger.set_transport(transport)
+    }
+
     /// Upgrade a video from a normal video to a video in the video storage of the pipeline.
     pub fn upgrade_video(&self, video: &ExpandedVideo<V>) -> Arc<Mutex<ExpandedVideo<V>>> {
         self.video_store.lock().unwrap().get(video)
     }
+
+    /// Save the current state of the video, e.g. its [WatchProgress][crate::WatchProgress],
+    /// into the video storage of the pipeline, see [VideoStore::save].
+    pub fn save_video(&self, video: &ExpandedVideo<V>) {
+        self.video_store.lock().unwrap().save(video)
+    }
+
+    /// Get when the video was first seen by the video storage of the pipeline,
+    /// see [VideoStore::first_seen].
+    pub fn first_seen(&self, video: &ExpandedVideo<V>) -> Option<chrono::NaiveDateTime> {
+        self.video_store.lock().unwrap().first_seen(video)
+    }
+
+    /// Generate the videos of only the given [Subscription]s, e.g. of a group of them.
+    ///
+    /// Like [Generator::generate], the videos are put into the video storage of the pipeline.
+    pub async fn generate_subscriptions(
+        &self,
+        errors: &ErrorStore,
+        subscriptions: &[S],
+    ) -> Vec<Arc<Mutex<ExpandedVideo<V>>>> {
+        let videos = self
+            .merger
+            .generate_subscriptions(errors, subscriptions)
+            .await;
+        let mut store = self.video_store.lock().unwrap();
+        videos.map(|v| store.get(&ExpandedVideo::from(v))).collect()
+    }
+
+    /// Refresh the [Subscription]s, giving back only the videos that were not seen in a
+    /// previous refresh or generation, see [Merger::refresh].
+    ///
+    /// The videos are put into the video storage of the pipeline.
+    pub async fn refresh(&self, errors: &ErrorStore) -> Vec<Arc<Mutex<ExpandedVideo<V>>>> {
+        let videos = self.merger.refresh(errors).await;
+        let mut store = self.video_store.lock().unwrap();
+        videos.map(|v| store.get(&ExpandedVideo::from(v))).collect()
+    }
+
+    /// Refresh only the given [Subscription]s, see [Merger::refresh_subscriptions].
+    ///
+    /// Gives back for every [Subscription], in the same order, all videos of its feed together with
+    /// the videos that were not seen before. The videos are put into the video storage of the pipeline.
+    pub async fn refresh_subscriptions(
+        &self,
+        errors: &ErrorStore,
+        subscriptions: &[S],
+    ) -> Vec<(
+        Vec<Arc<Mutex<ExpandedVideo<V>>>>,
+        Vec<Arc<Mutex<ExpandedVideo<V>>>>,
+    )> {
+        let results = self
+            .merger
+            .refresh_subscriptions(errors, subscriptions)
+            .await;
+        let mut store = self.video_store.lock().unwrap();
+        results
+            .into_iter()
+            .map(|(feed, new)| {
+                (
+                    feed.into_iter()
+                        .map(|v| store.get(&ExpandedVideo::from(v)))
+                        .collect(),
+                    new.into_iter()
+                        .map(|v| store.get(&ExpandedVideo::from(v)))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Fetch the pages of older videos of the given [Subscription]s at the given [PageCursor]s,
+    /// see [Merger::fetch_pages].
+    ///
+    /// Gives back, in the same order, the pages together with whether fetching them failed.
+    /// The videos are put into the video storage of the pipeline.
+    pub async fn fetch_pages(
+        &self,
+        errors: &ErrorStore,
+        pages: &[(S, Option<PageCursor>)],
+    ) -> Vec<(Page<Arc<Mutex<ExpandedVideo<V>>>>, bool)>
+    where
+        S: PagedGenerator,
+    {
+        let results = self.merger.fetch_pages(errors, pages).await;
+        let mut store = self.video_store.lock().unwrap();
+        results
+            .into_iter()
+            .map(|(page, failed)| (page.map(|v| store.get(&ExpandedVideo::from(v))), failed))
+            .collect()
+    }
+
+    /// Get the [PageCursor] to the page of older videos following the last fetched feed of the
+    /// [Subscription], see [RefreshState::next_page][crate::RefreshState::next_page].
+    pub fn next_page(&self, subscription: &S) -> Option<Option<PageCursor>> {
+        self.merger
+            .refresh_state(subscription)
+            .and_then(|s| s.next_page())
+    }
+
+    /// Select the videos in the [Window] from all videos in the video storage of the pipeline,
+    /// see [VideoStore::select].
+    pub fn select<F: Fn(&ExpandedVideo<V>) -> bool>(
+        &self,
+        window: &Window,
+        keep: F,
+    ) -> Vec<ExpandedVideo<V>> {
+        self.video_store.lock().unwrap().select(window, keep)
+    }
+
+    /// Set the persistent [VideoStoreBackend] of the video storage of the pipeline.
+    ///
+    /// See [VideoStore::set_backend].
+    pub fn set_video_store_backend(
+        &self,
+        backend: Box<dyn VideoStoreBackend<ExpandedVideo<V>>>,
+        max_age: Option<chrono::Duration>,
+    ) {
+        self.video_store
+            .lock()
+            .unwrap()
+            .set_backend(backend, max_age)
+    }
+
+    /// Set the maximum age of the videos in the video storage of the pipeline.
+    ///
+    /// See [VideoStore::set_max_age].
+    pub fn set_video_max_age(&self, max_age: Option<chrono::Duration>) {
+        self.video_store.lock().unwrap().set_max_age(max_age)
+    }
+
+    /// Get all videos in the video storage of the pipeline, including the ones loaded
+    /// from the [VideoStoreBackend].
+    pub fn stored_videos(&self) -> Vec<Arc<Mutex<ExpandedVideo<V>>>> {
+        self.video_store.lock().unwrap().stored()
+    }
+
+    /// Whether any videos are in the video storage of the pipeline, see [VideoStore::is_empty].
+    pub fn has_stored_videos(&self) -> bool {
+        !self.video_store.lock().unwrap().is_empty()
+    }
+
+    /// Take the videos in the video storage of the pipeline which were updated in place since
+    /// the last call, see [VideoStore::take_updated].
+    pub fn take_updated_videos(&self) -> Vec<Arc<Mutex<ExpandedVideo<V>>>> {
+        self.video_store.lock().unwrap().take_updated()
+    }
 }
 
 #[async_trait]
@@ -91,6 +270,19 @@ where
     }
 }
 
+impl<S, V> StreamGenerator for Pipeline<S, V>
+where
+    S: 'static + Subscription<Video = V> + GeneratorWithClient<Item = V> + Generator<Item = V>,
+    V: 'static + Video<Subscription = S>,
+    <S as GeneratorWithClient>::Iterator: std::marker::Send,
+{
+    type Item = Arc<Mutex<ExpandedVideo<V>>>;
+
+    fn generate_stream<'a>(&'a self, errors: &'a ErrorStore) -> BoxStream<'a, Vec<Self::Item>> {
+        self.store_access.generate_stream(errors)
+    }
+}
+
 impl<S, V> Default for Pipeline<S, V>
 where
     S: 'static + Subscription<Video = V> + GeneratorWithClient<Item = V> + Generator<Item = V>,
@@ -0,0 +1,438 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand::Rng;
+
+/// The default minimal interval in between two refreshes of a [Subscription][crate::Subscription].
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// The default maximal interval in between two refreshes of a [Subscription][crate::Subscription].
+const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// The default interval of [Subscription][crate::Subscription]s without known uploads.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// The default amount of refreshes in the time a [Subscription][crate::Subscription] usually takes to upload.
+const DEFAULT_REFRESHES_PER_UPLOAD: u32 = 4;
+/// The default jitter as fraction of the interval.
+const DEFAULT_JITTER: f64 = 0.1;
+/// The amount of upload times remembered per [Subscription][crate::Subscription].
+const HISTORY_SIZE: usize = 10;
+
+/// The policy of the [Scheduler] on how often to refresh the [Subscription][crate::Subscription]s.
+///
+/// The interval of a [Subscription][crate::Subscription] is derived from its upload history:
+/// The usual time in between two uploads (or the time since the last upload if that is longer,
+/// e.g. for dormant channels) divided by the refreshes per upload, clamped into the minimal
+/// and maximal interval. A random jitter is added such that not all
+/// [Subscription][crate::Subscription]s are refreshed at the same time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulePolicy {
+    /// The minimal interval in between two refreshes.
+    min_interval: Duration,
+    /// The maximal interval in between two refreshes.
+    max_interval: Duration,
+    /// The interval used when no uploads are known.
+    default_interval: Duration,
+    /// The amount of refreshes in the usual time in between two uploads.
+    refreshes_per_upload: u32,
+    /// The jitter as fraction of the interval.
+    jitter: f64,
+}
+
+impl SchedulePolicy {
+    /// Create a new [SchedulePolicy] with the default values.
+    pub fn new() -> Self {
+        SchedulePolicy {
+            min_interval: DEFAULT_MIN_INTERVAL,
+            max_interval: DEFAULT_MAX_INTERVAL,
+            default_interval: DEFAULT_INTERVAL,
+            refreshes_per_upload: DEFAULT_REFRESHES_PER_UPLOAD,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+
+    /// Set the minimal interval in between two refreshes.
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Set the maximal interval in between two refreshes.
+    pub fn with_max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Set the interval used for [Subscription][crate::Subscription]s without known uploads.
+    pub fn with_default_interval(mut self, interval: Duration) -> Self {
+        self.default_interval = interval;
+        self
+    }
+
+    /// Set the amount of refreshes in the usual time in between two uploads.
+    ///
+    /// A value of `0` will be treated as `1`.
+    pub fn with_refreshes_per_upload(mut self, refreshes: u32) -> Self {
+        self.refreshes_per_upload = refreshes.max(1);
+        self
+    }
+
+    /// Set the jitter as fraction of the interval, e.g. `0.1` for up to 10% earlier or later.
+    ///
+    /// The value will be clamped into `0.0..=1.0`.
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Get the minimal interval in between two refreshes.
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    /// Get the maximal interval in between two refreshes.
+    pub fn max_interval(&self) -> Duration {
+        self.max_interval
+    }
+
+    /// Get the interval used for [Subscription][crate::Subscription]s without known uploads.
+    pub fn default_interval(&self) -> Duration {
+        self.default_interval
+    }
+
+    /// Get the amount of refreshes in the usual time in between two uploads.
+    pub fn refreshes_per_upload(&self) -> u32 {
+        self.refreshes_per_upload
+    }
+
+    /// Get the jitter as fraction of the interval.
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    /// Get the interval without jitter for a [Subscription][crate::Subscription] with the
+    /// given upload times at the given time.
+    pub fn interval(
+        &self,
+        uploads: &[chrono::NaiveDateTime],
+        now: chrono::NaiveDateTime,
+    ) -> Duration {
+        let newest = match uploads.iter().max() {
+            Some(newest) => *newest,
+            None => return self.default_interval.min(self.max_interval),
+        };
+
+        let mut sorted = uploads.to_vec();
+        sorted.sort_unstable();
+        let gaps = sorted
+            .windows(2)
+            .filter_map(|w| (w[1] - w[0]).to_std().ok())
+            .collect::<Vec<_>>();
+        let gap = if gaps.is_empty() {
+            Duration::ZERO
+        } else {
+            gaps.iter().sum::<Duration>() / gaps.len() as u32
+        };
+        let silence = (now - newest).to_std().unwrap_or_default();
+
+        (gap.max(silence) / self.refreshes_per_upload)
+            .max(self.min_interval)
+            .min(self.max_interval)
+    }
+
+    /// Add a random jitter to the interval.
+    pub fn jittered(&self, interval: Duration) -> Duration {
+        if self.jitter == 0.0 {
+            return interval;
+        }
+        let factor = rand::thread_rng().gen_range(-self.jitter..=self.jitter);
+        if factor < 0.0 {
+            interval.saturating_sub(interval.mul_f64(-factor))
+        } else {
+            interval + interval.mul_f64(factor)
+        }
+    }
+}
+
+impl Default for SchedulePolicy {
+    fn default() -> Self {
+        SchedulePolicy::new()
+    }
+}
+
+/// The schedule of one [Subscription][crate::Subscription].
+#[derive(Debug, Clone)]
+struct ScheduleEntry {
+    /// The latest known upload times, at most [HISTORY_SIZE].
+    uploads: Vec<chrono::NaiveDateTime>,
+    /// The interval used for the next refresh.
+    interval: Duration,
+    /// When the next refresh is due, [None] if due immediately.
+    next: Option<chrono::NaiveDateTime>,
+}
+
+/// Schedule the refreshes of [Subscription][crate::Subscription]s `S`, each on its own interval.
+///
+/// The [Scheduler] does not refresh by itself, it only keeps track of when which
+/// [Subscription][crate::Subscription] is [due][Scheduler::due]. After refreshing a
+/// [Subscription][crate::Subscription], its upload times have to be [recorded][Scheduler::record]
+/// to schedule the next refresh according to the [SchedulePolicy].
+///
+/// Cloning the [Scheduler] will still share the same schedule.
+#[derive(Clone)]
+pub struct Scheduler<S> {
+    /// The [SchedulePolicy] used to compute the intervals.
+    policy: Arc<Mutex<SchedulePolicy>>,
+    /// The schedule of every [Subscription][crate::Subscription] refreshed at least once.
+    entries: Arc<Mutex<HashMap<S, ScheduleEntry>>>,
+    /// Whether the [Scheduler] is paused.
+    paused: Arc<Mutex<bool>>,
+}
+
+impl<S> Scheduler<S>
+where
+    S: Hash + Eq + Clone,
+{
+    /// Create a new [Scheduler] using the default [SchedulePolicy].
+    pub fn new() -> Self {
+        Scheduler {
+            policy: Arc::new(Mutex::new(SchedulePolicy::new())),
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            paused: Arc::new(Mutex::new(false)),
+        }
+    }
+
+    /// Get the [SchedulePolicy] used to compute the intervals.
+    ///
+    /// Modifying this [SchedulePolicy] will alter the intervals of the following refreshes.
+    pub fn policy(&self) -> Arc<Mutex<SchedulePolicy>> {
+        self.policy.clone()
+    }
+
+    /// Get the given [Subscription][crate::Subscription]s that are due at the given time.
+    ///
+    /// [Subscription][crate::Subscription]s that were never refreshed are always due.
+    /// Nothing is due while the [Scheduler] is [paused][Scheduler::pause].
+    pub fn due(&self, subscriptions: &[S], now: chrono::NaiveDateTime) -> Vec<S> {
+        if self.is_paused() {
+            return vec![];
+        }
+        let entries = self.entries.lock().unwrap();
+        subscriptions
+            .iter()
+            .filter(|s| !matches!(entries.get(s).and_then(|e| e.next), Some(next) if next > now))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the time the first of the given [Subscription][crate::Subscription]s is due.
+    ///
+    /// This is [None] if there are no [Subscription][crate::Subscription]s or the [Scheduler] is
+    /// [paused][Scheduler::pause]. A time in the past means a [Subscription][crate::Subscription]
+    /// is due already.
+    pub fn next_due(
+        &self,
+        subscriptions: &[S],
+        now: chrono::NaiveDateTime,
+    ) -> Option<chrono::NaiveDateTime> {
+        if self.is_paused() {
+            return None;
+        }
+        let entries = self.entries.lock().unwrap();
+        subscriptions
+            .iter()
+            .map(|s| entries.get(s).and_then(|e| e.next).unwrap_or(now))
+            .min()
+    }
+
+    /// Record that the [Subscription][crate::Subscription] was refreshed at the given time,
+    /// finding [Video][crate::Video]s uploaded at the given times.
+    ///
+    /// This schedules the next refresh according to the [SchedulePolicy] and gives back when it is due.
+    pub fn record(
+        &self,
+        subscription: &S,
+        uploads: &[chrono::NaiveDateTime],
+        now: chrono::NaiveDateTime,
+    ) -> chrono::NaiveDateTime {
+        let policy = self.policy.lock().unwrap().clone();
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries
+            .entry(subscription.clone())
+            .or_insert_with(|| ScheduleEntry {
+                uploads: vec![],
+                interval: policy.default_interval(),
+                next: None,
+            });
+
+        for upload in uploads {
+            if !entry.uploads.contains(upload) {
+                entry.uploads.push(*upload);
+            }
+        }
+        entry.uploads.sort_unstable_by(|a, b| b.cmp(a));
+        entry.uploads.truncate(HISTORY_SIZE);
+
+        entry.interval = policy.interval(&entry.uploads, now);
+        let jittered = chrono::Duration::from_std(policy.jittered(entry.interval))
+            .unwrap_or(chrono::Duration::MAX);
+        let next = now.checked_add_signed(jittered).unwrap_or(now);
+        entry.next = Some(next);
+        next
+    }
+
+    /// Get the interval without jitter the [Subscription][crate::Subscription] is currently refreshed with.
+    ///
+    /// This is [None] if the [Subscription][crate::Subscription] was never refreshed.
+    pub fn interval(&self, subscription: &S) -> Option<Duration> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(subscription)
+            .map(|e| e.interval)
+    }
+
+    /// Make the [Subscription][crate::Subscription] due immediately, e.g. when the user requests it.
+    pub fn refresh_now(&self, subscription: &S) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(subscription) {
+            entry.next = None;
+        }
+    }
+
+    /// Make all [Subscription][crate::Subscription]s due immediately.
+    pub fn refresh_all_now(&self) {
+        self.entries
+            .lock()
+            .unwrap()
+            .values_mut()
+            .for_each(|e| e.next = None);
+    }
+
+    /// Pause the [Scheduler], no [Subscription][crate::Subscription]s will be due until
+    /// [resumed][Scheduler::resume].
+    pub fn pause(&self) {
+        *self.paused.lock().unwrap() = true;
+    }
+
+    /// Resume the [Scheduler] after it was [paused][Scheduler::pause].
+    ///
+    /// [Subscription][crate::Subscription]s that became due in the meantime are due immediately.
+    pub fn resume(&self) {
+        *self.paused.lock().unwrap() = false;
+    }
+
+    /// Whether the [Scheduler] is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().unwrap()
+    }
+}
+
+impl<S> Default for Scheduler<S>
+where
+    S: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Scheduler::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    fn date(day: u32) -> chrono::NaiveDateTime {
+        NaiveDate::from_ymd_opt(2021, 8, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn schedule_policy_interval() {
+        let policy = SchedulePolicy::new().with_refreshes_per_upload(2);
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        // Daily uploads.
+        let daily = [date(1), date(2), date(3), date(4)];
+        assert_eq!(policy.interval(&daily, date(4)), day / 2);
+
+        // Dormant channels are refreshed less often, at most with the maximal interval.
+        assert_eq!(policy.interval(&daily, date(30)), day);
+
+        // Frequent uploads are refreshed at most with the minimal interval.
+        let frequent = [date(1), date(1) + chrono::Duration::minutes(1)];
+        assert_eq!(
+            policy.interval(&frequent, date(1) + chrono::Duration::minutes(1)),
+            DEFAULT_MIN_INTERVAL
+        );
+
+        assert_eq!(policy.interval(&[], date(1)), DEFAULT_INTERVAL);
+    }
+
+    #[test]
+    fn schedule_policy_jitter() {
+        let policy = SchedulePolicy::new().with_jitter(0.5);
+        let interval = Duration::from_secs(100);
+        for _ in 0..20 {
+            let jittered = policy.jittered(interval);
+            assert!(jittered >= Duration::from_secs(50));
+            assert!(jittered <= Duration::from_secs(150));
+        }
+        assert_eq!(
+            SchedulePolicy::new().with_jitter(0.0).jittered(interval),
+            interval
+        );
+    }
+
+    #[test]
+    fn scheduler_due() {
+        let scheduler = Scheduler::<&str>::new();
+        *scheduler.policy().lock().unwrap() = SchedulePolicy::new()
+            .with_jitter(0.0)
+            .with_refreshes_per_upload(1);
+        let subscriptions = ["daily", "dormant"];
+        let now = date(10);
+
+        assert_eq!(scheduler.due(&subscriptions, now), subscriptions);
+
+        let next_daily = scheduler.record(&"daily", &[date(8), date(9), date(10)], now);
+        let next_dormant = scheduler.record(&"dormant", &[date(1)], now);
+        assert_eq!(next_daily, date(11));
+        assert_eq!(next_dormant, date(11));
+        assert_eq!(
+            scheduler.interval(&"daily"),
+            Some(Duration::from_secs(24 * 60 * 60))
+        );
+        assert!(scheduler.due(&subscriptions, now).is_empty());
+        assert_eq!(scheduler.next_due(&subscriptions, now), Some(date(11)));
+
+        scheduler.refresh_now(&"dormant");
+        assert_eq!(scheduler.due(&subscriptions, now), vec!["dormant"]);
+
+        scheduler.pause();
+        assert!(scheduler.due(&subscriptions, date(20)).is_empty());
+        assert_eq!(scheduler.next_due(&subscriptions, now), None);
+        scheduler.resume();
+        assert_eq!(scheduler.due(&subscriptions, date(20)), subscriptions);
+    }
+}
@@ -19,9 +19,11 @@
 
 use std::sync::{Arc, Mutex};
 
-use crate::{ErrorStore, Generator, Video, VideoStore};
+use crate::{ErrorStore, Generator, StreamGenerator, Video, VideoStore};
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 
 /// A pipeline elemnt putting generated [Video]s `V` from the [Generator] `G` into
 /// the [VideoStore] and generating `Arc<Mutex<V>>`.
@@ -65,6 +67,25 @@ where
     }
 }
 
+impl<V, G> StreamGenerator for StoreAccess<V, G>
+where
+    V: 'static + Video + std::hash::Hash + std::cmp::Eq + std::marker::Sync + std::marker::Send,
+    G: StreamGenerator<Item = V> + std::marker::Send + std::marker::Sync + 'static,
+{
+    type Item = Arc<Mutex<V>>;
+
+    fn generate_stream<'a>(&'a self, errors: &'a ErrorStore) -> BoxStream<'a, Vec<Self::Item>> {
+        let store = self.store.clone();
+        self.generator
+            .generate_stream(errors)
+            .map(move |batch| {
+                let mut store = store.lock().unwrap();
+                batch.iter().map(|v| store.get(v)).collect()
+            })
+            .boxed()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use chrono::{NaiveDate, NaiveDateTime};
@@ -74,9 +95,14 @@ mod test {
     use crate::mock::MockVideo;
 
     fn make_video(datetime: NaiveDateTime) -> MockVideo {
-        let datetime_clone = datetime.clone();
+        let datetime_clone = datetime;
         let mut video = MockVideo::new();
         video.expect_uploaded().returning(move || datetime_clone);
+        video
+            .expect_url()
+            .returning(move || datetime_clone.to_string());
+        video.expect_title().returning(String::new);
+        video.expect_thumbnail_url().returning(String::new);
         video.expect_clone().returning(move || make_video(datetime));
         video
     }
@@ -48,10 +48,12 @@ where
         self.subscriptions.retain(|s| s != &subscription);
     }
 
-    /// Update a [Subscription] from the [SubscriptionList].
+    /// Update a [Subscription] from the [SubscriptionList], keeping its position.
     pub fn update(&mut self, subscription: S) {
-        self.remove(subscription.clone());
-        self.add(subscription)
+        match self.subscriptions.iter_mut().find(|s| *s == &subscription) {
+            Some(s) => *s = subscription,
+            None => self.add(subscription),
+        }
     }
 
     /// Get a [Vec] of all [Subscription]s.
@@ -0,0 +1,377 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use image::{imageops::FilterType, DynamicImage, ImageFormat};
+
+use crate::{HttpRequest, Transport, Video};
+
+/// The directory inside the cache mapping thumbnail urls to the content they point to.
+const URL_DIRECTORY: &str = "urls";
+/// The directory inside the cache containing the downloaded thumbnails and their variants.
+const CONTENT_DIRECTORY: &str = "content";
+
+/// The size variant of a thumbnail in the [ThumbnailCache].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ThumbnailSize {
+    /// The thumbnail as it was downloaded.
+    Original,
+    /// A small thumbnail fitting into 320 by 180 pixels, e.g. for lists of videos.
+    List,
+    /// A larger thumbnail fitting into 640 by 360 pixels, e.g. for details about a video.
+    Detail,
+}
+
+impl ThumbnailSize {
+    /// The maximal width and height of the variant, [None] for [ThumbnailSize::Original].
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        match self {
+            ThumbnailSize::Original => None,
+            ThumbnailSize::List => Some((320, 180)),
+            ThumbnailSize::Detail => Some((640, 360)),
+        }
+    }
+
+    /// Shrink the image to fit into the [ThumbnailSize], keeping the aspect ratio.
+    ///
+    /// Images already fitting are not changed.
+    pub fn resize(&self, image: DynamicImage) -> DynamicImage {
+        match self.dimensions() {
+            Some((width, height)) if image.width() > width || image.height() > height => {
+                image.resize(width, height, FilterType::Triangle)
+            }
+            _ => image,
+        }
+    }
+
+    /// The suffix of the file of the variant in the [ThumbnailCache].
+    fn suffix(&self) -> &'static str {
+        match self {
+            ThumbnailSize::Original => "original",
+            ThumbnailSize::List => "list.png",
+            ThumbnailSize::Detail => "detail.png",
+        }
+    }
+}
+
+/// A content-addressed on-disk cache of thumbnails.
+///
+/// Every downloaded thumbnail is stored once under the hash of its content, such that
+/// thumbnails shared by multiple [Video]s (e.g. default thumbnails) are only stored once.
+/// The [ThumbnailSize] variants are generated when they are first requested and stored next to
+/// the downloaded thumbnail.
+///
+/// Thumbnails downloaded longer than the maximal age ago (see [ThumbnailCache::with_max_age])
+/// are downloaded again when requested and removed when calling [ThumbnailCache::prune].
+#[derive(Clone, Debug)]
+pub struct ThumbnailCache {
+    /// The directory containing the cache.
+    directory: PathBuf,
+
+    /// The maximal age of a downloaded thumbnail.
+    max_age: Option<chrono::Duration>,
+}
+
+impl ThumbnailCache {
+    /// Create a new [ThumbnailCache] in the given directory without a maximal age.
+    ///
+    /// The directory will be created when the first thumbnail is stored.
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        ThumbnailCache {
+            directory: directory.as_ref().to_path_buf(),
+            max_age: None,
+        }
+    }
+
+    /// Set the maximal age of the downloaded thumbnails, [None] to keep them forever.
+    pub fn with_max_age(mut self, max_age: Option<chrono::Duration>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Get the directory containing the cache.
+    pub fn directory(&self) -> PathBuf {
+        self.directory.clone()
+    }
+
+    /// Get the maximal age of the downloaded thumbnails.
+    pub fn max_age(&self) -> Option<chrono::Duration> {
+        self.max_age
+    }
+
+    /// Get the thumbnail of the [Video] in the given [ThumbnailSize].
+    ///
+    /// If the thumbnail is cached and not expired, this will not use the network. Otherwise it
+    /// will be downloaded using the given [Transport] and stored in the cache.
+    /// If downloading or decoding fails, the [Video::default_thumbnail] is given back and
+    /// nothing is cached.
+    pub async fn thumbnail<V: Video>(
+        &self,
+        video: &V,
+        size: ThumbnailSize,
+        client: &dyn Transport,
+    ) -> DynamicImage {
+        let thumbnail_url = video.thumbnail_url();
+        if let Some(image) = self.cached::<V>(&thumbnail_url, size) {
+            return image;
+        }
+
+        log::debug!("Getting thumbnail from url {}", thumbnail_url);
+        let response = match client.get(HttpRequest::new(&thumbnail_url)).await {
+            Ok(response) if response.is_success() => response,
+            _ => {
+                log::error!(
+                    "Failed getting thumbnail for url {}, use default",
+                    thumbnail_url
+                );
+                return video.default_thumbnail();
+            }
+        };
+
+        let image = match V::convert_image(response.body()) {
+            Some(image) => image,
+            None => return video.default_thumbnail(),
+        };
+
+        let content = format!("{:016x}", hash(response.body()));
+        if let Err(e) = self.store(&thumbnail_url, &content, response.body()) {
+            log::error!(
+                "Failed storing thumbnail for url {} in cache at {:?}: {}",
+                thumbnail_url,
+                self.directory,
+                e
+            );
+            return size.resize(image);
+        }
+        self.variant(&content, image, size)
+    }
+
+    /// Remove all expired thumbnails and their variants from the cache.
+    pub fn prune(&self) {
+        let urls = match std::fs::read_dir(self.directory.join(URL_DIRECTORY)) {
+            Ok(urls) => urls,
+            Err(_) => return,
+        };
+
+        let mut referenced = HashSet::new();
+        for entry in urls.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if self.is_expired(&path) {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::error!("Failed removing thumbnail at {:?}: {}", path, e);
+                }
+            } else if let Ok(content) = std::fs::read_to_string(&path) {
+                referenced.insert(content);
+            }
+        }
+
+        let contents = match std::fs::read_dir(self.directory.join(CONTENT_DIRECTORY)) {
+            Ok(contents) => contents,
+            Err(_) => return,
+        };
+        for entry in contents.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let content = entry
+                .file_name()
+                .to_string_lossy()
+                .split('-')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if !referenced.contains(&content) {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::error!("Failed removing thumbnail at {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    /// Get the cached thumbnail of the given url in the given [ThumbnailSize] without using
+    /// the network, generating the variant if needed.
+    fn cached<V: Video>(&self, thumbnail_url: &str, size: ThumbnailSize) -> Option<DynamicImage> {
+        let url_path = self.url_path(thumbnail_url);
+        if self.is_expired(&url_path) {
+            return None;
+        }
+        let content = std::fs::read_to_string(url_path).ok()?;
+
+        if size != ThumbnailSize::Original {
+            if let Ok(image) = image::open(self.content_path(&content, size)) {
+                return Some(image);
+            }
+        }
+        let data = std::fs::read(self.content_path(&content, ThumbnailSize::Original)).ok()?;
+        let image = V::convert_image(&data)?;
+        Some(self.variant(&content, image, size))
+    }
+
+    /// Store the downloaded thumbnail of the given url under the given content hash.
+    fn store(&self, thumbnail_url: &str, content: &str, data: &[u8]) -> std::io::Result<()> {
+        std::fs::create_dir_all(self.directory.join(URL_DIRECTORY))?;
+        std::fs::create_dir_all(self.directory.join(CONTENT_DIRECTORY))?;
+        let original = self.content_path(content, ThumbnailSize::Original);
+        if !original.exists() {
+            std::fs::write(original, data)?;
+        }
+        std::fs::write(self.url_path(thumbnail_url), content)
+    }
+
+    /// Resize the thumbnail with the given content hash into the [ThumbnailSize], storing the
+    /// variant in the cache.
+    fn variant(&self, content: &str, image: DynamicImage, size: ThumbnailSize) -> DynamicImage {
+        if size == ThumbnailSize::Original {
+            return image;
+        }
+        let image = size.resize(image);
+        let path = self.content_path(content, size);
+        if let Err(e) = image.save_with_format(&path, ImageFormat::Png) {
+            log::error!("Failed storing thumbnail at {:?}: {}", path, e);
+        }
+        image
+    }
+
+    /// Whether the url entry at the path is missing or older than the maximal age.
+    fn is_expired(&self, path: &Path) -> bool {
+        let modified = match std::fs::metadata(path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(_) => return true,
+        };
+        match self.max_age.and_then(|a| a.to_std().ok()) {
+            Some(max_age) => SystemTime::now()
+                .duration_since(modified)
+                .is_ok_and(|age| age > max_age),
+            None => false,
+        }
+    }
+
+    /// The path of the file mapping the url to the content hash.
+    fn url_path(&self, thumbnail_url: &str) -> PathBuf {
+        self.directory
+            .join(URL_DIRECTORY)
+            .join(format!("{:016x}", hash(thumbnail_url.as_bytes())))
+    }
+
+    /// The path of the file of the content hash in the given [ThumbnailSize].
+    fn content_path(&self, content: &str, size: ThumbnailSize) -> PathBuf {
+        self.directory
+            .join(CONTENT_DIRECTORY)
+            .join(format!("{}-{}", content, size.suffix()))
+    }
+}
+
+/// A stable 64-bit FNV-1a hash, used to address the files in the [ThumbnailCache].
+///
+/// The hash of the standard library is not guaranteed to stay the same between releases.
+fn hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use crate::mock::MockVideo;
+    use crate::{HttpResponse, NetworkError};
+
+    struct ImageTransport {
+        body: Vec<u8>,
+        requests: Mutex<usize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for ImageTransport {
+        async fn get(&self, _request: HttpRequest) -> Result<HttpResponse, NetworkError> {
+            *self.requests.lock().unwrap() += 1;
+            Ok(HttpResponse::new(200, self.body.clone()))
+        }
+    }
+
+    fn make_video(url: &'static str) -> MockVideo {
+        let mut video = MockVideo::new();
+        video
+            .expect_thumbnail_url()
+            .returning(move || url.to_owned());
+        video
+    }
+
+    #[tokio::test]
+    async fn thumbnail_cache() {
+        let directory =
+            std::env::temp_dir().join(format!("tf_core_thumbnail_cache_{}", std::process::id()));
+        let mut body = std::io::Cursor::new(vec![]);
+        DynamicImage::new_rgb8(1280, 720)
+            .write_to(&mut body, ImageFormat::Png)
+            .unwrap();
+        let transport = ImageTransport {
+            body: body.into_inner(),
+            requests: Mutex::new(0),
+        };
+        let cache = ThumbnailCache::new(&directory);
+
+        let video1 = make_video("https://example.com/1.png");
+        let list = cache
+            .thumbnail(&video1, ThumbnailSize::List, &transport)
+            .await;
+        assert_eq!((list.width(), list.height()), (320, 180));
+        let detail = cache
+            .thumbnail(&video1, ThumbnailSize::Detail, &transport)
+            .await;
+        assert_eq!((detail.width(), detail.height()), (640, 360));
+        let original = cache
+            .thumbnail(&video1, ThumbnailSize::Original, &transport)
+            .await;
+        assert_eq!((original.width(), original.height()), (1280, 720));
+        assert_eq!(*transport.requests.lock().unwrap(), 1);
+
+        // The same content behind another url is only stored once.
+        let video2 = make_video("https://example.com/2.png");
+        cache
+            .thumbnail(&video2, ThumbnailSize::List, &transport)
+            .await;
+        assert_eq!(*transport.requests.lock().unwrap(), 2);
+        let contents = std::fs::read_dir(directory.join(CONTENT_DIRECTORY))
+            .unwrap()
+            .count();
+        assert_eq!(contents, 3);
+
+        // Expired thumbnails are downloaded again and removed when pruning.
+        let expired = ThumbnailCache::new(&directory).with_max_age(Some(chrono::Duration::zero()));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        expired
+            .thumbnail(&video1, ThumbnailSize::List, &transport)
+            .await;
+        assert_eq!(*transport.requests.lock().unwrap(), 3);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        expired.prune();
+        assert_eq!(
+            std::fs::read_dir(directory.join(CONTENT_DIRECTORY))
+                .unwrap()
+                .count(),
+            0
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -17,61 +17,373 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::collections::HashMap;
-use std::hash::Hash;
+use std::collections::{HashMap, HashSet};
 use std::sync::Weak;
 use std::sync::{Arc, Mutex};
 
+use crate::{Video, VideoId, Window};
+
+/// A persistent backend of a [VideoStore], e.g. a file or a database.
+///
+/// The backend stores every [Video][crate::Video] `V` together with the time it was first seen.
+pub trait VideoStoreBackend<V>: std::marker::Send {
+    /// Load all stored [Video][crate::Video]s with the time they were first seen.
+    fn load(&mut self) -> Vec<(V, chrono::NaiveDateTime)>;
+
+    /// Store a new [Video][crate::Video] with the time it was first seen.
+    fn insert(&mut self, video: &V, first_seen: chrono::NaiveDateTime);
+
+    /// Replace all stored [Video][crate::Video]s with the given ones, e.g. after pruning.
+    fn replace(&mut self, videos: &[(V, chrono::NaiveDateTime)]);
+}
+
+/// How often the [VideoStore] prunes expired [Video][crate::Video]s when
+/// [getting][VideoStore::get] or [saving][VideoStore::save] them, see [VideoStore::set_max_age].
+const PRUNE_INTERVAL_MINUTES: i64 = 60;
+
 /// A store mapping [Video]s `V` to a globally
 /// unique `Arc<Mutex<V>>`-Version of the video.
+///
+/// [Video]s are identified by their [Video::id]. If a known [Video] is given again with e.g. a
+/// edited title, the stored [Video] will be updated in place using [Video::update].
+///
+/// The [VideoStore] also remembers when a [Video][crate::Video] was first seen.
+/// Using a [VideoStoreBackend], these [Video][crate::Video]s can be persisted and loaded again
+/// on the next start, see [VideoStore::set_backend]. To not grow forever, [Video][crate::Video]s
+/// can be pruned after a maximum age, see [VideoStore::set_max_age].
 pub struct VideoStore<V> {
-    videos: HashMap<V, Weak<Mutex<V>>>,
+    videos: HashMap<VideoId, Weak<Mutex<V>>>,
+
+    /// The last known state of every [Video][crate::Video] together with the time it was first seen.
+    known: HashMap<VideoId, (V, chrono::NaiveDateTime)>,
+
+    /// The persistent backend.
+    backend: Option<Box<dyn VideoStoreBackend<V>>>,
+
+    /// The number of rows in the backend, including outdated rows of the same [Video][crate::Video].
+    backend_rows: usize,
+
+    /// The maximum age of the [Video][crate::Video]s, see [VideoStore::set_max_age].
+    max_age: Option<chrono::Duration>,
+
+    /// When the [Video][crate::Video]s were pruned the last time.
+    last_prune: chrono::NaiveDateTime,
+
+    /// The [VideoId]s of the `Arc<Mutex<V>>` updated in place, see [VideoStore::take_updated].
+    updated: HashSet<VideoId>,
 }
 
-impl<V: Hash + Clone + std::cmp::Eq> VideoStore<V> {
+impl<V: Video> VideoStore<V> {
     /// Create a new, empty [VideoStore].
     pub fn new() -> Self {
         VideoStore {
             videos: HashMap::new(),
+            known: HashMap::new(),
+            backend: None,
+            backend_rows: 0,
+            max_age: None,
+            last_prune: chrono::Local::now().naive_local(),
+            updated: HashSet::new(),
+        }
+    }
+
+    /// Set the persistent [VideoStoreBackend] of the [VideoStore].
+    ///
+    /// This will load all [Video][crate::Video]s from the backend, pruning the ones
+    /// first seen longer than `max_age` ago if given (see [VideoStore::set_max_age]).
+    /// Afterwards, every new [Video][crate::Video] will also be inserted into the backend.
+    pub fn set_backend(
+        &mut self,
+        mut backend: Box<dyn VideoStoreBackend<V>>,
+        max_age: Option<chrono::Duration>,
+    ) {
+        let loaded = backend.load();
+        log::debug!(
+            "Loaded {} videos from the video store backend",
+            loaded.len()
+        );
+        self.backend_rows = loaded.len();
+        // Later rows contain the newer information of the same video, but the first time
+        // a video was seen stays the earliest one.
+        for (video, first_seen) in loaded {
+            let first_seen = self
+                .known
+                .get(&video.id())
+                .map_or(first_seen, |(_, f)| (*f).min(first_seen));
+            self.known.insert(video.id(), (video, first_seen));
         }
+        self.backend = Some(backend);
+
+        if max_age.is_some() {
+            self.set_max_age(max_age);
+        }
+        self.compact_if_needed();
+    }
+
+    /// Set the maximum age of the [Video][crate::Video]s in the [VideoStore], pruning the ones
+    /// first seen longer than `max_age` ago (see [VideoStore::prune]).
+    ///
+    /// Afterwards, expired [Video][crate::Video]s are pruned regularly when getting or saving
+    /// [Video][crate::Video]s. If no maximum age is set, no [Video][crate::Video] is forgotten.
+    pub fn set_max_age(&mut self, max_age: Option<chrono::Duration>) {
+        self.max_age = max_age;
+        if let Some(max_age) = max_age {
+            self.prune(max_age);
+        }
+    }
+
+    /// Remove all [Video][crate::Video]s that were first seen longer than `max_age` ago.
+    ///
+    /// [Video][crate::Video]s that are still in use, i.e. their `Arc<Mutex<V>>` was not dropped yet,
+    /// are kept. This will also remove the [Video][crate::Video]s from the [VideoStoreBackend].
+    pub fn prune(&mut self, max_age: chrono::Duration) {
+        let now = chrono::Local::now().naive_local();
+        let oldest = now - max_age;
+        self.last_prune = now;
+
+        self.videos.retain(|_, v| v.strong_count() > 0);
+        let count = self.known.len();
+        let videos = &self.videos;
+        self.known
+            .retain(|id, (_, first_seen)| *first_seen >= oldest || videos.contains_key(id));
+        let pruned = count - self.known.len();
+        log::debug!("Pruned {} videos from the video store", pruned);
+
+        self.compact();
+    }
+
+    /// Replace the rows of the [VideoStoreBackend] with the current state of the known
+    /// [Video][crate::Video]s, dropping outdated rows.
+    fn compact(&mut self) {
+        if let Some(backend) = &mut self.backend {
+            let remaining = self.known.values().cloned().collect::<Vec<_>>();
+            backend.replace(&remaining);
+            self.backend_rows = remaining.len();
+        }
+    }
+
+    /// Compact the [VideoStoreBackend] if it contains more outdated rows than known
+    /// [Video][crate::Video]s, e.g. after reporting the progress of a [Video][crate::Video] often.
+    fn compact_if_needed(&mut self) {
+        if self.backend_rows > 2 * self.known.len() {
+            log::debug!("Compacting the video store backend");
+            self.compact();
+        }
+    }
+
+    /// Insert the [Video][crate::Video] into the [VideoStoreBackend], compacting it if needed.
+    fn insert_into_backend(&mut self, video: &V, first_seen: chrono::NaiveDateTime) {
+        if let Some(backend) = &mut self.backend {
+            backend.insert(video, first_seen);
+            self.backend_rows += 1;
+            self.compact_if_needed();
+        }
+    }
+
+    /// Prune the expired [Video][crate::Video]s if a maximum age is set and the last time
+    /// they were pruned is long enough ago.
+    fn prune_expired(&mut self) {
+        let due = self.last_prune + chrono::Duration::minutes(PRUNE_INTERVAL_MINUTES);
+        if let Some(max_age) = self.max_age {
+            if chrono::Local::now().naive_local() >= due {
+                self.prune(max_age);
+            }
+        }
+    }
+
+    /// Get the time the [Video][crate::Video] was first seen by the [VideoStore].
+    pub fn first_seen(&self, video: &V) -> Option<chrono::NaiveDateTime> {
+        self.known.get(&video.id()).map(|(_, f)| *f)
+    }
+
+    /// Whether no [Video][crate::Video]s are known to the [VideoStore], including the ones
+    /// loaded from the [VideoStoreBackend].
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty()
+    }
+
+    /// Get the `Arc<Mutex<V>>` of all [Video][crate::Video]s known to the [VideoStore],
+    /// including the ones loaded from the [VideoStoreBackend].
+    pub fn stored(&mut self) -> Vec<Arc<Mutex<V>>> {
+        let videos = self
+            .known
+            .values()
+            .map(|(v, _)| v.clone())
+            .collect::<Vec<_>>();
+        videos.iter().map(|v| self.get(v)).collect()
+    }
+
+    /// Select the [Video][crate::Video]s in the [Window] from all [Video][crate::Video]s known
+    /// to the [VideoStore], see [Window::select].
+    ///
+    /// In contrast to [VideoStore::stored], only the selected [Video][crate::Video]s are cloned
+    /// and they are not inserted as `Arc<Mutex<V>>`, use [VideoStore::get] for the ones actually needed.
+    pub fn select<F: Fn(&V) -> bool>(&self, window: &Window, keep: F) -> Vec<V> {
+        window.select(self.known.values().map(|(v, _)| v), keep)
+    }
+
+    /// Take the `Arc<Mutex<V>>` which were updated in place by [VideoStore::get] since the last
+    /// call, e.g. to update copies of them held elsewhere.
+    ///
+    /// The observers of these were already notified using [VideoEvent::Update][crate::VideoEvent::Update].
+    /// As this is called without holding the lock of the `Arc<Mutex<V>>`, they can be accessed safely.
+    pub fn take_updated(&mut self) -> Vec<Arc<Mutex<V>>> {
+        self.updated
+            .drain()
+            .filter_map(|id| self.videos.get(&id).and_then(|v| v.upgrade()))
+            .collect()
     }
 
     /// Get the `Arc<Mutex<V>>` from the [Video] `V`.
     ///
     /// This will either insert this video into the `VideoStore` or get a
     /// already existing instance.
+    ///
+    /// If the [Video] is already known but changed, the existing instance will be updated.
     pub fn get(&mut self, video: &V) -> Arc<Mutex<V>> {
-        if let Some(value) = self.videos.get(video) {
-            if let Some(strong) = value.upgrade() {
-                strong
+        self.prune_expired();
+
+        let id = video.id();
+        let strong = self.videos.get(&id).and_then(|v| v.upgrade());
+        // Clones of a video may share their observers, therefore only update one of them.
+        let changed_strong = strong.as_ref().map(|s| s.lock().unwrap().update(video));
+
+        if let Some((known, first_seen)) = self.known.remove(&id) {
+            let (updated, changed) = if let Some(strong) = &strong {
+                (strong.lock().unwrap().clone(), changed_strong == Some(true))
             } else {
-                let value = Arc::new(Mutex::new(video.clone()));
-                self.videos.insert(video.clone(), Arc::downgrade(&value));
-                value
+                let mut updated = known;
+                let changed = updated.update(video);
+                (updated, changed)
+            };
+            self.known.insert(id.clone(), (updated.clone(), first_seen));
+            if changed {
+                log::debug!("Updating video {} in the video store", id);
+                self.insert_into_backend(&updated, first_seen);
+                if strong.is_some() {
+                    self.updated.insert(id.clone());
+                }
             }
         } else {
-            let value = Arc::new(Mutex::new(video.clone()));
-            self.videos.insert(video.clone(), Arc::downgrade(&value));
+            let now = chrono::Local::now().naive_local();
+            self.known.insert(id.clone(), (video.clone(), now));
+            self.insert_into_backend(video, now);
+        }
+
+        if let Some(strong) = strong {
+            strong
+        } else {
+            // The stored video may contain more information, e.g. loaded from the backend.
+            let stored = self
+                .known
+                .get(&id)
+                .map(|(v, _)| v.clone())
+                .unwrap_or_else(|| video.clone());
+            let value = Arc::new(Mutex::new(stored));
+            self.videos.insert(id, Arc::downgrade(&value));
             value
         }
     }
+
+    /// Save the current state of the given, already known [Video] into the [VideoStore] and its
+    /// [VideoStoreBackend], e.g. after its state changed outside of the [VideoStore].
+    ///
+    /// Unknown [Video]s are ignored, use [VideoStore::get] to insert them.
+    pub fn save(&mut self, video: &V) {
+        self.prune_expired();
+
+        if let Some((known, first_seen)) = self.known.get_mut(&video.id()) {
+            *known = video.clone();
+            let first_seen = *first_seen;
+            self.insert_into_backend(video, first_seen);
+        }
+    }
+}
+
+impl<V: Video> Default for VideoStore<V> {
+    fn default() -> Self {
+        VideoStore::new()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::mock::MockVideo;
+    use crate::{ExpandedVideo, VideoEvent};
     use chrono::{NaiveDate, NaiveDateTime};
+    use tf_observer::{Observable, Observer};
 
     use super::*;
 
     fn make_video(datetime: NaiveDateTime) -> MockVideo {
-        let datetime_clone = datetime.clone();
+        let datetime_clone = datetime;
         let mut video = MockVideo::new();
         video.expect_uploaded().returning(move || datetime_clone);
+        video
+            .expect_url()
+            .returning(move || datetime_clone.to_string());
+        video.expect_title().returning(String::new);
+        video.expect_thumbnail_url().returning(String::new);
         video.expect_clone().returning(move || make_video(datetime));
         video
     }
 
+    fn make_titled_video(datetime: NaiveDateTime, title: &'static str) -> MockVideo {
+        let mut video = MockVideo::new();
+        video.expect_uploaded().returning(move || datetime);
+        video.expect_url().returning(move || datetime.to_string());
+        video.expect_title().returning(move || title.to_string());
+        video.expect_thumbnail_url().returning(String::new);
+        video
+            .expect_clone()
+            .returning(move || make_titled_video(datetime, title));
+        video
+    }
+
+    struct UpdateCounter(Arc<Mutex<usize>>);
+
+    impl Observer<VideoEvent> for UpdateCounter {
+        fn notify(&mut self, message: VideoEvent) {
+            if let VideoEvent::Update = message {
+                *self.0.lock().unwrap() += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn video_store_update() {
+        let date = NaiveDate::from_ymd_opt(2021, 8, 21)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let mut store = VideoStore::<ExpandedVideo<MockVideo>>::new();
+        let arc1 = store.get(&make_titled_video(date, "Title").into());
+
+        let updates = Arc::new(Mutex::new(0));
+        let observer: Arc<Mutex<Box<dyn Observer<VideoEvent> + Send>>> =
+            Arc::new(Mutex::new(Box::new(UpdateCounter(updates.clone()))));
+        arc1.lock().unwrap().attach(Arc::downgrade(&observer));
+        arc1.lock().unwrap().play();
+
+        let arc2 = store.get(&make_titled_video(date, "Title").into());
+        assert_eq!(*updates.lock().unwrap(), 0);
+        assert!(store.take_updated().is_empty());
+
+        let arc3 = store.get(&make_titled_video(date, "Edited Title").into());
+
+        assert!(Arc::ptr_eq(&arc1, &arc2));
+        assert!(Arc::ptr_eq(&arc1, &arc3));
+        assert_eq!(*updates.lock().unwrap(), 1);
+        assert_eq!(arc1.lock().unwrap().title(), "Edited Title");
+        let updated = store.take_updated();
+        assert_eq!(updated.len(), 1);
+        assert!(Arc::ptr_eq(&updated[0], &arc1));
+        assert!(store.take_updated().is_empty());
+        assert!(arc1.lock().unwrap().playing());
+        assert_eq!(store.stored().len(), 1);
+        assert_eq!(store.stored()[0].lock().unwrap().title(), "Edited Title");
+    }
+
     #[test]
     fn video_store_empty() {
         let store = VideoStore::<MockVideo>::new();
@@ -106,11 +418,161 @@ mod test {
             .unwrap()
             .and_hms_opt(0, 0, 0)
             .unwrap();
-        let arc1 = store.get(&make_video(date.clone()));
+        let arc1 = store.get(&make_video(date));
 
         let arc2 = store.get(&make_video(date));
 
         assert_eq!(store.videos.len(), 1);
         assert!(Arc::ptr_eq(&arc1, &arc2));
     }
+
+    type BackendVideos<V = MockVideo> = Arc<Mutex<Vec<(V, NaiveDateTime)>>>;
+
+    struct TestBackend<V = MockVideo>(BackendVideos<V>);
+
+    impl<V: Clone + Send> VideoStoreBackend<V> for TestBackend<V> {
+        fn load(&mut self) -> Vec<(V, NaiveDateTime)> {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn insert(&mut self, video: &V, first_seen: NaiveDateTime) {
+            self.0.lock().unwrap().push((video.clone(), first_seen));
+        }
+
+        fn replace(&mut self, videos: &[(V, NaiveDateTime)]) {
+            *self.0.lock().unwrap() = videos.to_vec();
+        }
+    }
+
+    #[test]
+    fn video_store_backend_load() {
+        let date = NaiveDate::from_ymd_opt(2021, 8, 21)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let first_seen = chrono::Local::now().naive_local();
+        let videos: BackendVideos = Arc::new(Mutex::new(vec![(make_video(date), first_seen)]));
+
+        let mut store = VideoStore::<MockVideo>::new();
+        store.set_backend(Box::new(TestBackend(videos.clone())), None);
+
+        let stored = store.stored();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(store.first_seen(&make_video(date)), Some(first_seen));
+
+        store.get(&make_video(date));
+        assert_eq!(videos.lock().unwrap().len(), 1);
+
+        store.get(&make_video(
+            NaiveDate::from_ymd_opt(2021, 8, 20)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        ));
+        assert_eq!(videos.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn video_store_backend_prune() {
+        let date_old = NaiveDate::from_ymd_opt(2021, 8, 20)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_new = NaiveDate::from_ymd_opt(2021, 8, 21)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let now = chrono::Local::now().naive_local();
+        let videos: BackendVideos = Arc::new(Mutex::new(vec![
+            (make_video(date_old), now - chrono::Duration::days(10)),
+            (make_video(date_new), now),
+        ]));
+
+        let mut store = VideoStore::<MockVideo>::new();
+        store.set_backend(
+            Box::new(TestBackend(videos.clone())),
+            Some(chrono::Duration::days(5)),
+        );
+
+        assert_eq!(store.stored().len(), 1);
+        assert!(store.first_seen(&make_video(date_old)).is_none());
+        assert_eq!(videos.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn video_store_backend_compact() {
+        let date = NaiveDate::from_ymd_opt(2021, 8, 21)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let first_seen = chrono::Local::now().naive_local();
+        let videos: BackendVideos = Arc::new(Mutex::new(vec![
+            (make_video(date), first_seen),
+            (make_video(date), first_seen),
+            (make_video(date), first_seen),
+        ]));
+
+        // Duplicate rows are dropped on load.
+        let mut store = VideoStore::<MockVideo>::new();
+        store.set_backend(Box::new(TestBackend(videos.clone())), None);
+        assert_eq!(videos.lock().unwrap().len(), 1);
+
+        for _ in 0..10 {
+            store.save(&make_video(date));
+            assert!(videos.lock().unwrap().len() <= 2);
+        }
+        assert_eq!(store.first_seen(&make_video(date)), Some(first_seen));
+    }
+
+    #[test]
+    fn video_store_max_age() {
+        let date_unused = NaiveDate::from_ymd_opt(2021, 8, 20)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let date_used = NaiveDate::from_ymd_opt(2021, 8, 21)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        let mut store = VideoStore::<MockVideo>::new();
+        drop(store.get(&make_video(date_unused)));
+        let used = store.get(&make_video(date_used));
+        assert_eq!(store.known.len(), 2);
+
+        store.set_max_age(Some(chrono::Duration::zero()));
+        assert!(store.first_seen(&make_video(date_unused)).is_none());
+        assert!(store.first_seen(&make_video(date_used)).is_some());
+        assert_eq!(store.videos.len(), 1);
+        assert!(Arc::ptr_eq(&used, &store.get(&make_video(date_used))));
+    }
+
+    #[test]
+    fn video_store_save() {
+        let date = NaiveDate::from_ymd_opt(2021, 8, 21)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let videos: BackendVideos<ExpandedVideo<MockVideo>> = Arc::new(Mutex::new(vec![]));
+
+        let mut store = VideoStore::<ExpandedVideo<MockVideo>>::new();
+        store.set_backend(Box::new(TestBackend(videos.clone())), None);
+        let arc = store.get(&make_video(date).into());
+        arc.lock().unwrap().set_watched(true);
+        store.save(&arc.lock().unwrap().clone());
+        drop(arc);
+
+        assert_eq!(videos.lock().unwrap().len(), 2);
+        assert!(videos.lock().unwrap()[1].0.watched());
+
+        // A unknown video is not saved.
+        store.save(&make_video(date - chrono::Duration::days(1)).into());
+        assert_eq!(videos.lock().unwrap().len(), 2);
+
+        // The video fetched again keeps the progress of the stored one.
+        let mut store = VideoStore::<ExpandedVideo<MockVideo>>::new();
+        store.set_backend(Box::new(TestBackend(videos.clone())), None);
+        let arc = store.get(&make_video(date).into());
+        assert!(arc.lock().unwrap().watched());
+    }
 }
@@ -0,0 +1,280 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use chrono::NaiveDateTime;
+
+use crate::{Video, VideoId};
+
+/// The stable position of a [Video] in a feed sorted newest first.
+///
+/// [WindowCursor]s are ordered like the feed, i.e. by their upload time newest first and by
+/// their [VideoId] for [Video]s uploaded at the same time. In contrast to an offset into the feed,
+/// the cursor stays valid if newer [Video]s are added in front of the feed.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowCursor {
+    /// The upload time of the [Video].
+    uploaded: Reverse<NaiveDateTime>,
+    /// The [VideoId] of the [Video].
+    id: VideoId,
+}
+
+impl WindowCursor {
+    /// Get the [WindowCursor] of the given [Video].
+    pub fn of<V: Video>(video: &V) -> Self {
+        WindowCursor {
+            uploaded: Reverse(video.uploaded()),
+            id: video.id(),
+        }
+    }
+
+    /// The upload time of the [Video] at the cursor.
+    pub fn uploaded(&self) -> NaiveDateTime {
+        self.uploaded.0
+    }
+
+    /// The [VideoId] of the [Video] at the cursor.
+    pub fn id(&self) -> VideoId {
+        self.id.clone()
+    }
+}
+
+/// A window into a feed of [Video]s sorted newest first.
+///
+/// The window contains the [Video]s uploaded in the range from [since][Window::with_since]
+/// (inclusive) to [until][Window::with_until] (exclusive) which come after the
+/// [cursor][Window::with_after] in the feed, at most [limit][Window::with_limit] of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Window {
+    /// The oldest upload time of the [Video]s in the window.
+    since: Option<NaiveDateTime>,
+    /// The upload time all [Video]s in the window are older than.
+    until: Option<NaiveDateTime>,
+    /// The cursor all [Video]s in the window come after.
+    after: Option<WindowCursor>,
+    /// The maximum number of [Video]s in the window.
+    limit: Option<usize>,
+}
+
+impl Window {
+    /// Create a new [Window] containing the whole feed.
+    pub fn new() -> Self {
+        Window::default()
+    }
+
+    /// Only contain [Video]s uploaded at or after the given time.
+    pub fn with_since(mut self, since: Option<NaiveDateTime>) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Only contain [Video]s uploaded before the given time.
+    pub fn with_until(mut self, until: Option<NaiveDateTime>) -> Self {
+        self.until = until;
+        self
+    }
+
+    /// Only contain [Video]s after the given [WindowCursor], e.g. the last [Video] of the previous window.
+    pub fn with_after(mut self, after: Option<WindowCursor>) -> Self {
+        self.after = after;
+        self
+    }
+
+    /// Contain at most `limit` [Video]s.
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// The oldest upload time of the [Video]s in the window.
+    pub fn since(&self) -> Option<NaiveDateTime> {
+        self.since
+    }
+
+    /// The upload time all [Video]s in the window are older than.
+    pub fn until(&self) -> Option<NaiveDateTime> {
+        self.until
+    }
+
+    /// The cursor all [Video]s in the window come after.
+    pub fn after(&self) -> Option<WindowCursor> {
+        self.after.clone()
+    }
+
+    /// The maximum number of [Video]s in the window.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Whether the [Video] is in the range and after the cursor of the window, ignoring the limit.
+    // `Option::is_none_or` would raise the minimum supported Rust version to 1.82.
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn contains<V: Video>(&self, video: &V) -> bool {
+        let uploaded = video.uploaded();
+        self.since.map_or(true, |since| uploaded >= since)
+            && self.until.map_or(true, |until| uploaded < until)
+            && self
+                .after
+                .as_ref()
+                .map_or(true, |after| &WindowCursor::of(video) > after)
+    }
+
+    /// Select the [Video]s in the window from the given [Video]s, sorted newest first.
+    ///
+    /// Only [Video]s for which `keep` returns `true` are part of the window. Only the selected
+    /// [Video]s are cloned, and with a limit, only the `limit` first [Video]s are kept while selecting.
+    // `Option::is_none_or` would raise the minimum supported Rust version to 1.82.
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn select<'a, V: Video + 'a, I: IntoIterator<Item = &'a V>, F: Fn(&V) -> bool>(
+        &self,
+        videos: I,
+        keep: F,
+    ) -> Vec<V> {
+        let candidates = videos.into_iter().filter(|v| self.contains(*v));
+        let selected = match self.limit {
+            Some(limit) => {
+                // The heap keeps the first `limit` videos, the last of them on top.
+                let candidate_count = candidates.size_hint().1.unwrap_or_default();
+                let mut heap =
+                    BinaryHeap::with_capacity(limit.min(candidate_count).saturating_add(1));
+                for video in candidates {
+                    let cursor = WindowCursor::of(video);
+                    if heap.len() == limit
+                        && heap
+                            .peek()
+                            .map_or(true, |top: &Selected<V>| cursor >= top.0)
+                    {
+                        continue;
+                    }
+                    if keep(video) {
+                        heap.push(Selected(cursor, video));
+                        if heap.len() > limit {
+                            heap.pop();
+                        }
+                    }
+                }
+                heap.into_sorted_vec()
+            }
+            None => {
+                let mut selected = candidates
+                    .filter(|v| keep(v))
+                    .map(|v| Selected(WindowCursor::of(v), v))
+                    .collect::<Vec<_>>();
+                selected.sort();
+                selected
+            }
+        };
+        selected.into_iter().map(|s| s.1.clone()).collect()
+    }
+}
+
+/// A [Video] selected by [Window::select], ordered by its [WindowCursor].
+struct Selected<'a, V>(WindowCursor, &'a V);
+
+impl<V> PartialEq for Selected<'_, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<V> Eq for Selected<'_, V> {}
+
+impl<V> PartialOrd for Selected<'_, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<V> Ord for Selected<'_, V> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::mock::MockVideo;
+
+    use super::*;
+
+    fn make_video(day: u32, url: &'static str) -> MockVideo {
+        let mut video = MockVideo::new();
+        video.expect_uploaded().returning(move || {
+            NaiveDate::from_ymd_opt(2021, 8, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        });
+        video.expect_url().returning(move || url.to_string());
+        video.expect_clone().returning(move || make_video(day, url));
+        video
+    }
+
+    fn urls(videos: &[MockVideo]) -> Vec<String> {
+        videos.iter().map(|v| v.url()).collect()
+    }
+
+    #[test]
+    fn window_select() {
+        let videos = vec![
+            make_video(1, "a"),
+            make_video(3, "b"),
+            make_video(2, "c"),
+            make_video(3, "d"),
+            make_video(4, "e"),
+        ];
+        let day = |d| {
+            NaiveDate::from_ymd_opt(2021, 8, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+
+        let all = Window::new().select(&videos, |_| true);
+        assert_eq!(urls(&all), vec!["e", "b", "d", "c", "a"]);
+
+        let range = Window::new()
+            .with_since(Some(day(2)))
+            .with_until(Some(day(4)));
+        assert_eq!(urls(&range.select(&videos, |_| true)), vec!["b", "d", "c"]);
+        assert_eq!(
+            urls(&range.select(&videos, |v| v.url() != "d")),
+            vec!["b", "c"]
+        );
+
+        let first = Window::new().with_limit(Some(2));
+        let page1 = first.select(&videos, |_| true);
+        assert_eq!(urls(&page1), vec!["e", "b"]);
+
+        let next = first.with_after(Some(WindowCursor::of(page1.last().unwrap())));
+        let page2 = next.select(&videos, |_| true);
+        assert_eq!(urls(&page2), vec!["d", "c"]);
+
+        // The cursor stays valid when newer videos are added in front.
+        let mut more = videos.clone();
+        more.push(make_video(5, "f"));
+        assert_eq!(urls(&next.select(&more, |_| true)), vec!["d", "c"]);
+
+        let unlimited = Window::new().with_limit(Some(usize::MAX));
+        assert_eq!(urls(&unlimited.select(&videos, |_| true)), urls(&all));
+    }
+}
@@ -107,13 +107,13 @@ pub enum FilterEvent<T> {
 impl<T: Clone> Observable<FilterEvent<T>> for FilterGroup<T> {
     fn attach(
         &mut self,
-        observer: Weak<Mutex<Box<(dyn Observer<FilterEvent<T>> + Send + 'static)>>>,
+        observer: Weak<Mutex<Box<dyn Observer<FilterEvent<T>> + Send + 'static>>>,
     ) {
         self.observers.attach(observer);
     }
     fn detach(
         &mut self,
-        observer: Weak<Mutex<Box<(dyn Observer<FilterEvent<T>> + Send + 'static)>>>,
+        observer: Weak<Mutex<Box<dyn Observer<FilterEvent<T>> + Send + 'static>>>,
     ) {
         self.observers.detach(observer);
     }
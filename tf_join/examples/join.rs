@@ -33,14 +33,14 @@ use tf_lbry::LbrySubscription;
 use tf_pt::PTSubscription;
 
 #[cfg(feature = "youtube")]
-const YT_SUBSCRIPTION_IDS: &'static [&'static str] = &["UCj1VqrHhDte54oLgPG4xpuQ"];
+const YT_SUBSCRIPTION_IDS: &[&str] = &["UCj1VqrHhDte54oLgPG4xpuQ"];
 #[cfg(feature = "peertube")]
-const PT_SUBSCRIPTION_IDS: &'static [(&'static str, &'static str)] = &[(
+const PT_SUBSCRIPTION_IDS: &[(&str, &str)] = &[(
     "https://peertube.linuxrocks.online",
     "chrisweredigital@share.tube",
 )];
 #[cfg(feature = "lbry")]
-const LBRY_SUBSCRIPTION_IDS: &'static [&'static str] = &["@SomeOrdinaryGamers:a"];
+const LBRY_SUBSCRIPTION_IDS: &[&str] = &["@SomeOrdinaryGamers:a"];
 // -- Add const example here.
 #[cfg(test)]
 const TEST_SUBSCRIPTION_NAMES: &'static [&'static str] = &["Test1", "Test2"];
@@ -90,6 +90,12 @@ pub async fn main() {
             AnyVideo::Test(_v) => "Test",
         };
 
-        println!("{}: {} - {} ({})", source, video.title(), video.subscription(), video.uploaded())
+        println!(
+            "{}: {} - {} ({})",
+            source,
+            video.title(),
+            video.subscription(),
+            video.uploaded()
+        )
     }
 }
@@ -0,0 +1,699 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A small expression language for filtering [AnyVideo]s.
+//!
+//! A [FilterExpression] consists of conditions combined using `AND`, `OR`, `NOT` and parentheses,
+//! e.g. `platform:youtube AND title:/live/i AND NOT channel:"Foo" AND uploaded<7d`.
+//! Conditions following each other without an operator are combined using `AND`.
+//!
+//! The supported conditions are:
+//!
+//! - `platform:<platform>`: The [Platform] of the [AnyVideo], e.g. `youtube`.
+//! - `title:<text>`: The [Video::title][tf_core::Video::title] of the [AnyVideo].
+//! - `channel:<text>` or `subscription:<text>`: The
+//!   [Subscription::name][tf_core::Subscription::name] of the [AnyVideo].
+//! - `uploaded<duration>`: The time since the [AnyVideo] was uploaded, compared with `<`, `<=`,
+//!   `>` or `>=`. The duration is a number followed by `h` (hours), `d` (days) or `w` (weeks).
+//! - `<text>`: Either the [Video::title][tf_core::Video::title] or the
+//!   [Subscription::name][tf_core::Subscription::name] of the [AnyVideo].
+//!
+//! A text is either a word or a quoted string (e.g. `"Foo Bar"`) which must be contained in the
+//! value ignoring case, or a regular expression (e.g. `/live/i`) which must match the value. The
+//! only flag supported for regular expressions is `i` to ignore case.
+
+use std::str::FromStr;
+
+use regex::Regex;
+use tf_filter::Filter;
+
+use crate::{filter::FilterTarget, AnyVideo, Platform};
+
+/// An error parsing a [FilterExpression].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterParseError {
+    /// The byte position in the input the error occurred at.
+    pub position: usize,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl FilterParseError {
+    fn new<S: AsRef<str>>(position: usize, message: S) -> Self {
+        FilterParseError {
+            position,
+            message: message.as_ref().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for FilterParseError {}
+
+/// A text to match against, see the [module documentation][self].
+#[derive(Debug, Clone)]
+pub enum TextMatch {
+    /// The value must contain the text, ignoring case.
+    Contains(String),
+    /// The value must match the regular expression.
+    Regex {
+        /// The regular expression as written, without flags.
+        pattern: String,
+        /// The flags of the regular expression.
+        flags: String,
+        /// The compiled regular expression.
+        regex: Regex,
+    },
+}
+
+impl TextMatch {
+    /// Whether the given value matches.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            TextMatch::Contains(text) => value.to_lowercase().contains(&text.to_lowercase()),
+            TextMatch::Regex { regex, .. } => regex.is_match(value),
+        }
+    }
+}
+
+/// How to compare the time since a [AnyVideo] was uploaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `<`
+    Less,
+    /// `<=`
+    LessEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterEqual,
+}
+
+impl Comparison {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+        }
+    }
+}
+
+/// A single condition of a [FilterExpression].
+#[derive(Debug, Clone)]
+pub enum Condition {
+    /// `platform:<platform>`
+    Platform(Platform),
+    /// `title:<text>`
+    Title(TextMatch),
+    /// `channel:<text>`
+    Channel(TextMatch),
+    /// `uploaded<duration>`, the duration is given as non-negative amount and unit.
+    Uploaded(Comparison, i32, char),
+    /// `<text>`
+    Text(TextMatch),
+}
+
+impl Condition {
+    fn matches<T: FilterTarget>(&self, video: &T) -> bool {
+        let channel = || video.channel().unwrap_or_default();
+        match self {
+            Condition::Platform(platform) => &video.platform() == platform,
+            Condition::Title(text) => text.matches(&video.title()),
+            Condition::Channel(text) => text.matches(&channel()),
+            Condition::Text(text) => text.matches(&video.title()) || text.matches(&channel()),
+            Condition::Uploaded(comparison, amount, unit) => {
+                let age = chrono::Local::now().naive_local() - video.uploaded();
+                let duration = unit_duration(*unit) * *amount;
+                match comparison {
+                    Comparison::Less => age < duration,
+                    Comparison::LessEqual => age <= duration,
+                    Comparison::Greater => age > duration,
+                    Comparison::GreaterEqual => age >= duration,
+                }
+            }
+        }
+    }
+}
+
+/// The duration of one unit of a `uploaded`-condition.
+fn unit_duration(unit: char) -> chrono::Duration {
+    match unit {
+        'h' => chrono::Duration::hours(1),
+        'd' => chrono::Duration::days(1),
+        _ => chrono::Duration::weeks(1),
+    }
+}
+
+/// A parsed filter expression, see the [module documentation][self].
+///
+/// A [FilterExpression] can be parsed using [FromStr] and converted back into a string using
+/// [Display][std::fmt::Display] for storage.
+#[derive(Debug, Clone)]
+pub enum FilterExpression {
+    /// Both expressions must match.
+    And(Box<FilterExpression>, Box<FilterExpression>),
+    /// Any of the expressions must match.
+    Or(Box<FilterExpression>, Box<FilterExpression>),
+    /// The expression must not match.
+    Not(Box<FilterExpression>),
+    /// The [Condition] must match.
+    Condition(Condition),
+}
+
+impl FilterExpression {
+    /// Whether the expression matches on the [FilterTarget], see [Filter::matches].
+    pub(crate) fn matches_target<T: FilterTarget>(&self, video: &T) -> bool {
+        match self {
+            FilterExpression::And(e1, e2) => e1.matches_target(video) && e2.matches_target(video),
+            FilterExpression::Or(e1, e2) => e1.matches_target(video) || e2.matches_target(video),
+            FilterExpression::Not(e) => !e.matches_target(video),
+            FilterExpression::Condition(c) => c.matches(video),
+        }
+    }
+}
+
+impl Filter for FilterExpression {
+    type Item = AnyVideo;
+
+    fn matches(&self, video: &AnyVideo) -> bool {
+        self.matches_target(video)
+    }
+}
+
+impl PartialEq for FilterExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for FilterExpression {}
+
+impl std::hash::Hash for FilterExpression {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.to_string().hash(state);
+    }
+}
+
+impl FromStr for FilterExpression {
+    type Err = FilterParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: s.len(),
+        };
+        let expression = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(FilterParseError::new(token.position, "Unexpected token"));
+        }
+        Ok(expression)
+    }
+}
+
+impl std::fmt::Display for FilterExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterExpression::Or(e1, e2) => write!(f, "{} OR {}", e1, e2),
+            FilterExpression::And(e1, e2) => {
+                write_operand(f, e1, matches!(**e1, FilterExpression::Or(..)))?;
+                write!(f, " AND ")?;
+                write_operand(f, e2, matches!(**e2, FilterExpression::Or(..)))
+            }
+            FilterExpression::Not(e) => {
+                write!(f, "NOT ")?;
+                write_operand(
+                    f,
+                    e,
+                    !matches!(
+                        **e,
+                        FilterExpression::Condition(_) | FilterExpression::Not(_)
+                    ),
+                )
+            }
+            FilterExpression::Condition(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+/// Write the given [FilterExpression], in parentheses if needed.
+fn write_operand(
+    f: &mut std::fmt::Formatter<'_>,
+    expression: &FilterExpression,
+    parentheses: bool,
+) -> std::fmt::Result {
+    if parentheses {
+        write!(f, "({})", expression)
+    } else {
+        write!(f, "{}", expression)
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::Platform(p) => write!(f, "platform:{}", String::from(p.clone())),
+            Condition::Title(t) => write!(f, "title:{}", t),
+            Condition::Channel(t) => write!(f, "channel:{}", t),
+            Condition::Uploaded(c, amount, unit) => {
+                write!(f, "uploaded{}{}{}", c.as_str(), amount, unit)
+            }
+            Condition::Text(t) => write!(f, "{}", t),
+        }
+    }
+}
+
+impl std::fmt::Display for TextMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextMatch::Contains(text) => {
+                let is_word = !text.is_empty()
+                    && !text.starts_with('/')
+                    && !matches!(text.as_str(), "AND" | "OR" | "NOT")
+                    && text.chars().all(is_word_char);
+                if is_word {
+                    write!(f, "{}", text)
+                } else {
+                    write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+                }
+            }
+            TextMatch::Regex { pattern, flags, .. } => {
+                write!(f, "/")?;
+                let mut chars = pattern.chars();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            write!(f, "\\")?;
+                            if let Some(next) = chars.next() {
+                                write!(f, "{}", next)?;
+                            }
+                        }
+                        '/' => write!(f, "\\/")?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "/{}", flags)
+            }
+        }
+    }
+}
+
+/// Whether the character may be part of a unquoted word.
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | ':' | '<' | '>' | '=' | '"')
+}
+
+/// The kinds of tokens of a [FilterExpression].
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    OpenParen,
+    CloseParen,
+    Colon,
+    Comparison(Comparison),
+    Word(String),
+    Quoted(String),
+    Regex(String, String),
+}
+
+/// A token of a [FilterExpression] together with its position.
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+/// Split the input into [Token]s.
+fn tokenize(input: &str) -> Result<Vec<Token>, FilterParseError> {
+    let mut tokens = vec![];
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '(' => TokenKind::OpenParen,
+            ')' => TokenKind::CloseParen,
+            ':' => TokenKind::Colon,
+            '<' | '>' => {
+                let equal = chars.next_if(|(_, c)| c == &'=').is_some();
+                TokenKind::Comparison(match (c, equal) {
+                    ('<', false) => Comparison::Less,
+                    ('<', true) => Comparison::LessEqual,
+                    ('>', false) => Comparison::Greater,
+                    _ => Comparison::GreaterEqual,
+                })
+            }
+            '=' => return Err(FilterParseError::new(position, "Unexpected '='")),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => text.push(c),
+                            None => break,
+                        },
+                        Some((_, c)) => text.push(c),
+                        None => return Err(FilterParseError::new(position, "Unterminated string")),
+                    }
+                }
+                TokenKind::Quoted(text)
+            }
+            '/' => {
+                let mut pattern = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '/')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '/')) => pattern.push('/'),
+                            Some((_, c)) => {
+                                pattern.push('\\');
+                                pattern.push(c);
+                            }
+                            None => pattern.push('\\'),
+                        },
+                        Some((_, c)) => pattern.push(c),
+                        None => {
+                            return Err(FilterParseError::new(
+                                position,
+                                "Unterminated regular expression",
+                            ))
+                        }
+                    }
+                }
+                let mut flags = String::new();
+                while let Some((_, c)) = chars.next_if(|(_, c)| c.is_alphanumeric()) {
+                    flags.push(c);
+                }
+                TokenKind::Regex(pattern, flags)
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                TokenKind::Word(word)
+            }
+        };
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser for [FilterExpression]s.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// The length of the input, used as position for errors at the end of the input.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, FilterParseError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| FilterParseError::new(self.end, "Unexpected end of input"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token { kind: TokenKind::Word(w), .. }) if w == keyword)
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpression, FilterParseError> {
+        let mut expression = self.parse_and()?;
+        while self.peek_keyword("OR") {
+            self.position += 1;
+            let right = self.parse_and()?;
+            expression = FilterExpression::Or(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpression, FilterParseError> {
+        let mut expression = self.parse_not()?;
+        loop {
+            if self.peek_keyword("AND") {
+                self.position += 1;
+            } else if self.peek().is_none()
+                || self.peek_keyword("OR")
+                || matches!(self.peek().map(|t| &t.kind), Some(TokenKind::CloseParen))
+            {
+                break;
+            }
+            let right = self.parse_not()?;
+            expression = FilterExpression::And(Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_not(&mut self) -> Result<FilterExpression, FilterParseError> {
+        if self.peek_keyword("NOT") {
+            self.position += 1;
+            return Ok(FilterExpression::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<FilterExpression, FilterParseError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::OpenParen => {
+                let expression = self.parse_or()?;
+                match self.next() {
+                    Ok(Token {
+                        kind: TokenKind::CloseParen,
+                        ..
+                    }) => Ok(expression),
+                    Ok(t) => Err(FilterParseError::new(t.position, "Expected ')'")),
+                    Err(_) => Err(FilterParseError::new(self.end, "Expected ')'")),
+                }
+            }
+            TokenKind::Word(word) if matches!(word.as_str(), "AND" | "OR") => Err(
+                FilterParseError::new(token.position, format!("Unexpected '{}'", word)),
+            ),
+            TokenKind::Word(word) => match self.peek().map(|t| t.kind.clone()) {
+                Some(TokenKind::Colon) => {
+                    self.position += 1;
+                    self.parse_field(&word, token.position)
+                }
+                Some(TokenKind::Comparison(comparison)) => {
+                    self.position += 1;
+                    self.parse_comparison(&word, token.position, comparison)
+                }
+                _ => Ok(FilterExpression::Condition(Condition::Text(
+                    TextMatch::Contains(word),
+                ))),
+            },
+            TokenKind::Quoted(_) | TokenKind::Regex(_, _) => {
+                let text = text_match(token)?;
+                Ok(FilterExpression::Condition(Condition::Text(text)))
+            }
+            _ => Err(FilterParseError::new(token.position, "Unexpected token")),
+        }
+    }
+
+    fn parse_field(
+        &mut self,
+        field: &str,
+        position: usize,
+    ) -> Result<FilterExpression, FilterParseError> {
+        let value = self.next()?;
+        let condition = match field.to_lowercase().as_str() {
+            "platform" => match value.kind {
+                TokenKind::Word(ref p) | TokenKind::Quoted(ref p) => {
+                    Condition::Platform(Platform::from_str(p).map_err(|_| {
+                        FilterParseError::new(value.position, format!("Unknown platform '{}'", p))
+                    })?)
+                }
+                _ => return Err(FilterParseError::new(value.position, "Expected a platform")),
+            },
+            "title" => Condition::Title(text_match(value)?),
+            "channel" | "subscription" => Condition::Channel(text_match(value)?),
+            _ => {
+                return Err(FilterParseError::new(
+                    position,
+                    format!("Unknown field '{}'", field),
+                ))
+            }
+        };
+        Ok(FilterExpression::Condition(condition))
+    }
+
+    fn parse_comparison(
+        &mut self,
+        field: &str,
+        position: usize,
+        comparison: Comparison,
+    ) -> Result<FilterExpression, FilterParseError> {
+        if field.to_lowercase() != "uploaded" {
+            return Err(FilterParseError::new(
+                position,
+                format!("Field '{}' can not be compared", field),
+            ));
+        }
+        let value = self.next()?;
+        let duration = match value.kind {
+            TokenKind::Word(ref d) => d.clone(),
+            _ => return Err(FilterParseError::new(value.position, "Expected a duration")),
+        };
+        let invalid = || {
+            FilterParseError::new(
+                value.position,
+                format!("Invalid duration '{}', expected e.g. '7d'", duration),
+            )
+        };
+        let (amount, unit) = match duration.char_indices().last() {
+            Some((index, unit @ ('h' | 'd' | 'w'))) => (&duration[..index], unit),
+            _ => return Err(invalid()),
+        };
+        // The amount must fit into a `i32` to compute the duration without overflowing.
+        let amount = amount
+            .parse::<u32>()
+            .ok()
+            .and_then(|a| i32::try_from(a).ok())
+            .ok_or_else(invalid)?;
+        Ok(FilterExpression::Condition(Condition::Uploaded(
+            comparison, amount, unit,
+        )))
+    }
+}
+
+/// Convert a [Token] into a [TextMatch].
+fn text_match(token: Token) -> Result<TextMatch, FilterParseError> {
+    match token.kind {
+        TokenKind::Word(text) | TokenKind::Quoted(text) => Ok(TextMatch::Contains(text)),
+        TokenKind::Regex(pattern, flags) => {
+            if let Some(flag) = flags.chars().find(|f| f != &'i') {
+                return Err(FilterParseError::new(
+                    token.position,
+                    format!("Unknown flag '{}'", flag),
+                ));
+            }
+            let full_pattern = if flags.is_empty() {
+                pattern.clone()
+            } else {
+                format!("(?{}){}", flags, pattern)
+            };
+            let regex = Regex::new(&full_pattern).map_err(|e| {
+                FilterParseError::new(token.position, format!("Invalid regular expression: {}", e))
+            })?;
+            Ok(TextMatch::Regex {
+                pattern,
+                flags,
+                regex,
+            })
+        }
+        _ => Err(FilterParseError::new(token.position, "Expected a text")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tf_core::ExpandedVideo;
+    use tf_test::{TestSubscription, TestVideo};
+
+    fn video(title: &str, channel: &str) -> AnyVideo {
+        Arc::new(Mutex::new(ExpandedVideo::from(TestVideo::new(
+            title,
+            TestSubscription::new(channel),
+        ))))
+        .into()
+    }
+
+    fn parse(input: &str) -> FilterExpression {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn expression_match() {
+        let live = video("Live Stream", "Foo");
+        let other = video("Tutorial", "Bar");
+
+        assert!(parse("platform:test").matches(&live));
+        assert!(parse("title:/live/i").matches(&live));
+        assert!(!parse("title:/live/").matches(&live));
+        assert!(parse("channel:\"foo\"").matches(&live));
+        assert!(parse("NOT channel:Foo").matches(&other));
+        assert!(parse("stream OR bar").matches(&other));
+        assert!(!parse("stream bar").matches(&other));
+        assert!(parse("platform:test AND title:/live/i AND NOT channel:\"Bar\"").matches(&live));
+        assert!(
+            !parse("platform:test AND (title:/live/i OR channel:Bar) AND uploaded<7d")
+                .matches(&live)
+        );
+        assert!(parse("uploaded>7d").matches(&live));
+        assert!(parse("uploaded>=1w AND uploaded>2h").matches(&other));
+    }
+
+    #[test]
+    fn expression_round_trip() {
+        for input in [
+            "platform:test AND title:/live/i AND NOT channel:\"Foo Bar\" AND uploaded<7d",
+            "(stream OR bar) AND NOT (a OR b)",
+            "title:/a\\/b\\d/ OR channel:\"say \\\"hi\\\"\"",
+            "NOT NOT word",
+        ] {
+            let expression = parse(input);
+            assert_eq!(expression.to_string(), input);
+            assert_eq!(parse(&expression.to_string()), expression);
+        }
+
+        assert_eq!(
+            parse("stream  bar  subscription:x").to_string(),
+            "stream AND bar AND channel:x"
+        );
+    }
+
+    #[test]
+    fn expression_errors() {
+        let error = |input: &str| FilterExpression::from_str(input).unwrap_err();
+
+        assert_eq!(error("title:").position, 6);
+        assert_eq!(error("foo:bar").position, 0);
+        assert_eq!(error("a AND (b OR c").position, 13);
+        assert_eq!(error("a AND uploaded<7x").position, 15);
+        assert_eq!(error("uploaded<é").position, 9);
+        assert_eq!(error("uploaded<7é").position, 9);
+        assert_eq!(error("uploaded<d").position, 9);
+        assert_eq!(error("uploaded<3000000000w").position, 9);
+        assert_eq!(error("platform:unknown").position, 9);
+        assert_eq!(error("title:/(/").position, 6);
+        assert_eq!(error("channel:\"abc").position, 8);
+        assert_eq!(error("a OR OR b").position, 5);
+        assert_eq!(error("a )").position, 2);
+    }
+}
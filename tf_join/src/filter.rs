@@ -22,13 +22,21 @@ use std::str::FromStr;
 
 use regex::Regex;
 
-use crate::{AnyVideo, Platform};
+use crate::{AnyVideo, FilterExpression, Platform};
 
 use tf_core::{Subscription, Video};
 use tf_filter::Filter;
 
 /// A [Filter] for filtering [AnyVideo]s.
+///
+/// With the `serde` feature, the regular expressions are serialized as strings and validated
+/// when deserializing.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "AnyVideoFilterRepr", into = "AnyVideoFilterRepr")
+)]
 pub struct AnyVideoFilter {
     /// Filter the [Platform].
     ///
@@ -44,6 +52,11 @@ pub struct AnyVideoFilter {
     ///
     /// If this is `None`, the [Subscription::name] will be ignored.
     subscription: Option<Regex>,
+
+    /// Filter using a [FilterExpression].
+    ///
+    /// If this is `None`, no expression will be evaluated.
+    expression: Option<FilterExpression>,
 }
 
 impl PartialEq for AnyVideoFilter {
@@ -53,6 +66,7 @@ impl PartialEq for AnyVideoFilter {
                 == other.title.as_ref().map(|r| r.to_string())
             && self.subscription.as_ref().map(|r| r.to_string())
                 == other.subscription.as_ref().map(|r| r.to_string())
+            && self.expression == other.expression
     }
 }
 
@@ -66,6 +80,7 @@ impl std::hash::Hash for AnyVideoFilter {
             .as_ref()
             .map(|r| r.to_string())
             .hash(state);
+        self.expression.hash(state);
     }
 }
 
@@ -83,9 +98,31 @@ impl AnyVideoFilter {
             platform,
             title,
             subscription,
+            expression: None,
+        }
+    }
+
+    /// Create a new [AnyVideoFilter] matching a [AnyVideo] using a [FilterExpression].
+    pub fn from_expression(expression: FilterExpression) -> Self {
+        AnyVideoFilter {
+            platform: None,
+            title: None,
+            subscription: None,
+            expression: Some(expression),
         }
     }
 
+    /// Set the [FilterExpression] which must match in addition to the other fields.
+    pub fn with_expression(mut self, expression: Option<FilterExpression>) -> Self {
+        self.expression = expression;
+        self
+    }
+
+    /// Give the [FilterExpression] if it is set.
+    pub fn expression(&self) -> Option<&FilterExpression> {
+        self.expression.as_ref()
+    }
+
     /// Give the title-regex as a String if the title-regex is set.
     pub fn title_str(&self) -> Option<String> {
         self.title.clone().map(|r| r.to_string())
@@ -97,10 +134,62 @@ impl AnyVideoFilter {
     }
 }
 
-impl Filter for AnyVideoFilter {
-    type Item = AnyVideo;
+/// The properties of a video a [AnyVideoFilter] matches on.
+///
+/// This allows matching borrowed videos of the pipelines without wrapping them into a [AnyVideo].
+pub(crate) trait FilterTarget {
+    /// The [Platform] of the video.
+    fn platform(&self) -> Platform;
+    /// The [Video::title] of the video.
+    fn title(&self) -> String;
+    /// The [Subscription::name] of the video.
+    fn channel(&self) -> Option<String>;
+    /// The [Video::uploaded] of the video.
+    fn uploaded(&self) -> chrono::NaiveDateTime;
+}
 
-    fn matches(&self, video: &<Self as Filter>::Item) -> bool {
+impl FilterTarget for AnyVideo {
+    fn platform(&self) -> Platform {
+        AnyVideo::platform(self)
+    }
+
+    fn title(&self) -> String {
+        Video::title(self)
+    }
+
+    fn channel(&self) -> Option<String> {
+        self.subscription().name()
+    }
+
+    fn uploaded(&self) -> chrono::NaiveDateTime {
+        Video::uploaded(self)
+    }
+}
+
+/// A borrowed [Video] of a [Platform].
+pub(crate) struct PlatformVideo<'a, V>(pub(crate) Platform, pub(crate) &'a V);
+
+impl<V: Video> FilterTarget for PlatformVideo<'_, V> {
+    fn platform(&self) -> Platform {
+        self.0.clone()
+    }
+
+    fn title(&self) -> String {
+        self.1.title()
+    }
+
+    fn channel(&self) -> Option<String> {
+        self.1.subscription().name()
+    }
+
+    fn uploaded(&self) -> chrono::NaiveDateTime {
+        self.1.uploaded()
+    }
+}
+
+impl AnyVideoFilter {
+    /// Whether the filter matches on the [FilterTarget], see [Filter::matches].
+    pub(crate) fn matches_target<T: FilterTarget>(&self, video: &T) -> bool {
         if let Some(platform) = &self.platform {
             if &video.platform() != platform {
                 return false;
@@ -114,12 +203,13 @@ impl Filter for AnyVideoFilter {
         }
 
         if let Some(subscription) = &self.subscription {
-            if !subscription.is_match(
-                &video
-                    .subscription()
-                    .name()
-                    .unwrap_or_else(|| "".to_string()),
-            ) {
+            if !subscription.is_match(&video.channel().unwrap_or_default()) {
+                return false;
+            }
+        }
+
+        if let Some(expression) = &self.expression {
+            if !expression.matches_target(video) {
                 return false;
             }
         }
@@ -128,12 +218,20 @@ impl Filter for AnyVideoFilter {
     }
 }
 
+impl Filter for AnyVideoFilter {
+    type Item = AnyVideo;
+
+    fn matches(&self, video: &<Self as Filter>::Item) -> bool {
+        self.matches_target(video)
+    }
+}
+
 impl TryFrom<Vec<String>> for AnyVideoFilter {
     // TODO: Error handling
     type Error = ();
 
     fn try_from(value: Vec<String>) -> Result<Self, Self::Error> {
-        let platform_opt = value.get(0);
+        let platform_opt = value.first();
         let title_opt = value.get(1);
         let subscription_opt = value.get(2);
 
@@ -157,30 +255,78 @@ impl TryFrom<Vec<String>> for AnyVideoFilter {
             return Err(());
         }
 
+        // The expression is optional for compatibility with older rows.
+        let expression = value
+            .get(3)
+            .and_then(map_empty_to_none)
+            .map(|e| FilterExpression::from_str(&e))
+            .transpose()
+            .map_err(|_| ())?;
+
         Ok(AnyVideoFilter::new(
             platform.map(|r: Result<Platform, _>| r.unwrap()),
             title.map(|r| r.unwrap()),
             subscription.map(|r| r.unwrap()),
-        ))
+        )
+        .with_expression(expression))
     }
 }
 
 impl From<AnyVideoFilter> for Vec<String> {
     fn from(filter: AnyVideoFilter) -> Self {
-        vec![
-            filter
-                .platform
-                .map(|p| p.into())
-                .unwrap_or_else(|| "".to_string()),
-            filter
-                .title
-                .map(|r| r.to_string())
-                .unwrap_or_else(|| "".to_string()),
+        let mut row = vec![
+            filter.platform.map(|p| p.into()).unwrap_or_default(),
+            filter.title.map(|r| r.to_string()).unwrap_or_default(),
             filter
                 .subscription
                 .map(|r| r.to_string())
-                .unwrap_or_else(|| "".to_string()),
-        ]
+                .unwrap_or_default(),
+        ];
+        if let Some(expression) = filter.expression {
+            row.push(expression.to_string());
+        }
+        row
+    }
+}
+
+/// The serialized representation of a [AnyVideoFilter].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AnyVideoFilterRepr {
+    #[serde(default)]
+    platform: Option<Platform>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    subscription: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expression: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<AnyVideoFilter> for AnyVideoFilterRepr {
+    fn from(filter: AnyVideoFilter) -> Self {
+        AnyVideoFilterRepr {
+            title: filter.title_str(),
+            subscription: filter.subscription_str(),
+            expression: filter.expression.map(|e| e.to_string()),
+            platform: filter.platform,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AnyVideoFilterRepr> for AnyVideoFilter {
+    type Error = crate::SerializationError;
+
+    fn try_from(filter: AnyVideoFilterRepr) -> Result<Self, Self::Error> {
+        let title = filter.title.map(|s| Regex::new(&s)).transpose()?;
+        let subscription = filter.subscription.map(|s| Regex::new(&s)).transpose()?;
+        let expression = filter
+            .expression
+            .map(|e| FilterExpression::from_str(&e))
+            .transpose()?;
+        Ok(AnyVideoFilter::new(filter.platform, title, subscription).with_expression(expression))
     }
 }
 
@@ -353,4 +499,41 @@ mod test {
     fn filter_conversion_back_fail() {
         assert!(AnyVideoFilter::try_from(vec!["".to_string(), "itl".to_string()]).is_err());
     }
+
+    #[test]
+    fn filter_conversion_expression() {
+        let filter = AnyVideoFilter::new(Some(Platform::Test), None, None)
+            .with_expression(Some("title:/live/i OR Foo".parse().unwrap()));
+        let row = vec![
+            "test".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "title:/live/i OR Foo".to_string(),
+        ];
+
+        assert_eq!(Vec::<String>::from(filter.clone()), row);
+        assert_eq!(Ok(filter), row.try_into());
+        assert!(AnyVideoFilter::try_from(vec![
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "(".to_string()
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn filter_match_expression() {
+        let sub = TestSubscription::new("Subscription");
+        let video = Arc::new(Mutex::new(ExpandedVideo::from(TestVideo::new(
+            "Title", sub,
+        ))))
+        .into();
+
+        let filter = AnyVideoFilter::new(Some(Platform::Test), None, None)
+            .with_expression(Some("NOT title:itl".parse().unwrap()));
+
+        assert!(!filter.matches(&video));
+        assert!(AnyVideoFilter::from_expression("ubscr".parse().unwrap()).matches(&video));
+    }
 }
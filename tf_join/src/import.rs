@@ -0,0 +1,341 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Import of [AnySubscription]s from the exports of other applications.
+//!
+//! Currently supported are:
+//!
+//! - [NewPipe](https://newpipe.net/): `subscriptions.json`, see [ImportedSubscriptions::from_newpipe].
+//! - [FreeTube](https://freetubeapp.io/): `profiles.db` or `subscriptions.db`, see
+//!   [ImportedSubscriptions::from_freetube].
+//!
+//! The imported [AnySubscription]s can be compared to a [AnySubscriptionList] without modifying it
+//! using [AnySubscriptionList::diff] and applied afterwards using [AnySubscriptionList::apply].
+
+use serde::Deserialize;
+
+use crate::{AnySubscription, AnySubscriptionList};
+
+/// The service id of YouTube used by NewPipe.
+const NEWPIPE_SERVICE_YOUTUBE: u32 = 0;
+/// The service id of PeerTube used by NewPipe.
+const NEWPIPE_SERVICE_PEERTUBE: u32 = 3;
+
+/// A error parsing a export of another application.
+#[derive(Debug)]
+pub struct ImportError(pub String);
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error importing subscriptions: {}", self.0)
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(e: serde_json::Error) -> Self {
+        ImportError(e.to_string())
+    }
+}
+
+/// The `subscriptions.json` exported by NewPipe.
+#[derive(Deserialize)]
+struct NewPipeExport {
+    subscriptions: Vec<NewPipeSubscription>,
+}
+
+/// A subscription exported by NewPipe.
+#[derive(Deserialize)]
+struct NewPipeSubscription {
+    service_id: u32,
+    url: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// A profile exported by FreeTube, one per line.
+#[derive(Deserialize)]
+struct FreeTubeProfile {
+    #[serde(default)]
+    subscriptions: Vec<FreeTubeSubscription>,
+}
+
+/// A subscription exported by FreeTube.
+#[derive(Deserialize)]
+struct FreeTubeSubscription {
+    id: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// The [AnySubscription]s imported from the export of another application.
+#[derive(Debug, Clone, Default)]
+pub struct ImportedSubscriptions {
+    /// The recognized [AnySubscription]s.
+    subscriptions: Vec<AnySubscription>,
+    /// The urls or ids of entries that could not be recognized.
+    unrecognized: Vec<String>,
+}
+
+impl ImportedSubscriptions {
+    /// Import the `subscriptions.json` exported by NewPipe.
+    ///
+    /// YouTube-channels (`https://www.youtube.com/channel/<id>`) and PeerTube-channels
+    /// (`https://<instance>/video-channels/<name>`) are recognized.
+    pub fn from_newpipe(json: &str) -> Result<Self, ImportError> {
+        let export: NewPipeExport = serde_json::from_str(json)?;
+        let mut result = ImportedSubscriptions::default();
+        for subscription in export.subscriptions {
+            let name = subscription.name.as_deref().unwrap_or_default();
+            match newpipe_subscription(subscription.service_id, &subscription.url, name) {
+                Some(s) => result.push(s),
+                None => result.unrecognized.push(subscription.url),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Import the `profiles.db` or `subscriptions.db` exported by FreeTube.
+    ///
+    /// The subscriptions of all profiles are imported, FreeTube only supports YouTube-channels.
+    pub fn from_freetube(db: &str) -> Result<Self, ImportError> {
+        let mut result = ImportedSubscriptions::default();
+        for line in db.lines().filter(|l| !l.trim().is_empty()) {
+            let profile: FreeTubeProfile = serde_json::from_str(line)?;
+            for subscription in profile.subscriptions {
+                match freetube_subscription(&subscription.id, subscription.name.as_deref()) {
+                    Some(s) => result.push(s),
+                    None => result.unrecognized.push(subscription.id),
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Add a [AnySubscription] if it was not already imported.
+    fn push(&mut self, subscription: AnySubscription) {
+        if !self.subscriptions.contains(&subscription) {
+            self.subscriptions.push(subscription);
+        }
+    }
+
+    /// Get the recognized [AnySubscription]s.
+    pub fn subscriptions(&self) -> &[AnySubscription] {
+        &self.subscriptions
+    }
+
+    /// Get the urls or ids of entries that could not be recognized.
+    pub fn unrecognized(&self) -> &[String] {
+        &self.unrecognized
+    }
+}
+
+/// Map a subscription exported by NewPipe to a [AnySubscription].
+#[allow(unused_variables)]
+fn newpipe_subscription(service_id: u32, url: &str, name: &str) -> Option<AnySubscription> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?.to_string();
+    let segments = url.path_segments()?.collect::<Vec<_>>();
+    match (service_id, segments.as_slice()) {
+        #[cfg(feature = "youtube")]
+        (NEWPIPE_SERVICE_YOUTUBE, ["channel", id]) if host.ends_with("youtube.com") => {
+            Some(tf_yt::YTSubscription::new_with_name(id, name).into())
+        }
+        #[cfg(feature = "peertube")]
+        (NEWPIPE_SERVICE_PEERTUBE, ["video-channels" | "c", channel]) if !channel.is_empty() => {
+            let id = if channel.contains('@') {
+                channel.to_string()
+            } else {
+                format!("{}@{}", channel, host)
+            };
+            let base_url = match url.port() {
+                Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+                None => format!("{}://{}", url.scheme(), host),
+            };
+            Some(tf_pt::PTSubscription::new_with_name(base_url, id, name).into())
+        }
+        // -- Add new case here.
+        _ => None,
+    }
+}
+
+/// Map a subscription exported by FreeTube to a [AnySubscription].
+#[allow(unused_variables)]
+fn freetube_subscription(id: &str, name: Option<&str>) -> Option<AnySubscription> {
+    #[cfg(feature = "youtube")]
+    if id.starts_with("UC") {
+        return Some(
+            match name {
+                Some(name) => tf_yt::YTSubscription::new_with_name(id, name),
+                None => tf_yt::YTSubscription::new(id),
+            }
+            .into(),
+        );
+    }
+    None
+}
+
+/// The difference between imported [AnySubscription]s and a [AnySubscriptionList].
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionDiff {
+    /// The [AnySubscription]s not yet in the [AnySubscriptionList].
+    added: Vec<AnySubscription>,
+    /// The [AnySubscription]s already in the [AnySubscriptionList].
+    existing: Vec<AnySubscription>,
+}
+
+impl SubscriptionDiff {
+    /// Get the [AnySubscription]s that would be added to the [AnySubscriptionList].
+    pub fn added(&self) -> &[AnySubscription] {
+        &self.added
+    }
+
+    /// Get the [AnySubscription]s that are already in the [AnySubscriptionList].
+    pub fn existing(&self) -> &[AnySubscription] {
+        &self.existing
+    }
+}
+
+impl AnySubscriptionList {
+    /// Compare the given [ImportedSubscriptions] with this [AnySubscriptionList] without modifying it.
+    pub fn diff(&self, imported: &ImportedSubscriptions) -> SubscriptionDiff {
+        let current = self.iter().collect::<Vec<_>>();
+        let (existing, added) = imported
+            .subscriptions()
+            .iter()
+            .cloned()
+            .partition(|s| current.contains(s));
+        SubscriptionDiff { added, existing }
+    }
+
+    /// Add all [AnySubscription]s of the [SubscriptionDiff] that are not yet in the [AnySubscriptionList].
+    pub fn apply(&self, diff: &SubscriptionDiff) {
+        let current = self.iter().collect::<Vec<_>>();
+        diff.added()
+            .iter()
+            .filter(|s| !current.contains(s))
+            .for_each(|s| self.add(s.clone()));
+    }
+}
+
+#[cfg(all(test, feature = "youtube", feature = "peertube"))]
+mod test {
+    use super::*;
+    use tf_core::Subscription;
+
+    const NEWPIPE: &str = r#"{
+        "app_version": "0.26.1",
+        "app_version_int": 995,
+        "subscriptions": [
+            {"service_id": 0, "url": "https://www.youtube.com/channel/UCld68syR8Wi-GY_n4CaoJGA", "name": "Brodie Robertson"},
+            {"service_id": 3, "url": "https://framatube.org/video-channels/framasoft_channel", "name": "Framasoft"},
+            {"service_id": 3, "url": "https://peertube.example/video-channels/other@instance.example", "name": "Other"},
+            {"service_id": 1, "url": "https://soundcloud.com/someone", "name": "Someone"}
+        ]
+    }"#;
+
+    const FREETUBE: &str = r##"{"name":"All Channels","bgColor":"#000000","textColor":"#FFFFFF","subscriptions":[{"id":"UCld68syR8Wi-GY_n4CaoJGA","name":"Brodie Robertson","thumbnail":""},{"id":"UC7YOGHUfC1Tb6E4pudI9STA","name":"Mental Outlaw","thumbnail":""}],"_id":"allChannels"}
+{"name":"Linux","bgColor":"#000000","textColor":"#FFFFFF","subscriptions":[{"id":"UCld68syR8Wi-GY_n4CaoJGA","name":"Brodie Robertson","thumbnail":""}],"_id":"linux"}
+"##;
+
+    #[test]
+    fn import_newpipe() {
+        let imported = ImportedSubscriptions::from_newpipe(NEWPIPE).unwrap();
+
+        assert_eq!(
+            imported.subscriptions(),
+            &[
+                tf_yt::YTSubscription::new_with_name(
+                    "UCld68syR8Wi-GY_n4CaoJGA",
+                    "Brodie Robertson"
+                )
+                .into(),
+                tf_pt::PTSubscription::new_with_name(
+                    "https://framatube.org",
+                    "framasoft_channel@framatube.org",
+                    "Framasoft"
+                )
+                .into(),
+                tf_pt::PTSubscription::new_with_name(
+                    "https://peertube.example",
+                    "other@instance.example",
+                    "Other"
+                )
+                .into(),
+            ]
+        );
+        assert_eq!(
+            imported.subscriptions()[1].name(),
+            Some("Framasoft".to_string())
+        );
+        assert_eq!(
+            imported.unrecognized(),
+            &["https://soundcloud.com/someone".to_string()]
+        );
+    }
+
+    #[test]
+    fn import_newpipe_invalid() {
+        assert!(ImportedSubscriptions::from_newpipe("{}").is_err());
+    }
+
+    #[test]
+    fn import_freetube() {
+        let imported = ImportedSubscriptions::from_freetube(FREETUBE).unwrap();
+
+        assert_eq!(
+            imported.subscriptions(),
+            &[
+                tf_yt::YTSubscription::new("UCld68syR8Wi-GY_n4CaoJGA").into(),
+                tf_yt::YTSubscription::new("UC7YOGHUfC1Tb6E4pudI9STA").into(),
+            ]
+        );
+        assert_eq!(
+            imported.subscriptions()[1].name(),
+            Some("Mental Outlaw".to_string())
+        );
+        assert!(imported.unrecognized().is_empty());
+    }
+
+    #[test]
+    fn import_diff_apply() {
+        let list = AnySubscriptionList::default();
+        list.add(tf_yt::YTSubscription::new("UCld68syR8Wi-GY_n4CaoJGA").into());
+
+        let imported = ImportedSubscriptions::from_freetube(FREETUBE).unwrap();
+        let diff = list.diff(&imported);
+
+        assert_eq!(
+            diff.added(),
+            &[tf_yt::YTSubscription::new("UC7YOGHUfC1Tb6E4pudI9STA").into()]
+        );
+        assert_eq!(
+            diff.existing(),
+            &[tf_yt::YTSubscription::new("UCld68syR8Wi-GY_n4CaoJGA").into()]
+        );
+        assert_eq!(list.iter().count(), 1);
+
+        list.apply(&diff);
+        assert_eq!(list.iter().count(), 2);
+
+        list.apply(&diff);
+        assert_eq!(list.iter().count(), 2);
+    }
+}
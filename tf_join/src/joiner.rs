@@ -18,29 +18,103 @@
  */
 
 use std::{
+    collections::{HashMap, HashSet},
     future::Future,
+    path::Path,
     pin::Pin,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use tf_core::{ErrorStore, Generator, Pipeline, Video};
+use tf_core::{
+    ErrorStore, FetchPolicy, FileVideoStoreBackend, Generator, NetworkSettings, Page, PageCursor,
+    ParseError, Pipeline, StreamGenerator, ThumbnailCache, ThumbnailSize, Transport, Video,
+    VideoId, WindowCursor,
+};
 use tf_filter::{Filter, FilterGroup};
+use tf_observer::{Observable, Observer, ObserverList};
+use tf_playlist::PlaylistManager;
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use tf_yt::YTPipeline;
 
-use crate::{AnySubscriptionList, AnyVideo, AnyVideoFilter};
+use crate::{
+    filter::PlatformVideo, AnySubscription, AnySubscriptionList, AnyVideo, AnyVideoFilter,
+    FeedPage, FeedQuery, Platform,
+};
+
+/// How far older [AnyVideo]s of a [AnySubscription] were loaded, see [Joiner::load_older].
+#[derive(Clone)]
+enum PageState {
+    /// The next page to load.
+    Next(PageCursor),
+    /// All pages were loaded.
+    Exhausted,
+}
+
+/// The event sent by [Joiner] when the number of unseen [AnyVideo]s changed.
+#[derive(Clone, Debug)]
+pub enum UnreadEvent {
+    /// The unread counts changed, containing the new total number of unseen [AnyVideo]s.
+    /// See [Joiner::unread_per_subscription] for the detailed counts.
+    Changed(usize),
+}
+
+/// The event sent by [Joiner] when new [AnyVideo]s arrived.
+#[derive(Clone)]
+pub enum FeedEvent {
+    /// New [AnyVideo]s of the [AnySubscription] arrived, i.e. [AnyVideo]s seen for the first
+    /// time by the pipelines (see [Joiner::first_seen]).
+    ///
+    /// Contains the new [AnyVideo]s passing the [FilterGroup] followed by the new [AnyVideo]s
+    /// filtered out, both sorted newest first.
+    NewVideos(AnySubscription, Vec<AnyVideo>, Vec<AnyVideo>),
+}
 
 /// Join multiple platforms together into one [Generator].
 ///
 /// This will handle the generation and filtering of videos.
+///
+/// The [Joiner] also keeps track of which [AnyVideo]s were already seen, see [Joiner::set_seen].
+/// It implements [Observable] and emits [UnreadEvent] to the [Observer]s when the unread
+/// counts change and [FeedEvent] when new [AnyVideo]s arrived.
 #[derive(Clone)]
 pub struct Joiner {
+    observers: ObserverList<UnreadEvent>,
+    feed_observers: ObserverList<FeedEvent>,
+
     /// The [AnySubscriptionList] used to generate the [AnyVideo]s.
     subscription_list: AnySubscriptionList,
 
     /// The [FilterGroup] used to filter out [AnyVideo]s.
     filters: Arc<Mutex<FilterGroup<AnyVideoFilter>>>,
+
+    /// The [FetchPolicy] used by the [Pipeline]s.
+    fetch_policy: Arc<Mutex<FetchPolicy>>,
+
+    /// The fraction of the duration after which a [AnyVideo] is watched, see [Joiner::report_progress].
+    watched_threshold: Arc<Mutex<f64>>,
+
+    /// The [ThumbnailCache] used by [Joiner::thumbnail], see [Joiner::set_thumbnail_cache].
+    thumbnail_cache: Arc<Mutex<Option<ThumbnailCache>>>,
+
+    /// The [NetworkSettings] of every [Platform] they were set for.
+    network_settings: Arc<Mutex<HashMap<Platform, NetworkSettings>>>,
+
+    /// When the last refresh started, see [Joiner::is_new].
+    last_refresh: Arc<Mutex<Option<chrono::NaiveDateTime>>>,
+
+    /// The [VideoId]s of the unseen [AnyVideo]s per [AnySubscription], kept up to date
+    /// whenever [AnyVideo]s arrive or are marked as seen.
+    unread: Arc<Mutex<HashMap<AnySubscription, HashSet<VideoId>>>>,
+
+    /// The [PageState] of every [AnySubscription] older [AnyVideo]s were loaded for.
+    pages: Arc<Mutex<HashMap<AnySubscription, PageState>>>,
+
+    /// The [PlaylistManager] updated [AnyVideo]s are forwarded to, see [Joiner::set_playlist_manager].
+    playlist_manager: Arc<Mutex<Option<PlaylistManager<String, AnyVideo>>>>,
     #[cfg(feature = "youtube")]
     yt_pipeline: YTPipeline,
     #[cfg(feature = "peertube")]
@@ -55,15 +129,17 @@ pub struct Joiner {
 impl Joiner {
     /// Create a new [Joiner] with no [AnySubscription][crate::AnySubscription]s and no [Filter][tf_filter::Filter]s.
     pub fn new() -> Self {
+        let fetch_policy = Arc::new(Mutex::new(FetchPolicy::new()));
+
         #[cfg(feature = "youtube")]
-        let yt_pipeline = YTPipeline::new();
+        let yt_pipeline = YTPipeline::new_with_fetch_policy(fetch_policy.clone());
         #[cfg(feature = "peertube")]
-        let pt_pipeline = Pipeline::new();
+        let pt_pipeline = Pipeline::new_with_fetch_policy(fetch_policy.clone());
         #[cfg(feature = "lbry")]
-        let lbry_pipeline = Pipeline::new();
+        let lbry_pipeline = Pipeline::new_with_fetch_policy(fetch_policy.clone());
         // -- Add value here.
         #[cfg(test)]
-        let test_pipeline = Pipeline::new();
+        let test_pipeline = Pipeline::new_with_fetch_policy(fetch_policy.clone());
 
         let mut subscriptions = AnySubscriptionList::default();
         #[cfg(feature = "youtube")]
@@ -77,6 +153,8 @@ impl Joiner {
         subscriptions.test_subscriptions(test_pipeline.subscription_list());
 
         Joiner {
+            observers: ObserverList::default(),
+            feed_observers: ObserverList::default(),
             subscription_list: subscriptions,
             #[cfg(feature = "youtube")]
             yt_pipeline,
@@ -88,6 +166,14 @@ impl Joiner {
             #[cfg(test)]
             test_pipeline,
             filters: Arc::new(Mutex::new(FilterGroup::new())),
+            fetch_policy,
+            watched_threshold: Arc::new(Mutex::new(tf_core::DEFAULT_WATCHED_THRESHOLD)),
+            thumbnail_cache: Arc::new(Mutex::new(None)),
+            network_settings: Arc::new(Mutex::new(HashMap::new())),
+            last_refresh: Arc::new(Mutex::new(None)),
+            unread: Arc::new(Mutex::new(HashMap::new())),
+            pages: Arc::new(Mutex::new(HashMap::new())),
+            playlist_manager: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -104,6 +190,1128 @@ impl Joiner {
         self.filters.clone()
     }
 
+    /// Keep the [AnyVideo]s in the playlists of the [PlaylistManager] up to date.
+    ///
+    /// Whenever a stored [AnyVideo] is updated in place, e.g. as its title was edited, and its
+    /// observers are notified with [VideoEvent::Update][tf_core::VideoEvent::Update], it is also
+    /// updated in all playlists containing it, see [PlaylistManager::update].
+    pub fn set_playlist_manager(&self, playlist_manager: PlaylistManager<String, AnyVideo>) {
+        *self.playlist_manager.lock().unwrap() = Some(playlist_manager);
+    }
+
+    /// Forward the [AnyVideo]s updated in place in the pipelines to the [PlaylistManager],
+    /// see [Joiner::set_playlist_manager].
+    fn forward_updates(&self) {
+        let mut updated: Vec<AnyVideo> = vec![];
+        #[cfg(feature = "youtube")]
+        updated.extend(
+            self.yt_pipeline
+                .take_updated_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+        #[cfg(feature = "peertube")]
+        updated.extend(
+            self.pt_pipeline
+                .take_updated_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+        #[cfg(feature = "lbry")]
+        updated.extend(
+            self.lbry_pipeline
+                .take_updated_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+        // -- Add updated.extend here.
+        #[cfg(test)]
+        updated.extend(
+            self.test_pipeline
+                .take_updated_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+
+        let playlist_manager = self.playlist_manager.lock().unwrap().clone();
+        if let Some(mut playlist_manager) = playlist_manager {
+            for video in &updated {
+                playlist_manager.update(video);
+            }
+        }
+    }
+
+    /// Get the [FetchPolicy] used to fetch the [AnySubscription][crate::AnySubscription]s.
+    ///
+    /// Modifying this [FetchPolicy] will alter how all platforms using a [Pipeline] fetch their
+    /// [AnySubscription][crate::AnySubscription]s.
+    pub fn fetch_policy(&self) -> Arc<Mutex<FetchPolicy>> {
+        self.fetch_policy.clone()
+    }
+
+    /// Get the [Transport] used to request the web for the given [Platform], e.g. to fetch the
+    /// [AnySubscription][crate::AnySubscription]s.
+    ///
+    /// This can also be used to fetch thumbnails using
+    /// [Video::thumbnail_with_client][tf_core::Video::thumbnail_with_client].
+    pub fn transport(&self, platform: &Platform) -> Arc<dyn Transport> {
+        match platform {
+            #[cfg(feature = "youtube")]
+            Platform::Youtube => self.yt_pipeline.transport(),
+            #[cfg(feature = "peertube")]
+            Platform::Peertube => self.pt_pipeline.transport(),
+            #[cfg(feature = "lbry")]
+            Platform::Lbry => self.lbry_pipeline.transport(),
+            // -- Add new case here.
+            #[cfg(test)]
+            Platform::Test => self.test_pipeline.transport(),
+        }
+    }
+
+    /// Set the [Transport] used by the given [Platform] to request the web.
+    ///
+    /// This replaces any [Transport] built from [NetworkSettings] of the [Platform].
+    pub fn set_platform_transport(&self, platform: &Platform, transport: Arc<dyn Transport>) {
+        self.network_settings.lock().unwrap().remove(platform);
+        match platform {
+            #[cfg(feature = "youtube")]
+            Platform::Youtube => self.yt_pipeline.set_transport(transport),
+            #[cfg(feature = "peertube")]
+            Platform::Peertube => self.pt_pipeline.set_transport(transport),
+            #[cfg(feature = "lbry")]
+            Platform::Lbry => self.lbry_pipeline.set_transport(transport),
+            // -- Add new case here.
+            #[cfg(test)]
+            Platform::Test => self.test_pipeline.set_transport(transport),
+        }
+    }
+
+    /// Set the [Transport] used by all platforms to request the web.
+    ///
+    /// This replaces all [Transport]s built from [NetworkSettings].
+    pub fn set_transport(&self, transport: Arc<dyn Transport>) {
+        #[cfg(feature = "youtube")]
+        self.yt_pipeline.set_transport(transport.clone());
+        #[cfg(feature = "peertube")]
+        self.pt_pipeline.set_transport(transport.clone());
+        #[cfg(feature = "lbry")]
+        self.lbry_pipeline.set_transport(transport.clone());
+        // -- Add set_transport here.
+        #[cfg(test)]
+        self.test_pipeline.set_transport(transport.clone());
+        self.network_settings.lock().unwrap().clear();
+    }
+
+    /// Get the [NetworkSettings] of the given [Platform].
+    ///
+    /// These are the default [NetworkSettings] if none were set for the [Platform].
+    pub fn network_settings(&self, platform: &Platform) -> NetworkSettings {
+        self.network_settings
+            .lock()
+            .unwrap()
+            .get(platform)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Set the [NetworkSettings] of the given [Platform], e.g. to route it through a [Proxy][tf_core::Proxy].
+    ///
+    /// This builds a new [Transport] for the [Platform], see [NetworkSettings::build_transport].
+    /// If building fails, the previous [NetworkSettings] stay in place.
+    pub fn set_network_settings(
+        &self,
+        platform: &Platform,
+        settings: NetworkSettings,
+    ) -> Result<(), ParseError> {
+        let transport = settings.build_transport()?;
+        self.set_platform_transport(platform, transport);
+        self.network_settings
+            .lock()
+            .unwrap()
+            .insert(platform.clone(), settings);
+        Ok(())
+    }
+
+    /// Get the fraction of the duration of a [AnyVideo] that has to be played to mark it as watched.
+    pub fn watched_threshold(&self) -> f64 {
+        *self.watched_threshold.lock().unwrap()
+    }
+
+    /// Set the fraction of the duration of a [AnyVideo] that has to be played to mark it as watched,
+    /// e.g. `0.9` for 90%. The value will be clamped into `0.0..=1.0`.
+    pub fn set_watched_threshold(&self, threshold: f64) {
+        *self.watched_threshold.lock().unwrap() = threshold.clamp(0.0, 1.0);
+    }
+
+    /// Report that the [AnyVideo] was played until `position`, watching it for `watched_for`
+    /// since the last report.
+    ///
+    /// The [AnyVideo] will be marked as watched once the [Joiner::watched_threshold] is reached.
+    /// The [WatchProgress][tf_core::WatchProgress] is persisted in the video cache
+    /// (see [Joiner::set_video_cache]) such that it is still known after a restart.
+    pub fn report_progress(&self, video: &AnyVideo, position: Duration, watched_for: Duration) {
+        video.report_progress(position, watched_for, self.watched_threshold());
+        self.save_video(video);
+    }
+
+    /// Mark the [AnyVideo] as watched or unwatched and persist this in the video cache.
+    pub fn set_watched(&self, video: &AnyVideo, watched: bool) {
+        video.set_watched(watched);
+        self.save_video(video);
+    }
+
+    /// Get when the [AnyVideo] was first seen by the pipelines, e.g. when it first appeared in
+    /// the feed.
+    ///
+    /// This is [None] if the [AnyVideo] is not in the video storage of its pipeline.
+    pub fn first_seen(&self, video: &AnyVideo) -> Option<chrono::NaiveDateTime> {
+        match video {
+            #[cfg(feature = "youtube")]
+            AnyVideo::Youtube(v) => {
+                let v = v.lock().unwrap().clone();
+                self.yt_pipeline.first_seen(&v)
+            }
+            #[cfg(feature = "peertube")]
+            AnyVideo::Peertube(v) => {
+                let v = v.lock().unwrap().clone();
+                self.pt_pipeline.first_seen(&v)
+            }
+            #[cfg(feature = "lbry")]
+            AnyVideo::Lbry(v) => {
+                let v = v.lock().unwrap().clone();
+                self.lbry_pipeline.first_seen(&v)
+            }
+            // -- Add new case here.
+            #[cfg(test)]
+            AnyVideo::Test(v) => {
+                let v = v.lock().unwrap().clone();
+                self.test_pipeline.first_seen(&v)
+            }
+        }
+    }
+
+    /// Whether the [AnyVideo] is new since the last refresh, i.e. it was first seen during
+    /// the last [Joiner::generate], [Joiner::generate_stream][StreamGenerator::generate_stream]
+    /// or [Joiner::refresh].
+    pub fn is_new(&self, video: &AnyVideo) -> bool {
+        let last_refresh = *self.last_refresh.lock().unwrap();
+        match (last_refresh, self.first_seen(video)) {
+            (Some(last_refresh), Some(first_seen)) => first_seen >= last_refresh,
+            _ => false,
+        }
+    }
+
+    /// Whether the next refresh is the initial fill of the feed, i.e. there was no refresh yet
+    /// and no [AnyVideo]s are known, e.g. from the video cache (see [Joiner::set_video_cache]).
+    ///
+    /// The [Observer]s are not notified about the [AnyVideo]s of the initial fill as all of them
+    /// would be new.
+    fn is_initial_fill(&self) -> bool {
+        self.last_refresh.lock().unwrap().is_none() && !self.has_stored_videos()
+    }
+
+    /// Whether any [AnyVideo]s are known to the pipelines, see [Joiner::stored_videos].
+    fn has_stored_videos(&self) -> bool {
+        let mut result = false;
+        #[cfg(feature = "youtube")]
+        {
+            result |= self.yt_pipeline.has_stored_videos();
+        }
+        #[cfg(feature = "peertube")]
+        {
+            result |= self.pt_pipeline.has_stored_videos();
+        }
+        #[cfg(feature = "lbry")]
+        {
+            result |= self.lbry_pipeline.has_stored_videos();
+        }
+        // -- Add result here.
+        #[cfg(test)]
+        {
+            result |= self.test_pipeline.has_stored_videos();
+        }
+        result
+    }
+
+    /// Mark the [AnyVideo] as seen or unseen, persist this in the video cache and update
+    /// the unread counts.
+    pub fn set_seen(&self, video: &AnyVideo, seen: bool) {
+        video.set_seen(seen);
+        self.save_video(video);
+        self.update_unread(std::slice::from_ref(video));
+    }
+
+    /// Mark all [AnyVideo]s as seen, see [Joiner::set_seen].
+    pub fn mark_all_seen(&self) {
+        self.mark_seen_where(|_| true)
+    }
+
+    /// Mark all [AnyVideo]s of the [AnySubscription] as seen, see [Joiner::set_seen].
+    pub fn mark_subscription_seen(&self, subscription: &AnySubscription) {
+        self.mark_seen_where(|v| &v.subscription() == subscription)
+    }
+
+    /// Mark all unseen [AnyVideo]s matching the predicate as seen, notifying the [Observer]s once.
+    fn mark_seen_where<F: Fn(&AnyVideo) -> bool>(&self, predicate: F) {
+        let videos = self
+            .stored_videos()
+            .into_iter()
+            .filter(|v| !v.seen() && predicate(v))
+            .collect::<Vec<_>>();
+        for video in &videos {
+            video.set_seen(true);
+            self.save_video(video);
+        }
+        self.update_unread(&videos);
+    }
+
+    /// Get the total number of unseen [AnyVideo]s.
+    pub fn unread_count(&self) -> usize {
+        self.unread_per_subscription().values().sum()
+    }
+
+    /// Count the unseen [AnyVideo]s per [AnySubscription].
+    ///
+    /// Only [AnyVideo]s whose [AnySubscription] is part of the [AnySubscriptionList] and not
+    /// [muted][crate::SubscriptionSettings::muted] are counted, like hidden [AnyVideo]s in general
+    /// (see [Joiner::is_hidden]).
+    /// [AnySubscription]s without unseen [AnyVideo]s are not part of the result.
+    pub fn unread_per_subscription(&self) -> HashMap<AnySubscription, usize> {
+        let subscriptions: HashSet<AnySubscription> = self
+            .subscription_list
+            .iter()
+            .filter(|s| !self.subscription_list.settings(s).muted())
+            .collect();
+        self.unread
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(s, ids)| !ids.is_empty() && subscriptions.contains(s))
+            .map(|(s, ids)| (s.clone(), ids.len()))
+            .collect()
+    }
+
+    /// Count the unseen [AnyVideo]s per [Platform], see [Joiner::unread_per_subscription].
+    pub fn unread_per_platform(&self) -> HashMap<Platform, usize> {
+        let mut result = HashMap::new();
+        for (subscription, count) in self.unread_per_subscription() {
+            *result.entry(subscription.platform()).or_insert(0) += count;
+        }
+        result
+    }
+
+    /// Update the unread counts with the seen-status of the given [AnyVideo]s, which arrived
+    /// or were marked as seen, and notify the [Observer]s with [UnreadEvent::Changed] if they changed.
+    ///
+    /// Hidden [AnyVideo]s (see [Joiner::is_hidden]) are not counted, like in [Joiner::stored_videos].
+    pub(crate) fn update_unread(&self, videos: &[AnyVideo]) {
+        let hidden = videos.iter().map(|v| self.is_hidden(v)).collect::<Vec<_>>();
+        let changed = {
+            let mut unread = self.unread.lock().unwrap();
+            let mut changed = false;
+            for (video, hidden) in videos.iter().zip(hidden) {
+                let ids = unread.entry(video.subscription()).or_default();
+                changed |= if hidden || video.seen() {
+                    ids.remove(&video.id())
+                } else {
+                    ids.insert(video.id())
+                };
+            }
+            changed
+        };
+        if changed {
+            self.observers
+                .notify(UnreadEvent::Changed(self.unread_count()))
+        }
+    }
+
+    /// Count the unseen [AnyVideo]s of all stored [AnyVideo]s again, e.g. after loading
+    /// or forgetting [AnyVideo]s, and notify the [Observer]s if the counts changed.
+    fn recount_unread(&self) {
+        let previous = self.unread_per_subscription();
+        self.unread.lock().unwrap().clear();
+        let videos = self.stored_videos();
+        {
+            let mut unread = self.unread.lock().unwrap();
+            for video in videos.iter().filter(|v| !v.seen()) {
+                unread
+                    .entry(video.subscription())
+                    .or_default()
+                    .insert(video.id());
+            }
+        }
+        if self.unread_per_subscription() != previous {
+            self.observers
+                .notify(UnreadEvent::Changed(self.unread_count()))
+        }
+    }
+
+    /// Whether the [AnyVideo] is excluded from the feed, i.e. it is filtered out or its
+    /// [AnySubscription] is [muted][crate::SubscriptionSettings::muted].
+    pub fn is_hidden(&self, video: &AnyVideo) -> bool {
+        self.subscription_list
+            .settings(&video.subscription())
+            .muted()
+            || self.filters.lock().unwrap().matches(video)
+    }
+
+    /// Sort the [AnyVideo]s of the feed by the [priority][crate::SubscriptionSettings::priority]
+    /// of their [AnySubscription], the highest first, and then newest first.
+    fn sort_feed(&self, videos: &mut [AnyVideo]) {
+        videos.sort_by_cached_key(|v| {
+            let priority = self
+                .subscription_list
+                .settings(&v.subscription())
+                .priority();
+            (std::cmp::Reverse(priority), std::cmp::Reverse(v.uploaded()))
+        });
+    }
+
+    /// Notify the [Observer]s with one [FeedEvent::NewVideos] per [AnySubscription] of the given
+    /// new [AnyVideo]s.
+    ///
+    /// [AnySubscription]s which are muted or have notifications disabled are skipped,
+    /// see [SubscriptionSettings][crate::SubscriptionSettings].
+    pub(crate) fn notify_new_videos(&self, videos: &[AnyVideo]) {
+        let mut events: Vec<(AnySubscription, Vec<AnyVideo>, Vec<AnyVideo>)> = vec![];
+        {
+            let filters = self.filters.lock().unwrap();
+            for video in videos {
+                let subscription = video.subscription();
+                let settings = self.subscription_list.settings(&subscription);
+                if settings.muted() || !settings.notify() {
+                    continue;
+                }
+                let index = match events.iter().position(|(s, _, _)| s == &subscription) {
+                    Some(index) => index,
+                    None => {
+                        events.push((subscription, vec![], vec![]));
+                        events.len() - 1
+                    }
+                };
+                if filters.matches(video) {
+                    events[index].2.push(video.clone());
+                } else {
+                    events[index].1.push(video.clone());
+                }
+            }
+        }
+
+        for (subscription, mut passing, mut filtered) in events {
+            passing.sort_by_cached_key(|v| std::cmp::Reverse(v.uploaded()));
+            filtered.sort_by_cached_key(|v| std::cmp::Reverse(v.uploaded()));
+            self.feed_observers
+                .notify(FeedEvent::NewVideos(subscription, passing, filtered));
+        }
+    }
+
+    /// Save the current state of the [AnyVideo] into the video storage of its pipeline.
+    ///
+    /// The [AnyVideo] is cloned first, as the video storage locks the [AnyVideo]s it contains.
+    fn save_video(&self, video: &AnyVideo) {
+        match video {
+            #[cfg(feature = "youtube")]
+            AnyVideo::Youtube(v) => {
+                let v = v.lock().unwrap().clone();
+                self.yt_pipeline.save_video(&v)
+            }
+            #[cfg(feature = "peertube")]
+            AnyVideo::Peertube(v) => {
+                let v = v.lock().unwrap().clone();
+                self.pt_pipeline.save_video(&v)
+            }
+            #[cfg(feature = "lbry")]
+            AnyVideo::Lbry(v) => {
+                let v = v.lock().unwrap().clone();
+                self.lbry_pipeline.save_video(&v)
+            }
+            // -- Add new case here.
+            #[cfg(test)]
+            AnyVideo::Test(v) => {
+                let v = v.lock().unwrap().clone();
+                self.test_pipeline.save_video(&v)
+            }
+        }
+    }
+
+    /// Count the [Error][tf_core::Error]s in the given [ErrorStore] per [AnySubscription].
+    ///
+    /// [Error][tf_core::Error]s without a known [AnySubscription] are not counted.
+    pub fn errors_per_subscription(&self, errors: &ErrorStore) -> HashMap<AnySubscription, usize> {
+        let mut result = HashMap::new();
+        for (_, context) in errors.iter_with_context() {
+            if let Some(subscription) = AnySubscription::from_error_context(&context) {
+                *result.entry(subscription).or_insert(0) += 1;
+            }
+        }
+        result
+    }
+
+    /// Get all [AnySubscription]s whose last `n` refreshes failed according to the given [ErrorStore].
+    ///
+    /// This can be used to flag dead channels.
+    pub fn failing_subscriptions(&self, errors: &ErrorStore, n: usize) -> Vec<AnySubscription> {
+        #[allow(unused_mut)]
+        let mut result: Vec<AnySubscription> = vec![];
+        #[cfg(feature = "youtube")]
+        result.extend(
+            errors
+                .failing_subscriptions::<tf_yt::YTSubscription>(n)
+                .into_iter()
+                .map(AnySubscription::from),
+        );
+        #[cfg(feature = "peertube")]
+        result.extend(
+            errors
+                .failing_subscriptions::<tf_pt::PTSubscription>(n)
+                .into_iter()
+                .map(AnySubscription::from),
+        );
+        #[cfg(feature = "lbry")]
+        result.extend(
+            errors
+                .failing_subscriptions::<tf_lbry::LbrySubscription>(n)
+                .into_iter()
+                .map(AnySubscription::from),
+        );
+        // -- Add value here.
+        #[cfg(test)]
+        result.extend(
+            errors
+                .failing_subscriptions::<tf_test::TestSubscription>(n)
+                .into_iter()
+                .map(AnySubscription::from),
+        );
+        result
+    }
+
+    /// Persist the [AnyVideo]s of all platforms in the given directory.
+    ///
+    /// Every [Platform] will use its own file in the directory, e.g. `youtube.csv`.
+    /// Previously persisted [AnyVideo]s will be loaded (see [Joiner::stored_videos]), pruning the
+    /// ones first seen longer than `max_age` ago if given (see [Joiner::set_video_max_age]).
+    pub fn set_video_cache<P: AsRef<Path>>(&self, directory: P, max_age: Option<chrono::Duration>) {
+        let path = |platform: Platform| {
+            directory
+                .as_ref()
+                .join(format!("{}.csv", String::from(platform)))
+        };
+        #[cfg(feature = "youtube")]
+        self.yt_pipeline.set_video_store_backend(
+            Box::new(FileVideoStoreBackend::new(path(Platform::Youtube))),
+            max_age,
+        );
+        #[cfg(feature = "peertube")]
+        self.pt_pipeline.set_video_store_backend(
+            Box::new(FileVideoStoreBackend::new(path(Platform::Peertube))),
+            max_age,
+        );
+        #[cfg(feature = "lbry")]
+        self.lbry_pipeline.set_video_store_backend(
+            Box::new(FileVideoStoreBackend::new(path(Platform::Lbry))),
+            max_age,
+        );
+        // -- Add set_video_store_backend here.
+        #[cfg(test)]
+        self.test_pipeline.set_video_store_backend(
+            Box::new(FileVideoStoreBackend::new(path(Platform::Test))),
+            max_age,
+        );
+        self.recount_unread();
+    }
+
+    /// Forget the [AnyVideo]s of all platforms first seen longer than `max_age` ago, unless they
+    /// are still in use, see [VideoStore::set_max_age][tf_core::VideoStore::set_max_age].
+    ///
+    /// This also applies without a video cache (see [Joiner::set_video_cache]) and keeps the
+    /// memory used for the [AnyVideo]s bounded.
+    pub fn set_video_max_age(&self, max_age: Option<chrono::Duration>) {
+        #[cfg(feature = "youtube")]
+        self.yt_pipeline.set_video_max_age(max_age);
+        #[cfg(feature = "peertube")]
+        self.pt_pipeline.set_video_max_age(max_age);
+        #[cfg(feature = "lbry")]
+        self.lbry_pipeline.set_video_max_age(max_age);
+        // -- Add set_video_max_age here.
+        #[cfg(test)]
+        self.test_pipeline.set_video_max_age(max_age);
+        self.recount_unread();
+    }
+
+    /// Cache the thumbnails of the [AnyVideo]s in the given directory, see [ThumbnailCache].
+    ///
+    /// Thumbnails downloaded longer than `max_age` ago are downloaded again if requested.
+    /// These expired thumbnails are removed from the directory immediately.
+    pub fn set_thumbnail_cache<P: AsRef<Path>>(
+        &self,
+        directory: P,
+        max_age: Option<chrono::Duration>,
+    ) {
+        let cache = ThumbnailCache::new(directory).with_max_age(max_age);
+        cache.prune();
+        *self.thumbnail_cache.lock().unwrap() = Some(cache);
+    }
+
+    /// Get the [ThumbnailCache] set using [Joiner::set_thumbnail_cache].
+    pub fn thumbnail_cache(&self) -> Option<ThumbnailCache> {
+        self.thumbnail_cache.lock().unwrap().clone()
+    }
+
+    /// Get the thumbnail of the [AnyVideo] in the given [ThumbnailSize].
+    ///
+    /// The thumbnail is fetched using the [Transport] of the [Platform] of the [AnyVideo].
+    /// If a [ThumbnailCache] is set, cached thumbnails are used without accessing the network.
+    pub async fn thumbnail(&self, video: &AnyVideo, size: ThumbnailSize) -> image::DynamicImage {
+        let client = self.transport(&video.platform());
+        let cache = self.thumbnail_cache();
+        match cache {
+            Some(cache) => cache.thumbnail(video, size, client.as_ref()).await,
+            None => size.resize(video.thumbnail_with_client(client.as_ref()).await),
+        }
+    }
+
+    /// Get all [AnyVideo]s known to the pipelines, including the ones loaded from the video cache
+    /// (see [Joiner::set_video_cache]).
+    ///
+    /// The [AnyVideo]s are filtered and sorted like in [Joiner::generate].
+    pub fn stored_videos(&self) -> Vec<AnyVideo> {
+        let mut videos: Vec<AnyVideo> = vec![];
+        #[cfg(feature = "youtube")]
+        videos.extend(
+            self.yt_pipeline
+                .stored_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+        #[cfg(feature = "peertube")]
+        videos.extend(
+            self.pt_pipeline
+                .stored_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+        #[cfg(feature = "lbry")]
+        videos.extend(
+            self.lbry_pipeline
+                .stored_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+        // -- Add videos.extend here.
+        #[cfg(test)]
+        videos.extend(
+            self.test_pipeline
+                .stored_videos()
+                .into_iter()
+                .map(|v| v.into()),
+        );
+
+        videos.retain(|v| !self.is_hidden(v));
+        self.sort_feed(&mut videos);
+        videos
+    }
+
+    /// Load the next page of older [AnyVideo]s of every [AnySubscription], extending the feed
+    /// backwards in time.
+    ///
+    /// Every call loads one more page per [AnySubscription], see
+    /// [PagedGenerator][tf_core::PagedGenerator]. The pages are fetched by the pipelines according
+    /// to the [FetchPolicy]. If the feed of a [AnySubscription] is its first page, the following
+    /// one is loaded first (see [RefreshState::next_page][tf_core::RefreshState::next_page]),
+    /// otherwise the first page, giving back only its [AnyVideo]s not known before.
+    /// [AnySubscription]s which are [muted][crate::SubscriptionSettings::muted] are skipped.
+    ///
+    /// The [AnyVideo]s are stored in the pipelines like the ones of [Joiner::generate] and given
+    /// back filtered and sorted like those.
+    pub async fn load_older(&self, errors: &ErrorStore) -> Vec<AnyVideo> {
+        let start = chrono::Local::now().naive_local();
+        let requests: Vec<(AnySubscription, Option<PageCursor>)> = {
+            let mut pages = self.pages.lock().unwrap();
+            let mut requests = vec![];
+            for subscription in self.subscription_list.iter() {
+                if self.subscription_list.settings(&subscription).muted() {
+                    continue;
+                }
+                let cursor = match pages.get(&subscription) {
+                    Some(PageState::Exhausted) => continue,
+                    Some(PageState::Next(cursor)) => Some(cursor.clone()),
+                    None => match self.next_page(&subscription) {
+                        Some(Some(cursor)) => Some(cursor),
+                        Some(None) => {
+                            pages.insert(subscription, PageState::Exhausted);
+                            continue;
+                        }
+                        None => None,
+                    },
+                };
+                requests.push((subscription, cursor));
+            }
+            requests
+        };
+
+        let results = self.fetch_pages(errors, &requests).await;
+
+        let mut videos = vec![];
+        {
+            let mut pages = self.pages.lock().unwrap();
+            for ((subscription, cursor), (page, failed)) in requests.into_iter().zip(results) {
+                let first_page = cursor.is_none();
+                let (page_videos, next) = page.into_parts();
+                // Failed pages are retried on the next call.
+                match (failed, cursor, next) {
+                    (true, Some(cursor), _) => pages.insert(subscription, PageState::Next(cursor)),
+                    (true, None, _) => pages.remove(&subscription),
+                    (false, _, Some(next)) => pages.insert(subscription, PageState::Next(next)),
+                    (false, _, None) => pages.insert(subscription, PageState::Exhausted),
+                };
+                videos.extend(
+                    page_videos
+                        .into_iter()
+                        .filter(|v| !first_page || self.first_seen(v).is_some_and(|f| f >= start)),
+                );
+            }
+        }
+
+        videos
+            .iter()
+            .map(|v| v.subscription())
+            .for_each(|s| self.subscription_list.update(s));
+        self.update_unread(&videos);
+        self.forward_updates();
+        videos.retain(|v| !self.is_hidden(v));
+        self.sort_feed(&mut videos);
+        videos
+    }
+
+    /// Get the [PageCursor] to the page of older [AnyVideo]s following the last fetched feed of
+    /// the [AnySubscription], see [RefreshState::next_page][tf_core::RefreshState::next_page].
+    fn next_page(&self, subscription: &AnySubscription) -> Option<Option<PageCursor>> {
+        match subscription {
+            #[cfg(feature = "youtube")]
+            AnySubscription::Youtube(s) => self.yt_pipeline.next_page(s),
+            #[cfg(feature = "peertube")]
+            AnySubscription::Peertube(s) => self.pt_pipeline.next_page(s),
+            #[cfg(feature = "lbry")]
+            AnySubscription::Lbry(s) => self.lbry_pipeline.next_page(s),
+            // -- Add case here.
+            #[cfg(test)]
+            AnySubscription::Test(s) => self.test_pipeline.next_page(s),
+        }
+    }
+
+    /// Fetch the pages of the given [AnySubscription]s at the given [PageCursor]s using the
+    /// pipelines, see [Pipeline::fetch_pages].
+    ///
+    /// Gives back, in the same order, the pages together with whether fetching them failed.
+    async fn fetch_pages(
+        &self,
+        errors: &ErrorStore,
+        pages: &[(AnySubscription, Option<PageCursor>)],
+    ) -> Vec<(Page<AnyVideo>, bool)> {
+        #[cfg(feature = "youtube")]
+        let mut yt_pages = vec![];
+        #[cfg(feature = "peertube")]
+        let mut pt_pages = vec![];
+        #[cfg(feature = "lbry")]
+        let mut lbry_pages = vec![];
+        // -- Add vec here.
+        #[cfg(test)]
+        let mut test_pages = vec![];
+
+        for (index, (subscription, cursor)) in pages.iter().cloned().enumerate() {
+            match subscription {
+                #[cfg(feature = "youtube")]
+                AnySubscription::Youtube(s) => yt_pages.push((index, (s, cursor))),
+                #[cfg(feature = "peertube")]
+                AnySubscription::Peertube(s) => pt_pages.push((index, (s, cursor))),
+                #[cfg(feature = "lbry")]
+                AnySubscription::Lbry(s) => lbry_pages.push((index, (s, cursor))),
+                // -- Add case here.
+                #[cfg(test)]
+                AnySubscription::Test(s) => test_pages.push((index, (s, cursor))),
+            }
+        }
+
+        type Fetched = Vec<(usize, (Page<AnyVideo>, bool))>;
+        let mut fetches: Vec<Pin<Box<dyn Future<Output = Fetched> + std::marker::Send>>> = vec![];
+        #[cfg(feature = "youtube")]
+        fetches.push(Box::pin(async move {
+            let (indices, pages): (Vec<_>, Vec<_>) = yt_pages.into_iter().unzip();
+            let results = self.yt_pipeline.fetch_pages(errors, &pages).await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (page, failed))| (i, (page.map(|v| v.into()), failed)))
+                .collect()
+        }));
+        #[cfg(feature = "peertube")]
+        fetches.push(Box::pin(async move {
+            let (indices, pages): (Vec<_>, Vec<_>) = pt_pages.into_iter().unzip();
+            let results = self.pt_pipeline.fetch_pages(errors, &pages).await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (page, failed))| (i, (page.map(|v| v.into()), failed)))
+                .collect()
+        }));
+        #[cfg(feature = "lbry")]
+        fetches.push(Box::pin(async move {
+            let (indices, pages): (Vec<_>, Vec<_>) = lbry_pages.into_iter().unzip();
+            let results = self.lbry_pipeline.fetch_pages(errors, &pages).await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (page, failed))| (i, (page.map(|v| v.into()), failed)))
+                .collect()
+        }));
+        // -- Add fetches.push here.
+        #[cfg(test)]
+        fetches.push(Box::pin(async move {
+            let (indices, pages): (Vec<_>, Vec<_>) = test_pages.into_iter().unzip();
+            let results = self.test_pipeline.fetch_pages(errors, &pages).await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (page, failed))| (i, (page.map(|v| v.into()), failed)))
+                .collect()
+        }));
+
+        let mut results = (0..pages.len())
+            .map(|_| (Page::new(vec![], None), false))
+            .collect::<Vec<_>>();
+        for (index, result) in futures::future::join_all(fetches).await.concat() {
+            results[index] = result;
+        }
+        results
+    }
+
+    /// Whether all older [AnyVideo]s of the [AnySubscription] were loaded using [Joiner::load_older].
+    pub fn all_older_loaded(&self, subscription: &AnySubscription) -> bool {
+        matches!(
+            self.pages.lock().unwrap().get(subscription),
+            Some(PageState::Exhausted)
+        )
+    }
+
+    /// Generate the [AnyVideo]s of only the [AnySubscription]s in the given groups,
+    /// see [AnySubscriptionList::group_members].
+    ///
+    /// The [AnyVideo]s are fetched and stored by the pipelines like the ones of [Joiner::generate]
+    /// and given back filtered and sorted like those. This does not count as a refresh of the
+    /// whole feed, i.e. [Joiner::is_new] is not affected.
+    pub async fn generate_groups<S: AsRef<str>>(
+        &self,
+        errors: &ErrorStore,
+        groups: &[S],
+    ) -> Vec<AnyVideo> {
+        #[cfg(feature = "youtube")]
+        let mut yt_subscriptions = vec![];
+        #[cfg(feature = "peertube")]
+        let mut pt_subscriptions = vec![];
+        #[cfg(feature = "lbry")]
+        let mut lbry_subscriptions = vec![];
+        // -- Add vec here.
+        #[cfg(test)]
+        let mut test_subscriptions = vec![];
+
+        for subscription in self.subscription_list.group_members(groups) {
+            match subscription {
+                #[cfg(feature = "youtube")]
+                AnySubscription::Youtube(s) => yt_subscriptions.push(s),
+                #[cfg(feature = "peertube")]
+                AnySubscription::Peertube(s) => pt_subscriptions.push(s),
+                #[cfg(feature = "lbry")]
+                AnySubscription::Lbry(s) => lbry_subscriptions.push(s),
+                // -- Add case here.
+                #[cfg(test)]
+                AnySubscription::Test(s) => test_subscriptions.push(s),
+            }
+        }
+
+        let mut generators: Vec<Pin<Box<dyn Future<Output = Vec<AnyVideo>> + std::marker::Send>>> =
+            vec![];
+        #[cfg(feature = "youtube")]
+        generators.push(Box::pin(async move {
+            let videos = self
+                .yt_pipeline
+                .generate_subscriptions(errors, &yt_subscriptions)
+                .await;
+            videos.into_iter().map(|v| v.into()).collect()
+        }));
+        #[cfg(feature = "peertube")]
+        generators.push(Box::pin(async move {
+            let videos = self
+                .pt_pipeline
+                .generate_subscriptions(errors, &pt_subscriptions)
+                .await;
+            videos.into_iter().map(|v| v.into()).collect()
+        }));
+        #[cfg(feature = "lbry")]
+        generators.push(Box::pin(async move {
+            let videos = self
+                .lbry_pipeline
+                .generate_subscriptions(errors, &lbry_subscriptions)
+                .await;
+            videos.into_iter().map(|v| v.into()).collect()
+        }));
+        // -- Add generators.push here.
+        #[cfg(test)]
+        generators.push(Box::pin(async move {
+            let videos = self
+                .test_pipeline
+                .generate_subscriptions(errors, &test_subscriptions)
+                .await;
+            videos.into_iter().map(|v| v.into()).collect()
+        }));
+
+        let mut videos = futures::future::join_all(generators).await.concat();
+        videos
+            .iter()
+            .map(|v| v.subscription())
+            .for_each(|s| self.subscription_list.update(s));
+        self.update_unread(&videos);
+        self.forward_updates();
+        videos.retain(|v| !self.is_hidden(v));
+        self.sort_feed(&mut videos);
+        videos
+    }
+
+    /// Refresh all [AnySubscription]s, giving back only the [AnyVideo]s that were not seen in a
+    /// previous refresh or generation, filtered and sorted like in [Joiner::generate].
+    ///
+    /// The pipelines use conditional requests where supported, see [Pipeline::refresh].
+    /// Like [Joiner::generate][Generator::generate], this marks the start of a new refresh for
+    /// [Joiner::is_new], updates the unread counts and notifies the [Observer]s about the new
+    /// [AnyVideo]s using [FeedEvent::NewVideos].
+    pub async fn refresh(&self, errors: &ErrorStore) -> Vec<AnyVideo> {
+        let start = chrono::Local::now().naive_local();
+        let subscriptions = self.subscription_list.iter().collect::<Vec<_>>();
+        let mut videos = self
+            .refresh_subscriptions(errors, &subscriptions)
+            .await
+            .into_iter()
+            .flat_map(|(_feed, new)| new)
+            .collect::<Vec<_>>();
+        *self.last_refresh.lock().unwrap() = Some(start);
+        videos.retain(|v| !self.is_hidden(v));
+        self.sort_feed(&mut videos);
+        videos
+    }
+
+    /// Refresh only the given [AnySubscription]s, e.g. the ones that are due.
+    ///
+    /// Gives back for every [AnySubscription], in the same order, all [AnyVideo]s of its feed
+    /// together with the [AnyVideo]s that were not seen in a previous refresh or generation,
+    /// both unfiltered. The [AnyVideo]s are stored in the pipelines, the unread counts are updated
+    /// and the [Observer]s are notified about the [AnyVideo]s first seen during this refresh,
+    /// unless this is the initial fill of the feed (see [Joiner::generate][Generator::generate]).
+    /// This does not count as a refresh of the whole feed, i.e. [Joiner::is_new] is not affected.
+    pub async fn refresh_subscriptions(
+        &self,
+        errors: &ErrorStore,
+        subscriptions: &[AnySubscription],
+    ) -> Vec<(Vec<AnyVideo>, Vec<AnyVideo>)> {
+        let initial = self.is_initial_fill();
+        let start = chrono::Local::now().naive_local();
+
+        #[cfg(feature = "youtube")]
+        let mut yt_subscriptions = vec![];
+        #[cfg(feature = "peertube")]
+        let mut pt_subscriptions = vec![];
+        #[cfg(feature = "lbry")]
+        let mut lbry_subscriptions = vec![];
+        // -- Add vec here.
+        #[cfg(test)]
+        let mut test_subscriptions = vec![];
+
+        for (index, subscription) in subscriptions.iter().cloned().enumerate() {
+            match subscription {
+                #[cfg(feature = "youtube")]
+                AnySubscription::Youtube(s) => yt_subscriptions.push((index, s)),
+                #[cfg(feature = "peertube")]
+                AnySubscription::Peertube(s) => pt_subscriptions.push((index, s)),
+                #[cfg(feature = "lbry")]
+                AnySubscription::Lbry(s) => lbry_subscriptions.push((index, s)),
+                // -- Add case here.
+                #[cfg(test)]
+                AnySubscription::Test(s) => test_subscriptions.push((index, s)),
+            }
+        }
+
+        type Refreshed = Vec<(usize, (Vec<AnyVideo>, Vec<AnyVideo>))>;
+        let mut generators: Vec<Pin<Box<dyn Future<Output = Refreshed> + std::marker::Send>>> =
+            vec![];
+        #[cfg(feature = "youtube")]
+        generators.push(Box::pin(async move {
+            let (indices, subscriptions): (Vec<_>, Vec<_>) = yt_subscriptions.into_iter().unzip();
+            let results = self
+                .yt_pipeline
+                .refresh_subscriptions(errors, &subscriptions)
+                .await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (feed, new))| {
+                    let feed = feed.into_iter().map(|v| v.into()).collect();
+                    (i, (feed, new.into_iter().map(|v| v.into()).collect()))
+                })
+                .collect()
+        }));
+        #[cfg(feature = "peertube")]
+        generators.push(Box::pin(async move {
+            let (indices, subscriptions): (Vec<_>, Vec<_>) = pt_subscriptions.into_iter().unzip();
+            let results = self
+                .pt_pipeline
+                .refresh_subscriptions(errors, &subscriptions)
+                .await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (feed, new))| {
+                    let feed = feed.into_iter().map(|v| v.into()).collect();
+                    (i, (feed, new.into_iter().map(|v| v.into()).collect()))
+                })
+                .collect()
+        }));
+        #[cfg(feature = "lbry")]
+        generators.push(Box::pin(async move {
+            let (indices, subscriptions): (Vec<_>, Vec<_>) = lbry_subscriptions.into_iter().unzip();
+            let results = self
+                .lbry_pipeline
+                .refresh_subscriptions(errors, &subscriptions)
+                .await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (feed, new))| {
+                    let feed = feed.into_iter().map(|v| v.into()).collect();
+                    (i, (feed, new.into_iter().map(|v| v.into()).collect()))
+                })
+                .collect()
+        }));
+        // -- Add generators.push here.
+        #[cfg(test)]
+        generators.push(Box::pin(async move {
+            let (indices, subscriptions): (Vec<_>, Vec<_>) = test_subscriptions.into_iter().unzip();
+            let results = self
+                .test_pipeline
+                .refresh_subscriptions(errors, &subscriptions)
+                .await;
+            indices
+                .into_iter()
+                .zip(results)
+                .map(|(i, (feed, new))| {
+                    let feed = feed.into_iter().map(|v| v.into()).collect();
+                    (i, (feed, new.into_iter().map(|v| v.into()).collect()))
+                })
+                .collect()
+        }));
+
+        let mut results = vec![(vec![], vec![]); subscriptions.len()];
+        for (index, result) in futures::future::join_all(generators).await.concat() {
+            results[index] = result;
+        }
+
+        results
+            .iter()
+            .flat_map(|(feed, _new)| feed)
+            .map(|v| v.subscription())
+            .for_each(|s| self.subscription_list.update(s));
+        let first_seen = results
+            .iter()
+            .flat_map(|(_feed, new)| new)
+            .filter(|v| self.first_seen(v).is_some_and(|f| f >= start))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.update_unread(
+            &results
+                .iter()
+                .flat_map(|(feed, _new)| feed)
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+        self.forward_updates();
+        if !initial {
+            self.notify_new_videos(&first_seen);
+        }
+        results
+    }
+
+    /// Query a page of the feed, see [FeedQuery].
+    ///
+    /// The query is evaluated on all [AnyVideo]s known to the pipelines, i.e. the ones generated
+    /// before and the ones loaded from the video cache; nothing is fetched. [AnyVideo]s hidden by
+    /// [Joiner::is_hidden] are not part of the feed. Only the [AnyVideo]s of the page are put into
+    /// the video storage of the pipelines, see [Joiner::upgrade_video].
+    pub fn query(&self, query: &FeedQuery) -> FeedPage {
+        let window = query.pipeline_window();
+
+        let mut videos: Vec<AnyVideo> = vec![];
+        #[cfg(feature = "youtube")]
+        if query.includes_platform(&Platform::Youtube) {
+            videos.extend(
+                self.yt_pipeline
+                    .select(&window, |v| self.is_queried(query, Platform::Youtube, v))
+                    .into_iter()
+                    .map(|v| Arc::new(Mutex::new(v)).into()),
+            );
+        }
+        #[cfg(feature = "peertube")]
+        if query.includes_platform(&Platform::Peertube) {
+            videos.extend(
+                self.pt_pipeline
+                    .select(&window, |v| self.is_queried(query, Platform::Peertube, v))
+                    .into_iter()
+                    .map(|v| Arc::new(Mutex::new(v)).into()),
+            );
+        }
+        #[cfg(feature = "lbry")]
+        if query.includes_platform(&Platform::Lbry) {
+            videos.extend(
+                self.lbry_pipeline
+                    .select(&window, |v| self.is_queried(query, Platform::Lbry, v))
+                    .into_iter()
+                    .map(|v| Arc::new(Mutex::new(v)).into()),
+            );
+        }
+        // -- Add videos.extend here.
+        #[cfg(test)]
+        if query.includes_platform(&Platform::Test) {
+            videos.extend(
+                self.test_pipeline
+                    .select(&window, |v| self.is_queried(query, Platform::Test, v))
+                    .into_iter()
+                    .map(|v| Arc::new(Mutex::new(v)).into()),
+            );
+        }
+
+        videos.sort_by_cached_key(WindowCursor::of);
+        let videos = videos
+            .into_iter()
+            .skip(query.offset())
+            .take(query.limit().unwrap_or(usize::MAX))
+            .map(|v| self.upgrade_video(&v))
+            .collect();
+        FeedPage::new(videos, query.limit())
+    }
+
+    /// Whether the borrowed video of the [Platform] matches the [FeedQuery] and is not hidden,
+    /// see [FeedQuery::matches] and [Joiner::is_hidden].
+    fn is_queried<V: Video>(&self, query: &FeedQuery, platform: Platform, video: &V) -> bool
+    where
+        AnySubscription: From<V::Subscription>,
+    {
+        let subscription = AnySubscription::from(video.subscription());
+        query.includes_subscription(&subscription)
+            && !self.subscription_list.settings(&subscription).muted()
+            && !self
+                .filters
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|f| f.matches_target(&PlatformVideo(platform.clone(), video)))
+    }
+
     /// Upgrades a normal [AnyVideo] into a [AnyVideo] in the video storage of the pipelines.
     pub fn upgrade_video(&self, video: &AnyVideo) -> AnyVideo {
         match video {
@@ -126,7 +1334,17 @@ impl Generator for Joiner {
 
     type Iterator = std::vec::IntoIter<AnyVideo>;
 
+    /// Generate the [AnyVideo]s from all platforms, filtered and sorted by the
+    /// [priority][crate::SubscriptionSettings::priority] of their [AnySubscription], then newest first.
+    /// The [AnyVideo]s of muted [AnySubscription]s are left out as well, see [Joiner::is_hidden].
+    ///
+    /// This marks the start of a new refresh for [Joiner::is_new], updates the unread counts
+    /// and notifies the [Observer]s about the new [AnyVideo]s using [FeedEvent::NewVideos].
+    /// The initial fill of the feed, i.e. the first generation without any known [AnyVideo]s,
+    /// is not notified.
     async fn generate(&self, errors: &ErrorStore) -> Self::Iterator {
+        let initial = self.is_initial_fill();
+        *self.last_refresh.lock().unwrap() = Some(chrono::Local::now().naive_local());
         // TODO: Error handling
         // TODO: More efficient
         let mut generators: Vec<
@@ -173,15 +1391,916 @@ impl Generator for Joiner {
             .iter()
             .map(|v| v.subscription())
             .for_each(|s| self.subscription_list.update(s));
-        videos.retain(|v| !self.filters.lock().unwrap().matches(v));
-        videos.sort_by_cached_key(|v| v.uploaded());
-        videos.reverse();
+        let new = videos
+            .iter()
+            .filter(|v| self.is_new(v))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.update_unread(&videos);
+        self.forward_updates();
+        videos.retain(|v| !self.is_hidden(v));
+        self.sort_feed(&mut videos);
+        if !initial {
+            self.notify_new_videos(&new);
+        }
         videos.into_iter()
     }
 }
 
+impl StreamGenerator for Joiner {
+    type Item = AnyVideo;
+
+    /// Generate batches of [AnyVideo]s from all platforms as soon as they are available.
+    ///
+    /// Every batch is filtered using the [FilterGroup] and [Joiner::is_hidden] and sorted like in
+    /// [Joiner::generate].
+    /// Like [Joiner::generate], this marks the start of a new refresh, updates the unread counts
+    /// and notifies the [Observer]s about the new [AnyVideo]s of every batch.
+    fn generate_stream<'a>(&'a self, errors: &'a ErrorStore) -> BoxStream<'a, Vec<AnyVideo>> {
+        let initial = self.is_initial_fill();
+        *self.last_refresh.lock().unwrap() = Some(chrono::Local::now().naive_local());
+        let mut streams: Vec<BoxStream<'a, Vec<AnyVideo>>> = vec![];
+        #[cfg(feature = "youtube")]
+        streams.push(
+            self.yt_pipeline
+                .generate_stream(errors)
+                .map(|b| b.into_iter().map(|v| v.into()).collect())
+                .boxed(),
+        );
+        #[cfg(feature = "peertube")]
+        streams.push(
+            self.pt_pipeline
+                .generate_stream(errors)
+                .map(|b| b.into_iter().map(|v| v.into()).collect())
+                .boxed(),
+        );
+        #[cfg(feature = "lbry")]
+        streams.push(
+            self.lbry_pipeline
+                .generate_stream(errors)
+                .map(|b| b.into_iter().map(|v| v.into()).collect())
+                .boxed(),
+        );
+        // -- Add streams.push here.
+        #[cfg(test)]
+        streams.push(
+            self.test_pipeline
+                .generate_stream(errors)
+                .map(|b| b.into_iter().map(|v| v.into()).collect())
+                .boxed(),
+        );
+
+        futures::stream::select_all(streams)
+            .map(move |mut videos: Vec<AnyVideo>| {
+                videos
+                    .iter()
+                    .map(|v| v.subscription())
+                    .for_each(|s| self.subscription_list.update(s));
+                let new = videos
+                    .iter()
+                    .filter(|v| self.is_new(v))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                self.update_unread(&videos);
+                self.forward_updates();
+                videos.retain(|v| !self.is_hidden(v));
+                self.sort_feed(&mut videos);
+                if !initial {
+                    self.notify_new_videos(&new);
+                }
+                videos
+            })
+            .filter(|videos| futures::future::ready(!videos.is_empty()))
+            .boxed()
+    }
+}
+
+impl Observable<UnreadEvent> for Joiner {
+    fn attach(&mut self, observer: std::sync::Weak<Mutex<Box<dyn Observer<UnreadEvent> + Send>>>) {
+        self.observers.attach(observer)
+    }
+
+    fn detach(&mut self, observer: std::sync::Weak<Mutex<Box<dyn Observer<UnreadEvent> + Send>>>) {
+        self.observers.detach(observer)
+    }
+}
+
+impl Observable<FeedEvent> for Joiner {
+    fn attach(&mut self, observer: std::sync::Weak<Mutex<Box<dyn Observer<FeedEvent> + Send>>>) {
+        self.feed_observers.attach(observer)
+    }
+
+    fn detach(&mut self, observer: std::sync::Weak<Mutex<Box<dyn Observer<FeedEvent> + Send>>>) {
+        self.feed_observers.detach(observer)
+    }
+}
+
 impl Default for Joiner {
     fn default() -> Self {
         Joiner::new()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use tf_test::TestSubscription;
+
+    use crate::{FeedQuery, SubscriptionSettings};
+
+    #[tokio::test]
+    async fn joiner_generate_stream() {
+        let joiner = Joiner::new();
+        joiner
+            .subscription_list()
+            .add(TestSubscription::new("Channel1").into());
+        joiner
+            .subscription_list()
+            .add(TestSubscription::new("Channel2").into());
+
+        let errors = ErrorStore::new();
+        let batches = joiner.generate_stream(&errors).collect::<Vec<_>>().await;
+
+        assert_eq!(batches.len(), 2);
+        assert!(batches.iter().all(|b| b.len() == 2));
+
+        let merged = joiner
+            .generate_merged_by_key(&errors, |v| std::cmp::Reverse(v.uploaded()))
+            .await;
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+
+        assert_eq!(merged.len(), 4);
+        assert_eq!(
+            merged.iter().map(|v| v.uploaded()).collect::<Vec<_>>(),
+            generated.iter().map(|v| v.uploaded()).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn joiner_refresh() {
+        let joiner = Joiner::new();
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        joiner.subscription_list().add(channel1.clone());
+
+        let errors = ErrorStore::new();
+        assert_eq!(joiner.generate(&errors).await.len(), 2);
+        assert!(joiner.refresh(&errors).await.is_empty());
+
+        joiner.subscription_list().add(channel2.clone());
+        let refreshed = joiner.refresh(&errors).await;
+        assert_eq!(refreshed.len(), 2);
+        assert!(refreshed.iter().all(|v| v.subscription() == channel2));
+
+        let results = joiner
+            .refresh_subscriptions(&errors, &[channel2.clone(), channel1.clone()])
+            .await;
+        assert_eq!(results.len(), 2);
+        assert!(results[0].0.iter().all(|v| v.subscription() == channel2));
+        assert_eq!(results[1].0.len(), 2);
+        assert!(results.iter().all(|(_, new)| new.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn joiner_load_older() {
+        let joiner = Joiner::new();
+        let subscription: AnySubscription = TestSubscription::new("Channel1").into();
+        let muted: AnySubscription = TestSubscription::new("Channel2").into();
+        joiner.subscription_list().add(subscription.clone());
+        joiner.subscription_list().add(muted.clone());
+        joiner
+            .subscription_list()
+            .set_settings(&muted, SubscriptionSettings::new().with_muted(true));
+
+        let errors = ErrorStore::new();
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        assert_eq!(generated.len(), 2);
+        assert!(!joiner.all_older_loaded(&subscription));
+
+        // The page following the feed is loaded directly, the muted subscription is skipped.
+        let older = joiner.load_older(&errors).await;
+        assert_eq!(older.len(), 1);
+        assert_eq!(older[0].title(), "This is the test video 0");
+        assert!(older[0].uploaded() < generated[1].uploaded());
+        assert!(joiner.all_older_loaded(&subscription));
+        assert!(!joiner.all_older_loaded(&muted));
+        assert_eq!(joiner.stored_videos().len(), 3);
+
+        assert!(joiner.load_older(&errors).await.is_empty());
+        assert!(errors.iter_with_context().next().is_none());
+    }
+
+    #[tokio::test]
+    async fn joiner_video_cache() {
+        let directory =
+            std::env::temp_dir().join(format!("tf_join_video_cache_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let joiner = Joiner::new();
+        joiner.set_video_cache(&directory, None);
+        joiner
+            .subscription_list()
+            .add(TestSubscription::new("Channel1").into());
+
+        let errors = ErrorStore::new();
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        assert_eq!(generated.len(), 2);
+
+        let joiner_restarted = Joiner::new();
+        assert!(joiner_restarted.stored_videos().is_empty());
+        joiner_restarted.set_video_cache(&directory, None);
+
+        let mut titles = joiner_restarted
+            .stored_videos()
+            .iter()
+            .map(|v| v.title())
+            .collect::<Vec<_>>();
+        titles.sort();
+        assert_eq!(
+            titles,
+            vec![
+                "This is the test video 1".to_string(),
+                "This is the test video 2".to_string()
+            ]
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn joiner_watch_progress() {
+        let directory =
+            std::env::temp_dir().join(format!("tf_join_watch_progress_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let joiner = Joiner::new();
+        joiner.set_video_cache(&directory, None);
+        joiner
+            .subscription_list()
+            .add(TestSubscription::new("Channel1").into());
+
+        let errors = ErrorStore::new();
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        joiner.report_progress(
+            &generated[0],
+            Duration::from_secs(30),
+            Duration::from_secs(20),
+        );
+        joiner.set_watched(&generated[1], true);
+        assert_eq!(generated[0].progress().position(), Duration::from_secs(30));
+        assert!(!generated[0].watched());
+        assert!(generated[1].watched());
+
+        let joiner_restarted = Joiner::new();
+        joiner_restarted.set_video_cache(&directory, None);
+        let stored = joiner_restarted.stored_videos();
+
+        let find = |title: String| {
+            stored
+                .iter()
+                .find(|v| v.title() == title)
+                .unwrap()
+                .progress()
+        };
+        let progress = find(generated[0].title());
+        assert_eq!(progress.position(), Duration::from_secs(30));
+        assert_eq!(progress.watched_time(), Duration::from_secs(20));
+        assert!(!progress.is_watched());
+        assert!(find(generated[1].title()).is_watched());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    struct PlaylistRecorder(Arc<Mutex<Vec<AnyVideo>>>);
+
+    impl Observer<tf_playlist::PlaylistEvent<AnyVideo>> for PlaylistRecorder {
+        fn notify(&mut self, message: tf_playlist::PlaylistEvent<AnyVideo>) {
+            if let tf_playlist::PlaylistEvent::Update(video) = message {
+                self.0.lock().unwrap().push(video);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn joiner_playlist_updates() {
+        let joiner = Joiner::new();
+        let channel: AnySubscription = TestSubscription::new("PlaylistChannel").into();
+        joiner.subscription_list().add(channel);
+        let mut playlist_manager = PlaylistManager::new();
+        joiner.set_playlist_manager(playlist_manager.clone());
+
+        let updated = Arc::new(Mutex::new(vec![]));
+        let observer: Arc<Mutex<Box<dyn Observer<tf_playlist::PlaylistEvent<AnyVideo>> + Send>>> =
+            Arc::new(Mutex::new(Box::new(PlaylistRecorder(updated.clone()))));
+        let playlist = "Watch later".to_owned();
+        playlist_manager.attach_at(Arc::downgrade(&observer), &playlist);
+
+        let errors = ErrorStore::new();
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        let stored = match &generated[0] {
+            AnyVideo::Test(v) => v.clone(),
+            _ => panic!("Expected a test video"),
+        };
+        // A copy of the video, e.g. loaded from a file.
+        let copy: AnyVideo = Arc::new(Mutex::new(stored.lock().unwrap().clone())).into();
+        playlist_manager.toggle(&playlist, &copy);
+
+        joiner.generate(&errors).await.for_each(drop);
+        assert!(updated.lock().unwrap().is_empty());
+
+        // Edit the stored video, the next generation reverts the edit in place.
+        let edited = stored
+            .lock()
+            .unwrap()
+            .internal()
+            .with_metadata(tf_core::VideoMetadata::new().with_views(Some(10)));
+        joiner.upgrade_video(&Arc::new(Mutex::new(tf_core::ExpandedVideo::from(edited))).into());
+        joiner.generate(&errors).await.for_each(drop);
+
+        let updated = updated.lock().unwrap();
+        assert_eq!(updated.len(), 1);
+        assert!(matches!(&updated[0], AnyVideo::Test(v) if Arc::ptr_eq(v, &stored)));
+        assert!(matches!(
+            &playlist_manager.items(&playlist)[0],
+            AnyVideo::Test(v) if Arc::ptr_eq(v, &stored)
+        ));
+    }
+
+    struct UnreadRecorder(Arc<Mutex<Vec<usize>>>);
+
+    impl Observer<UnreadEvent> for UnreadRecorder {
+        fn notify(&mut self, message: UnreadEvent) {
+            let UnreadEvent::Changed(total) = message;
+            self.0.lock().unwrap().push(total);
+        }
+    }
+
+    #[tokio::test]
+    async fn joiner_unread() {
+        let directory = std::env::temp_dir().join(format!("tf_join_unread_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut joiner = Joiner::new();
+        joiner.set_video_cache(&directory, None);
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        joiner.subscription_list().add(channel1.clone());
+        joiner.subscription_list().add(channel2.clone());
+
+        let totals = Arc::new(Mutex::new(vec![]));
+        let observer: Arc<Mutex<Box<dyn Observer<UnreadEvent> + Send>>> =
+            Arc::new(Mutex::new(Box::new(UnreadRecorder(totals.clone()))));
+        joiner.attach(Arc::downgrade(&observer));
+
+        let errors = ErrorStore::new();
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        assert_eq!(generated.len(), 4);
+        assert!(generated.iter().all(|v| joiner.is_new(v)));
+        assert!(generated.iter().all(|v| joiner.first_seen(v).is_some()));
+        assert_eq!(joiner.unread_count(), 4);
+        assert_eq!(*totals.lock().unwrap(), vec![4]);
+
+        let video = generated
+            .iter()
+            .find(|v| v.subscription() == channel1)
+            .unwrap();
+        joiner.set_seen(video, true);
+        assert!(video.seen());
+        assert_eq!(joiner.unread_per_subscription().get(&channel1), Some(&1));
+        assert_eq!(joiner.unread_per_subscription().get(&channel2), Some(&2));
+
+        joiner.mark_subscription_seen(&channel1);
+        assert_eq!(joiner.unread_per_subscription().get(&channel1), None);
+        assert_eq!(joiner.unread_per_platform().get(&Platform::Test), Some(&2));
+
+        joiner.mark_all_seen();
+        assert_eq!(joiner.unread_count(), 0);
+        assert_eq!(*totals.lock().unwrap(), vec![4, 3, 2, 0]);
+
+        let regenerated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        assert!(regenerated.iter().all(|v| !joiner.is_new(v)));
+        assert_eq!(*totals.lock().unwrap(), vec![4, 3, 2, 0]);
+
+        let joiner_restarted = Joiner::new();
+        joiner_restarted.set_video_cache(&directory, None);
+        joiner_restarted.subscription_list().add(channel1);
+        let stored = joiner_restarted.stored_videos();
+        assert_eq!(stored.len(), 4);
+        assert!(stored.iter().all(|v| v.seen()));
+        assert_eq!(joiner_restarted.unread_count(), 0);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn joiner_unread_hidden() {
+        let joiner = Joiner::new();
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        joiner.subscription_list().add(channel1.clone());
+        joiner.subscription_list().add(channel2.clone());
+        joiner
+            .subscription_list()
+            .set_settings(&channel2, SubscriptionSettings::new().with_muted(true));
+        joiner.filters().lock().unwrap().add(AnyVideoFilter::new(
+            None,
+            Some(regex::Regex::new("video 1").unwrap()),
+            None,
+        ));
+
+        // Only the second video of the first channel is neither filtered nor muted.
+        let errors = ErrorStore::new();
+        assert_eq!(joiner.generate(&errors).await.count(), 1);
+        assert_eq!(joiner.unread_count(), 1);
+        assert_eq!(joiner.unread_per_subscription().get(&channel2), None);
+
+        joiner.mark_all_seen();
+        assert_eq!(joiner.unread_count(), 0);
+
+        // Unmuting the channel counts its videos again after they arrive.
+        joiner
+            .subscription_list()
+            .set_settings(&channel2, SubscriptionSettings::new());
+        joiner.generate(&errors).await.for_each(drop);
+        assert_eq!(joiner.unread_per_subscription().get(&channel2), Some(&1));
+    }
+
+    struct FeedRecorder(Arc<Mutex<Vec<(AnySubscription, usize, usize)>>>);
+
+    impl Observer<FeedEvent> for FeedRecorder {
+        fn notify(&mut self, message: FeedEvent) {
+            let FeedEvent::NewVideos(subscription, passing, filtered) = message;
+            self.0
+                .lock()
+                .unwrap()
+                .push((subscription, passing.len(), filtered.len()));
+        }
+    }
+
+    #[tokio::test]
+    async fn joiner_new_videos() {
+        let mut joiner = Joiner::new();
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        joiner.subscription_list().add(channel1);
+        joiner.filters().lock().unwrap().add(AnyVideoFilter::new(
+            None,
+            Some(regex::Regex::new("video 1").unwrap()),
+            None,
+        ));
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let observer: Arc<Mutex<Box<dyn Observer<FeedEvent> + Send>>> =
+            Arc::new(Mutex::new(Box::new(FeedRecorder(events.clone()))));
+        joiner.attach(Arc::downgrade(&observer));
+
+        // The initial fill of the feed is not notified.
+        let errors = ErrorStore::new();
+        assert_eq!(joiner.generate(&errors).await.count(), 1);
+        assert!(events.lock().unwrap().is_empty());
+
+        joiner.subscription_list().add(channel2.clone());
+        joiner.generate(&errors).await;
+        assert_eq!(*events.lock().unwrap(), vec![(channel2, 1, 1)]);
+
+        events.lock().unwrap().clear();
+        joiner.generate(&errors).await;
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn joiner_subscription_stream() {
+        let joiner = Joiner::new();
+        let mut subscription_list = joiner.subscription_list();
+        let mut events = subscription_list.subscribe(tf_observer::Backpressure::Unbounded);
+
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        subscription_list.add(channel1.clone());
+
+        // Calling back into the list while handling the message does not deadlock.
+        match events.next().await {
+            Some(crate::SubscriptionEvent::Add(s)) => subscription_list.remove(s),
+            _ => panic!("Expected the added subscription"),
+        }
+        assert!(matches!(
+            events.next().await,
+            Some(crate::SubscriptionEvent::Remove(s)) if s == channel1
+        ));
+        assert_eq!(subscription_list.iter().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn joiner_groups() {
+        let joiner = Joiner::new();
+        let mut subscription_list = joiner.subscription_list();
+        let mut events = Observable::<crate::GroupEvent>::subscribe(
+            &mut subscription_list,
+            tf_observer::Backpressure::Unbounded,
+        );
+
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        subscription_list.add(channel1.clone());
+        subscription_list.add(channel2.clone());
+        subscription_list.add_to_group("News", &channel1);
+        assert!(subscription_list.add_group("Music"));
+        assert!(!subscription_list.add_group("News"));
+        subscription_list.add_to_group("Music", &channel1);
+        subscription_list.add_to_group("Music", &channel2);
+        assert_eq!(subscription_list.groups(), vec!["News", "Music"]);
+        assert_eq!(
+            subscription_list.groups_of(&channel1),
+            vec!["News", "Music"]
+        );
+
+        let errors = ErrorStore::new();
+        let news = joiner.generate_groups(&errors, &["News"]).await;
+        assert_eq!(news.len(), 2);
+        assert!(news.iter().all(|v| v.subscription() == channel1));
+        assert_eq!(joiner.generate_groups(&errors, &["Music"]).await.len(), 4);
+        assert!(joiner.generate_groups(&errors, &["Other"]).await.is_empty());
+
+        let rows = subscription_list.rows();
+        assert_eq!(
+            rows[0],
+            vec!["test", "Channel1", "group:News", "group:Music"]
+        );
+        // Readers only knowing the subscriptions can still read the rows.
+        assert_eq!(
+            AnySubscription::try_from(rows[0].clone()),
+            Ok(channel1.clone())
+        );
+        let restored = AnySubscriptionList::default();
+        for row in rows {
+            restored.add_row(row).unwrap();
+        }
+        assert_eq!(restored.groups(), vec!["News", "Music"]);
+        assert_eq!(
+            restored.group_members(&["Music"]),
+            vec![channel1.clone(), channel2.clone()]
+        );
+
+        assert!(subscription_list.rename_group("News", "Headlines"));
+        subscription_list.remove(channel1.clone());
+        assert_eq!(subscription_list.group_members(&["Headlines"]), vec![]);
+        assert!(subscription_list.remove_group("Headlines"));
+        assert_eq!(subscription_list.groups(), vec!["Music"]);
+
+        use crate::GroupEvent;
+        let mut received = vec![];
+        while let Some(Some(event)) = futures::FutureExt::now_or_never(events.next()) {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 9);
+        assert!(matches!(&received[0], GroupEvent::Add(g) if g == "News"));
+        assert!(matches!(&received[1], GroupEvent::Join(g, s) if g == "News" && s == &channel1));
+        assert!(matches!(&received[2], GroupEvent::Add(g) if g == "Music"));
+        assert!(matches!(&received[4], GroupEvent::Join(g, s) if g == "Music" && s == &channel2));
+        assert!(
+            matches!(&received[5], GroupEvent::Rename(from, to) if from == "News" && to == "Headlines")
+        );
+        assert!(
+            matches!(&received[6], GroupEvent::Leave(g, s) if g == "Headlines" && s == &channel1)
+        );
+        assert!(matches!(&received[7], GroupEvent::Leave(g, s) if g == "Music" && s == &channel1));
+        assert!(matches!(&received[8], GroupEvent::Remove(g) if g == "Headlines"));
+    }
+
+    #[tokio::test]
+    async fn joiner_subscription_settings() {
+        let mut joiner = Joiner::new();
+        let errors = ErrorStore::new();
+        // Fill the empty feed initially such that the new videos are notified.
+        joiner.generate(&errors).await.for_each(drop);
+        let mut subscription_list = joiner.subscription_list();
+        let channel1: AnySubscription = TestSubscription::new("AliasedChannel").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        let channel3: AnySubscription = TestSubscription::new("Channel3").into();
+        subscription_list.add(channel1.clone());
+        subscription_list.add(channel2.clone());
+        subscription_list.add(channel3.clone());
+
+        let mut updates = Observable::<crate::SubscriptionEvent>::subscribe(
+            &mut subscription_list,
+            tf_observer::Backpressure::Unbounded,
+        );
+        let mut feed =
+            Observable::<FeedEvent>::subscribe(&mut joiner, tf_observer::Backpressure::Unbounded);
+
+        let settings = SubscriptionSettings::new()
+            .with_notify(false)
+            .with_alias(Some("My Channel"));
+        subscription_list.set_settings(&channel1, settings.clone());
+        subscription_list.set_settings(&channel1, settings.clone());
+        subscription_list.set_settings(&channel2, SubscriptionSettings::new().with_priority(2));
+        subscription_list.set_settings(&channel3, SubscriptionSettings::new().with_muted(true));
+        assert_eq!(subscription_list.settings(&channel1), settings);
+        assert_eq!(subscription_list.display_name(&channel1), "My Channel");
+        assert_eq!(channel1.to_string(), "AliasedChannel");
+        // Settings of subscriptions outside of the list are ignored.
+        let other: AnySubscription = TestSubscription::new("Other").into();
+        subscription_list.set_settings(&other, settings.clone());
+        assert_eq!(subscription_list.display_name(&other), "Other");
+        assert_eq!(
+            subscription_list.display_name(&channel2),
+            channel2.to_string()
+        );
+        assert_eq!(
+            subscription_list.ranked(),
+            vec![channel2.clone(), channel1.clone(), channel3.clone()]
+        );
+
+        let mut updated = vec![];
+        while let Some(Some(event)) = futures::FutureExt::now_or_never(updates.next()) {
+            match event {
+                crate::SubscriptionEvent::Update(s) => updated.push(s),
+                _ => panic!("Expected only updates"),
+            }
+        }
+        assert_eq!(
+            updated,
+            vec![channel1.clone(), channel2.clone(), channel3.clone()]
+        );
+
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        assert_eq!(generated.len(), 4);
+        assert!(generated.iter().all(|v| v.subscription() != channel3));
+        // The videos of the channel with the higher priority come first.
+        assert!(generated[..2].iter().all(|v| v.subscription() == channel2));
+        assert!(generated[2].uploaded() > generated[3].uploaded());
+        match futures::FutureExt::now_or_never(feed.next()) {
+            Some(Some(FeedEvent::NewVideos(s, passing, _))) => {
+                assert_eq!(s, channel2);
+                assert_eq!(passing.len(), 2);
+            }
+            _ => panic!("Expected new videos of the second channel"),
+        }
+        assert!(futures::FutureExt::now_or_never(feed.next()).is_none());
+
+        // The settings follow the identifying columns.
+        assert_eq!(
+            subscription_list.rows()[0],
+            vec!["test", "AliasedChannel", "notify:false", "alias:My Channel"]
+        );
+        let restored = AnySubscriptionList::default();
+        for row in subscription_list.rows() {
+            restored.add_row(row).unwrap();
+        }
+        assert_eq!(restored.settings(&channel1), settings);
+        assert!(restored.settings(&channel3).muted());
+        assert_eq!(restored.ranked(), subscription_list.ranked());
+    }
+
+    #[tokio::test]
+    async fn joiner_query() {
+        let joiner = Joiner::new();
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        let channel3: AnySubscription = TestSubscription::new("Channel3").into();
+        joiner.subscription_list().add(channel1.clone());
+        joiner.subscription_list().add(channel2.clone());
+        joiner.subscription_list().add(channel3.clone());
+        joiner
+            .subscription_list()
+            .set_settings(&channel3, SubscriptionSettings::new().with_muted(true));
+
+        let errors = ErrorStore::new();
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        assert_eq!(generated.len(), 4);
+
+        let first = joiner.query(&FeedQuery::new().with_limit(2));
+        assert_eq!(first.videos().len(), 2);
+        assert!(first.videos().iter().all(|v| v.title().ends_with('1')));
+        // The videos of the page are the ones in the video storage.
+        assert!(joiner.stored_videos().iter().any(|v| matches!(
+            (&first.videos()[0], v),
+            (AnyVideo::Test(a), AnyVideo::Test(b)) if Arc::ptr_eq(a, b)
+        )));
+
+        let second = joiner.query(&FeedQuery::new().with_limit(2).with_cursor(first.next()));
+        assert_eq!(second.videos().len(), 2);
+        assert!(second.videos().iter().all(|v| v.title().ends_with('2')));
+        let third = joiner.query(&FeedQuery::new().with_limit(2).with_cursor(second.next()));
+        assert!(third.videos().is_empty());
+        assert!(third.next().is_none());
+
+        let offset = joiner.query(&FeedQuery::new().with_offset(1).with_limit(2));
+        assert_eq!(
+            offset
+                .videos()
+                .iter()
+                .map(|v| v.uploaded())
+                .collect::<Vec<_>>(),
+            vec![first.videos()[1].uploaded(), second.videos()[0].uploaded()]
+        );
+
+        let unlimited = joiner.query(&FeedQuery::new().with_offset(1).with_limit(usize::MAX));
+        assert_eq!(unlimited.videos().len(), 3);
+
+        let since = chrono::NaiveDate::from_ymd_opt(2021, 6, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let recent = joiner.query(&FeedQuery::new().with_range(Some(since), None));
+        assert_eq!(recent.videos().len(), 2);
+        assert!(recent.next().is_none());
+        let older = joiner.query(&FeedQuery::new().with_range(None, Some(since)));
+        assert_eq!(older.videos().len(), 2);
+
+        let subscription =
+            joiner.query(&FeedQuery::new().with_subscriptions(vec![channel1.clone()]));
+        assert_eq!(subscription.videos().len(), 2);
+        assert!(subscription
+            .videos()
+            .iter()
+            .all(|v| v.subscription() == channel1));
+        let muted = joiner.query(&FeedQuery::new().with_subscriptions(vec![channel3]));
+        assert!(muted.videos().is_empty());
+
+        let platforms = joiner.query(&FeedQuery::new().with_platforms(vec![]));
+        assert!(platforms.videos().is_empty());
+        let platforms = joiner.query(&FeedQuery::new().with_platforms(vec![Platform::Test]));
+        assert_eq!(platforms.videos().len(), 4);
+
+        joiner.filters().lock().unwrap().add(AnyVideoFilter::new(
+            None,
+            Some(regex::Regex::new("1$").unwrap()),
+            None,
+        ));
+        let filtered = joiner.query(&FeedQuery::new().with_limit(4));
+        assert_eq!(filtered.videos().len(), 2);
+        assert!(filtered.videos().iter().all(|v| v.title().ends_with('2')));
+    }
+
+    #[test]
+    fn joiner_network_settings() {
+        let joiner = Joiner::new();
+        let tor = NetworkSettings::new()
+            .with_proxy(Some(tf_core::Proxy::tor()))
+            .with_user_agent(Some("Tubefeeder"));
+        assert_eq!(
+            joiner.network_settings(&Platform::Test),
+            NetworkSettings::new()
+        );
+
+        joiner
+            .set_network_settings(&Platform::Test, tor.clone())
+            .unwrap();
+        assert_eq!(joiner.network_settings(&Platform::Test), tor);
+
+        let invalid =
+            NetworkSettings::new().with_proxy(Some(tf_core::Proxy::Http("in valid".to_owned())));
+        assert!(joiner
+            .set_network_settings(&Platform::Test, invalid)
+            .is_err());
+        assert_eq!(joiner.network_settings(&Platform::Test), tor);
+
+        let transport: Arc<dyn Transport> = Arc::new(reqwest::Client::new());
+        joiner.set_platform_transport(&Platform::Test, transport.clone());
+        assert!(Arc::ptr_eq(&joiner.transport(&Platform::Test), &transport));
+        assert_eq!(
+            joiner.network_settings(&Platform::Test),
+            NetworkSettings::new()
+        );
+    }
+
+    #[test]
+    fn joiner_subscription_errors() {
+        let joiner = Joiner::new();
+        let subscription = TestSubscription::new("Channel1");
+        let errors = ErrorStore::new();
+
+        errors.add(tf_core::ParseError("Parse".to_owned()).into());
+        for _ in 0..2 {
+            errors.add_with_context(
+                tf_core::NetworkError("Url".to_owned()).into(),
+                tf_core::ErrorContext::new().with_subscription(&subscription),
+            );
+            errors.record_refresh(&subscription, true);
+        }
+
+        let per_subscription = joiner.errors_per_subscription(&errors);
+        assert_eq!(per_subscription.len(), 1);
+        assert_eq!(
+            per_subscription.get(&AnySubscription::from(subscription.clone())),
+            Some(&2)
+        );
+
+        assert_eq!(
+            joiner.failing_subscriptions(&errors, 2),
+            vec![AnySubscription::from(subscription)]
+        );
+        assert!(joiner.failing_subscriptions(&errors, 3).is_empty());
+    }
+
+    /// A [Transport] replaying a fixed response and recording the requested urls.
+    struct FixtureTransport {
+        response: tf_core::HttpResponse,
+        requests: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for FixtureTransport {
+        async fn get(
+            &self,
+            request: tf_core::HttpRequest,
+        ) -> Result<tf_core::HttpResponse, tf_core::NetworkError> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push(request.url().to_string());
+            Ok(self.response.clone())
+        }
+    }
+
+    #[cfg(feature = "peertube")]
+    #[tokio::test]
+    async fn joiner_transport() {
+        let feed = r#"<?xml version="1.0" encoding="utf-8"?>
+            <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+                <channel>
+                    <title>Channel</title>
+                    <item>
+                        <title>Fixture video</title>
+                        <link>https://example.com/w/abc</link>
+                        <pubDate>Tue, 01 Dec 2020 10:00:00 GMT</pubDate>
+                        <media:title>Fixture video</media:title>
+                    </item>
+                </channel>
+            </rss>"#;
+        let transport = Arc::new(FixtureTransport {
+            response: tf_core::HttpResponse::new(200, feed),
+            requests: Mutex::new(vec![]),
+        });
+
+        let joiner = Joiner::new();
+        joiner.set_transport(transport.clone());
+        joiner
+            .subscription_list()
+            .add(tf_pt::PTSubscription::new("https://example.com", "channel").into());
+
+        let errors = ErrorStore::new();
+        let generated = joiner.generate(&errors).await.collect::<Vec<_>>();
+        assert!(errors.iter_with_context().next().is_none());
+        assert_eq!(generated.len(), 1);
+        assert_eq!(generated[0].title(), "Fixture video");
+        assert_eq!(
+            transport.requests.lock().unwrap().clone(),
+            vec!["https://example.com/feeds/videos.xml?videoChannelName=channel".to_string()]
+        );
+
+        let failing = Arc::new(FixtureTransport {
+            response: tf_core::HttpResponse::new(503, ""),
+            requests: Mutex::new(vec![]),
+        });
+        joiner.set_transport(failing);
+        *joiner.fetch_policy().lock().unwrap() = FetchPolicy::new().with_retries(0);
+        let errors = ErrorStore::new();
+        assert_eq!(joiner.generate(&errors).await.count(), 0);
+        let (_, context) = errors.iter_with_context().next().unwrap();
+        assert_eq!(context.status(), Some(503));
+    }
+
+    #[tokio::test]
+    async fn joiner_thumbnail_cache() {
+        let directory =
+            std::env::temp_dir().join(format!("tf_join_thumbnails_{}", std::process::id()));
+        let mut body = std::io::Cursor::new(vec![]);
+        image::DynamicImage::new_rgb8(1280, 720)
+            .write_to(&mut body, image::ImageFormat::Png)
+            .unwrap();
+        let transport = Arc::new(FixtureTransport {
+            response: tf_core::HttpResponse::new(200, body.into_inner()),
+            requests: Mutex::new(vec![]),
+        });
+
+        let joiner = Joiner::new();
+        joiner.set_platform_transport(&Platform::Test, transport.clone());
+        joiner.set_thumbnail_cache(&directory, Some(chrono::Duration::days(7)));
+        joiner
+            .subscription_list()
+            .add(TestSubscription::new("Channel1").into());
+        let generated = joiner
+            .generate(&ErrorStore::new())
+            .await
+            .collect::<Vec<_>>();
+
+        for video in &generated {
+            let thumbnail = joiner.thumbnail(video, ThumbnailSize::List).await;
+            assert_eq!((thumbnail.width(), thumbnail.height()), (320, 180));
+        }
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+
+        let joiner_restarted = Joiner::new();
+        joiner_restarted.set_platform_transport(&Platform::Test, transport.clone());
+        joiner_restarted.set_thumbnail_cache(&directory, Some(chrono::Duration::days(7)));
+        let thumbnail = joiner_restarted
+            .thumbnail(&generated[0], ThumbnailSize::Detail)
+            .await;
+        assert_eq!((thumbnail.width(), thumbnail.height()), (640, 360));
+        assert_eq!(transport.requests.lock().unwrap().len(), 1);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
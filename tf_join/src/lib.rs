@@ -22,10 +22,20 @@
 //! ### Capabilities
 //!
 //! - Join multiple platforms together using [Joiner].
-//! - Filter out videos using [AnyVideoFilter].
+//! - Filter out videos using [AnyVideoFilter], optionally using a [FilterExpression].
 //! - Generalization of [Video][tf_core::Video] and [Subscription][tf_core::Subscription] using
-//! [AnyVideo] and [AnySubscription].
+//!   [AnyVideo] and [AnySubscription].
 //! - Generalization of [SubscriptionList][tf_core::SubscriptionList] using [AnySubscriptionList].
+//! - Import and export of [AnySubscription]s using OPML, see [AnySubscriptionList::import_opml].
+//! - Import of [AnySubscription]s from NewPipe and FreeTube (with the `serde` feature), see
+//!   `ImportedSubscriptions`.
+//! - Tracking of unseen [AnyVideo]s, see [Joiner::set_seen] and [UnreadEvent].
+//! - Notifications about new [AnyVideo]s, see [FeedEvent].
+//! - Fetching thumbnails in the background using [ThumbnailPrefetcher].
+//! - Refreshing every [AnySubscription] on its own interval using [RefreshScheduler].
+//! - Grouping [AnySubscription]s and generating the feed of groups, see [Joiner::generate_groups].
+//! - Local settings of [AnySubscription]s like muting or an alias, see [SubscriptionSettings].
+//! - Querying pages of the feed, see [Joiner::query] and [FeedQuery].
 //!
 //!
 //! ### Features
@@ -36,17 +46,44 @@
 //! - `youtube`
 //! - `peertube`
 //! - `lbry`
+//!
+//! The `serde` feature, which is not activated by default, enables serialization of
+//! [AnySubscription], [AnyVideo] and [AnyVideoFilter] together with [Versioned] documents,
+//! including the settings and groups of [AnySubscription]s using `SubscriptionEntry`, as well
+//! as the import of [AnySubscription]s from other applications.
 
+mod expression;
 mod filter;
+#[cfg(feature = "serde")]
+mod import;
 mod joiner;
+mod opml;
+mod prefetch;
+mod query;
+mod scheduler;
+#[cfg(feature = "serde")]
+mod serialization;
+mod settings;
 mod subscription;
 mod subscription_list;
 mod video;
 
+pub use crate::expression::{Comparison, Condition, FilterExpression, FilterParseError, TextMatch};
 pub use crate::filter::AnyVideoFilter;
-pub use crate::joiner::Joiner;
+#[cfg(feature = "serde")]
+pub use crate::import::{ImportError, ImportedSubscriptions, SubscriptionDiff};
+pub use crate::joiner::{FeedEvent, Joiner, UnreadEvent};
+pub use crate::opml::{OpmlError, OpmlImport, OpmlOutline};
+pub use crate::prefetch::{ThumbnailEvent, ThumbnailPrefetcher, DEFAULT_PREFETCH_CONCURRENCY};
+pub use crate::query::{FeedPage, FeedQuery};
+pub use crate::scheduler::{RefreshScheduler, ScheduleEvent};
+#[cfg(feature = "serde")]
+pub use crate::serialization::{SerializationError, Versioned, FORMAT_VERSION};
+pub use crate::settings::SubscriptionSettings;
 pub use crate::subscription::AnySubscription;
 pub use crate::subscription::Platform;
 pub use crate::subscription_list::AnySubscriptionList;
-pub use crate::subscription_list::SubscriptionEvent;
+#[cfg(feature = "serde")]
+pub use crate::subscription_list::SubscriptionEntry;
+pub use crate::subscription_list::{GroupEvent, SubscriptionEvent};
 pub use crate::video::AnyVideo;
@@ -0,0 +1,266 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Import and export of [AnySubscription]s using [OPML](http://opml.org/spec2.opml).
+//!
+//! Every subscription is represented by a `outline` with the `xmlUrl` set to the rss-feed of the
+//! subscription, see [AnySubscription::feed_url].
+
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use tf_core::Subscription;
+
+use crate::{AnySubscription, AnySubscriptionList};
+
+/// The title of exported OPML-documents.
+const OPML_TITLE: &str = "Tubefeeder Subscriptions";
+
+/// A error parsing a OPML-document.
+#[derive(Debug)]
+pub struct OpmlError(pub String);
+
+impl std::fmt::Display for OpmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error parsing OPML: {}", self.0)
+    }
+}
+
+impl std::error::Error for OpmlError {}
+
+impl From<quick_xml::Error> for OpmlError {
+    fn from(e: quick_xml::Error) -> Self {
+        OpmlError(e.to_string())
+    }
+}
+
+/// A `outline` of a OPML-document whose `xmlUrl` could not be mapped to a [AnySubscription].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpmlOutline {
+    /// The `text` or `title` of the outline.
+    pub name: Option<String>,
+    /// The `xmlUrl` of the outline.
+    pub url: String,
+}
+
+/// The result of parsing a OPML-document.
+#[derive(Debug, Clone, Default)]
+pub struct OpmlImport {
+    /// The recognized [AnySubscription]s.
+    subscriptions: Vec<AnySubscription>,
+    /// The outlines that could not be recognized.
+    unrecognized: Vec<OpmlOutline>,
+}
+
+impl OpmlImport {
+    /// Parse the given OPML-document.
+    ///
+    /// All `outline`s with a `xmlUrl`, no matter how deeply nested, are considered.
+    /// The `xmlUrl` must be the exact rss-feed url of a [AnySubscription] produced by its
+    /// [Platform][crate::Platform] to be recognized.
+    pub fn parse(opml: &str) -> Result<Self, OpmlError> {
+        let mut reader = Reader::from_str(opml);
+        reader.trim_text(true);
+        let mut buf = vec![];
+        let mut result = OpmlImport::default();
+
+        loop {
+            match reader.read_event(&mut buf)? {
+                Event::Start(e) | Event::Empty(e) if e.name() == b"outline" => {
+                    let mut url = None;
+                    let mut text = None;
+                    let mut title = None;
+                    for attribute in e.attributes() {
+                        let attribute = attribute?;
+                        let value = attribute.unescape_and_decode_value(&reader)?;
+                        match attribute.key {
+                            b"xmlUrl" => url = Some(value),
+                            b"text" => text = Some(value),
+                            b"title" => title = Some(value),
+                            _ => {}
+                        }
+                    }
+
+                    if let Some(url) = url {
+                        let name = text.or(title).filter(|n| !n.is_empty());
+                        match AnySubscription::from_feed_url(&url) {
+                            Some(s) => result
+                                .subscriptions
+                                .push(name.map(|n| s.with_name(n)).unwrap_or(s)),
+                            None => result.unrecognized.push(OpmlOutline { name, url }),
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(result)
+    }
+
+    /// Get the recognized [AnySubscription]s.
+    pub fn subscriptions(&self) -> &[AnySubscription] {
+        &self.subscriptions
+    }
+
+    /// Get the outlines that could not be recognized.
+    pub fn unrecognized(&self) -> &[OpmlOutline] {
+        &self.unrecognized
+    }
+}
+
+impl AnySubscriptionList {
+    /// Import the [AnySubscription]s of the given OPML-document, see [OpmlImport::parse].
+    ///
+    /// [AnySubscription]s already contained in the [AnySubscriptionList] or contained multiple
+    /// times in the OPML-document will only be added once.
+    pub fn import_opml(&self, opml: &str) -> Result<OpmlImport, OpmlError> {
+        let import = OpmlImport::parse(opml)?;
+        let mut existing = self.iter().collect::<Vec<_>>();
+        for subscription in import.subscriptions() {
+            if !existing.contains(subscription) {
+                self.add(subscription.clone());
+                existing.push(subscription.clone());
+            }
+        }
+        Ok(import)
+    }
+
+    /// Export all [AnySubscription]s with a rss-feed into a OPML-document.
+    pub fn export_opml(&self) -> String {
+        let mut writer = Writer::new_with_indent(vec![], b' ', 2);
+        write_opml(&mut writer, self.iter()).expect("Writing OPML into memory failed");
+        String::from_utf8(writer.into_inner()).expect("OPML is not valid UTF-8")
+    }
+}
+
+/// Write the given [AnySubscription]s as a OPML-document.
+fn write_opml<I: Iterator<Item = AnySubscription>>(
+    writer: &mut Writer<Vec<u8>>,
+    subscriptions: I,
+) -> quick_xml::Result<()> {
+    writer.write_event(Event::Decl(BytesDecl::new(b"1.0", Some(b"UTF-8"), None)))?;
+    writer.write_event(Event::Start(
+        BytesStart::borrowed_name(b"opml").with_attributes(vec![("version", "2.0")]),
+    ))?;
+
+    writer.write_event(Event::Start(BytesStart::borrowed_name(b"head")))?;
+    writer.write_event(Event::Start(BytesStart::borrowed_name(b"title")))?;
+    writer.write_event(Event::Text(BytesText::from_plain_str(OPML_TITLE)))?;
+    writer.write_event(Event::End(BytesEnd::borrowed(b"title")))?;
+    writer.write_event(Event::End(BytesEnd::borrowed(b"head")))?;
+
+    writer.write_event(Event::Start(BytesStart::borrowed_name(b"body")))?;
+    for subscription in subscriptions {
+        if let Some(url) = subscription.feed_url() {
+            let name = subscription.name().unwrap_or_default();
+            writer.write_event(Event::Empty(
+                BytesStart::borrowed_name(b"outline").with_attributes(vec![
+                    ("text", name.as_str()),
+                    ("title", name.as_str()),
+                    ("type", "rss"),
+                    ("xmlUrl", url.as_str()),
+                ]),
+            ))?;
+        }
+    }
+    writer.write_event(Event::End(BytesEnd::borrowed(b"body")))?;
+
+    writer.write_event(Event::End(BytesEnd::borrowed(b"opml")))?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "youtube", feature = "peertube", feature = "lbry"))]
+mod test {
+    use super::*;
+
+    const OPML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="1.1">
+  <head><title>Subscriptions</title></head>
+  <body>
+    <outline text="YouTube">
+      <outline text="Channel &amp; Co" type="rss" xmlUrl="https://www.youtube.com/feeds/videos.xml?channel_id=UCld68syR8Wi-GY_n4CaoJGA"/>
+    </outline>
+    <outline title="PeerTube" type="rss" xmlUrl="https://framatube.org/feeds/videos.xml?videoChannelName=framasoft_channel@framatube.org"/>
+    <outline text="Lbry" type="rss" xmlUrl="https://odysee.com/$/rss/@DistroTube:2"/>
+    <outline text="Blog" type="rss" xmlUrl="https://example.com/feed.xml"/>
+  </body>
+</opml>"#;
+
+    #[test]
+    fn opml_parse() {
+        let import = OpmlImport::parse(OPML).unwrap();
+
+        assert_eq!(
+            import.subscriptions(),
+            &[
+                tf_yt::YTSubscription::new_with_name("UCld68syR8Wi-GY_n4CaoJGA", "Channel & Co")
+                    .into(),
+                tf_pt::PTSubscription::new_with_name(
+                    "https://framatube.org",
+                    "framasoft_channel@framatube.org",
+                    "PeerTube"
+                )
+                .into(),
+                tf_lbry::LbrySubscription::new_with_name("@DistroTube:2", "Lbry").into(),
+            ]
+        );
+        assert_eq!(
+            import.subscriptions()[0].name(),
+            Some("Channel & Co".to_string())
+        );
+        assert_eq!(
+            import.unrecognized(),
+            &[OpmlOutline {
+                name: Some("Blog".to_string()),
+                url: "https://example.com/feed.xml".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn opml_parse_invalid() {
+        assert!(OpmlImport::parse("<opml><body></opml>").is_err());
+    }
+
+    #[test]
+    fn opml_import_export() {
+        let list = AnySubscriptionList::default();
+        list.add(tf_yt::YTSubscription::new("UCld68syR8Wi-GY_n4CaoJGA").into());
+
+        let import = list.import_opml(OPML).unwrap();
+        assert_eq!(import.subscriptions().len(), 3);
+        assert_eq!(list.iter().count(), 3);
+
+        let exported = list.export_opml();
+        let reimported = OpmlImport::parse(&exported).unwrap();
+
+        assert!(reimported.unrecognized().is_empty());
+        assert_eq!(reimported.subscriptions(), list.iter().collect::<Vec<_>>());
+
+        // A outline contained twice in the document is only added once.
+        let list = AnySubscriptionList::default();
+        let outline =
+            r#"<outline text="Lbry" type="rss" xmlUrl="https://odysee.com/$/rss/@DistroTube:2"/>"#;
+        list.import_opml(&OPML.replace(outline, &outline.repeat(2)))
+            .unwrap();
+        assert_eq!(list.iter().count(), 3);
+    }
+}
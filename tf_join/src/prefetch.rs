@@ -0,0 +1,361 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use futures::{
+    future::{AbortHandle, Abortable},
+    stream::FuturesUnordered,
+    StreamExt,
+};
+use image::DynamicImage;
+use tf_core::{ThumbnailSize, Video, VideoId};
+use tf_observer::{Observable, Observer, ObserverList};
+
+use crate::{AnyVideo, FeedPage, Joiner};
+
+/// The default number of thumbnails fetched at the same time by the [ThumbnailPrefetcher].
+pub const DEFAULT_PREFETCH_CONCURRENCY: usize = 4;
+
+/// The event sent by [ThumbnailPrefetcher].
+#[derive(Clone)]
+pub enum ThumbnailEvent {
+    /// The thumbnail of the [AnyVideo] in the [ThumbnailSize] was fetched.
+    Ready(AnyVideo, ThumbnailSize, DynamicImage),
+}
+
+/// The state of the [ThumbnailPrefetcher] shared between its clones.
+#[derive(Default)]
+struct PrefetchState {
+    /// The [AnyVideo]s waiting to be fetched, the next one in front.
+    queue: VecDeque<AnyVideo>,
+
+    /// The [VideoId]s of the [AnyVideo]s currently visible, see [ThumbnailPrefetcher::set_visible].
+    visible: HashSet<VideoId>,
+
+    /// The [VideoId]s of the [AnyVideo]s currently fetched together with the handle to cancel them.
+    fetching: HashMap<VideoId, AbortHandle>,
+
+    /// The [VideoId]s of the [AnyVideo]s whose thumbnails were already fetched.
+    fetched: HashSet<VideoId>,
+}
+
+/// Fetch thumbnails of [AnyVideo]s in the background using [Joiner::thumbnail].
+///
+/// The thumbnails are fetched with bounded concurrency (see [ThumbnailPrefetcher::with_concurrency])
+/// while [ThumbnailPrefetcher::run] is awaited. Visible [AnyVideo]s are fetched first and
+/// fetching them is cancelled as soon as they are no longer visible, see [ThumbnailPrefetcher::set_visible].
+///
+/// This implements [Observable] and emits [ThumbnailEvent] to the [Observer]s once a thumbnail
+/// is ready. Setting a [ThumbnailCache][tf_core::ThumbnailCache] using
+/// [Joiner::set_thumbnail_cache] makes the fetched thumbnails available after a restart.
+#[derive(Clone)]
+pub struct ThumbnailPrefetcher {
+    observers: ObserverList<ThumbnailEvent>,
+
+    /// The [Joiner] used to fetch the thumbnails.
+    joiner: Joiner,
+
+    /// The [ThumbnailSize] of the fetched thumbnails.
+    size: ThumbnailSize,
+
+    /// The maximal number of thumbnails fetched at the same time.
+    concurrency: usize,
+
+    state: Arc<Mutex<PrefetchState>>,
+}
+
+impl ThumbnailPrefetcher {
+    /// Create a new [ThumbnailPrefetcher] fetching [ThumbnailSize::List] thumbnails using the
+    /// given [Joiner], fetching [DEFAULT_PREFETCH_CONCURRENCY] thumbnails at the same time.
+    pub fn new(joiner: Joiner) -> Self {
+        ThumbnailPrefetcher {
+            observers: ObserverList::default(),
+            joiner,
+            size: ThumbnailSize::List,
+            concurrency: DEFAULT_PREFETCH_CONCURRENCY,
+            state: Arc::new(Mutex::new(PrefetchState::default())),
+        }
+    }
+
+    /// Set the [ThumbnailSize] of the fetched thumbnails.
+    pub fn with_size(mut self, size: ThumbnailSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the maximal number of thumbnails fetched at the same time, at least one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Get the [ThumbnailSize] of the fetched thumbnails.
+    pub fn size(&self) -> ThumbnailSize {
+        self.size
+    }
+
+    /// Get the maximal number of thumbnails fetched at the same time.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Queue the thumbnails of the newest `n` [AnyVideo]s, e.g. of the merged feed after a refresh.
+    ///
+    /// The [AnyVideo]s are fetched newest first after all already queued [AnyVideo]s.
+    /// [AnyVideo]s which are already queued, fetched or being fetched are skipped.
+    pub fn prefetch_newest(&self, videos: &[AnyVideo], n: usize) {
+        let mut videos = videos.to_vec();
+        videos.sort_by_cached_key(|v| std::cmp::Reverse(v.uploaded()));
+
+        let mut state = self.state.lock().unwrap();
+        for video in videos.into_iter().take(n) {
+            if !state.is_known(&video) {
+                state.queue.push_back(video);
+            }
+        }
+    }
+
+    /// Queue the thumbnails of all [AnyVideo]s of a [FeedPage] returned by
+    /// [Joiner::query], see [ThumbnailPrefetcher::prefetch_newest].
+    pub fn prefetch_page(&self, page: &FeedPage) {
+        self.prefetch_newest(page.videos(), page.videos().len())
+    }
+
+    /// Set the currently visible [AnyVideo]s, e.g. when scrolling the feed.
+    ///
+    /// The visible [AnyVideo]s are queued in front of all other [AnyVideo]s.
+    /// [AnyVideo]s which were visible before but are no longer are removed from the queue,
+    /// and fetching their thumbnails is cancelled.
+    pub fn set_visible(&self, videos: &[AnyVideo]) {
+        let mut state = self.state.lock().unwrap();
+        let visible: HashSet<VideoId> = videos.iter().map(|v| v.id()).collect();
+
+        let hidden: Vec<VideoId> = state.visible.difference(&visible).cloned().collect();
+        for id in hidden {
+            state.cancel(&id);
+        }
+
+        state.queue.retain(|v| !visible.contains(&v.id()));
+        for video in videos.iter().rev() {
+            let id = video.id();
+            if !state.fetching.contains_key(&id) && !state.fetched.contains(&id) {
+                state.queue.push_front(video.clone());
+            }
+        }
+        state.visible = visible;
+    }
+
+    /// Cancel fetching the thumbnail of the [AnyVideo], removing it from the queue.
+    pub fn cancel(&self, video: &AnyVideo) {
+        self.state.lock().unwrap().cancel(&video.id());
+    }
+
+    /// Get the number of [AnyVideo]s waiting for their thumbnails to be fetched.
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    /// Fetch the thumbnails of the queued [AnyVideo]s until the queue is empty.
+    ///
+    /// At most [ThumbnailPrefetcher::concurrency] thumbnails are fetched at the same time.
+    /// [AnyVideo]s queued while running are fetched as well. Every fetched thumbnail is sent to the
+    /// [Observer]s using [ThumbnailEvent::Ready], cancelled ones are not.
+    pub async fn run(&self) {
+        let mut fetching = FuturesUnordered::new();
+        loop {
+            while fetching.len() < self.concurrency {
+                let next = {
+                    let mut state = self.state.lock().unwrap();
+                    state.queue.pop_front().map(|video| {
+                        let (handle, registration) = AbortHandle::new_pair();
+                        state.fetching.insert(video.id(), handle);
+                        (video, registration)
+                    })
+                };
+                let (video, registration) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                let joiner = self.joiner.clone();
+                let size = self.size;
+                fetching.push(async move {
+                    let thumbnail =
+                        Abortable::new(joiner.thumbnail(&video, size), registration).await;
+                    (video, thumbnail)
+                });
+            }
+
+            let (video, thumbnail) = match fetching.next().await {
+                Some(result) => result,
+                None => return,
+            };
+            // Cancelled fetches were already removed by the cancellation.
+            if let Ok(thumbnail) = thumbnail {
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.fetching.remove(&video.id());
+                    state.fetched.insert(video.id());
+                }
+                self.observers
+                    .notify(ThumbnailEvent::Ready(video, self.size, thumbnail));
+            }
+        }
+    }
+}
+
+impl PrefetchState {
+    /// Whether the [AnyVideo] is queued, fetched or being fetched.
+    fn is_known(&self, video: &AnyVideo) -> bool {
+        let id = video.id();
+        self.queue.iter().any(|v| v.id() == id)
+            || self.fetching.contains_key(&id)
+            || self.fetched.contains(&id)
+    }
+
+    /// Remove the [AnyVideo] with the [VideoId] from the queue and cancel fetching it.
+    fn cancel(&mut self, id: &VideoId) {
+        self.queue.retain(|v| &v.id() != id);
+        if let Some(handle) = self.fetching.remove(id) {
+            handle.abort();
+        }
+    }
+}
+
+impl Observable<ThumbnailEvent> for ThumbnailPrefetcher {
+    fn attach(
+        &mut self,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<ThumbnailEvent> + Send>>>,
+    ) {
+        self.observers.attach(observer)
+    }
+
+    fn detach(
+        &mut self,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<ThumbnailEvent> + Send>>>,
+    ) {
+        self.observers.detach(observer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use tf_core::{ErrorStore, Generator, HttpRequest, HttpResponse, NetworkError, Transport};
+    use tf_test::TestSubscription;
+
+    use crate::Platform;
+
+    /// A [Transport] answering with a small image, or never if `hang` is set.
+    struct ImageTransport {
+        hang: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for ImageTransport {
+        async fn get(&self, _request: HttpRequest) -> Result<HttpResponse, NetworkError> {
+            if self.hang {
+                futures::future::pending::<()>().await;
+            }
+            let mut body = std::io::Cursor::new(vec![]);
+            DynamicImage::new_rgb8(16, 9)
+                .write_to(&mut body, image::ImageFormat::Png)
+                .unwrap();
+            Ok(HttpResponse::new(200, body.into_inner()))
+        }
+    }
+
+    /// The [ThumbnailPrefetcher], the generated [AnyVideo]s, the titles of the ready thumbnails
+    /// and the [Observer] recording them.
+    type Setup = (
+        ThumbnailPrefetcher,
+        Vec<AnyVideo>,
+        Arc<Mutex<Vec<String>>>,
+        Arc<Mutex<Box<dyn Observer<ThumbnailEvent> + Send>>>,
+    );
+
+    struct ReadyRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl Observer<ThumbnailEvent> for ReadyRecorder {
+        fn notify(&mut self, message: ThumbnailEvent) {
+            let ThumbnailEvent::Ready(video, _size, _thumbnail) = message;
+            self.0.lock().unwrap().push(video.title());
+        }
+    }
+
+    async fn setup(hang: bool) -> Setup {
+        let joiner = Joiner::new();
+        joiner.set_platform_transport(&Platform::Test, Arc::new(ImageTransport { hang }));
+        joiner
+            .subscription_list()
+            .add(TestSubscription::new("Channel1").into());
+        let videos = joiner
+            .generate(&ErrorStore::new())
+            .await
+            .collect::<Vec<_>>();
+
+        let ready = Arc::new(Mutex::new(vec![]));
+        let observer: Arc<Mutex<Box<dyn Observer<ThumbnailEvent> + Send>>> =
+            Arc::new(Mutex::new(Box::new(ReadyRecorder(ready.clone()))));
+        let mut prefetcher = ThumbnailPrefetcher::new(joiner).with_concurrency(1);
+        prefetcher.attach(Arc::downgrade(&observer));
+        (prefetcher, videos, ready, observer)
+    }
+
+    #[tokio::test]
+    async fn prefetch_visible_first() {
+        let (prefetcher, videos, ready, _observer) = setup(false).await;
+
+        prefetcher.prefetch_newest(&videos, 1);
+        prefetcher.prefetch_newest(&videos, 2);
+        assert_eq!(prefetcher.pending(), 2);
+        prefetcher.set_visible(&videos[1..]);
+        assert_eq!(prefetcher.pending(), 2);
+
+        prefetcher.run().await;
+        assert_eq!(prefetcher.pending(), 0);
+        assert_eq!(
+            *ready.lock().unwrap(),
+            vec![videos[1].title(), videos[0].title()]
+        );
+
+        prefetcher.prefetch_newest(&videos, 2);
+        assert_eq!(prefetcher.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn prefetch_cancel_hidden() {
+        let (prefetcher, videos, ready, _observer) = setup(true).await;
+
+        prefetcher.set_visible(&videos[..1]);
+        prefetcher.set_visible(&videos[1..]);
+        assert_eq!(prefetcher.pending(), 1);
+
+        let scroll = async {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            prefetcher.set_visible(&[]);
+        };
+        futures::join!(prefetcher.run(), scroll);
+        assert_eq!(prefetcher.pending(), 0);
+        assert!(ready.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::NaiveDateTime;
+use tf_core::{Video, Window, WindowCursor};
+
+use crate::{AnySubscription, AnyVideo, Platform};
+
+/// A query for a window of the feed of a [Joiner][crate::Joiner], see [Joiner::query][crate::Joiner::query].
+///
+/// The feed is sorted newest first. The query selects the [AnyVideo]s uploaded in the given date range
+/// and after the given [WindowCursor], optionally only of some [Platform]s and [AnySubscription]s.
+/// Of these, the first `offset` [AnyVideo]s are skipped and at most `limit` are returned.
+#[derive(Clone, Debug, Default)]
+pub struct FeedQuery {
+    /// The window into the feed, without the offset.
+    window: Window,
+    /// The number of [AnyVideo]s to skip.
+    offset: usize,
+    /// The [Platform]s to query, all if [None].
+    platforms: Option<Vec<Platform>>,
+    /// The [AnySubscription]s to query, all if [None].
+    subscriptions: Option<Vec<AnySubscription>>,
+}
+
+impl FeedQuery {
+    /// Create a new [FeedQuery] for the whole feed.
+    pub fn new() -> Self {
+        FeedQuery::default()
+    }
+
+    /// Return at most `limit` [AnyVideo]s.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.window = self.window.with_limit(Some(limit));
+        self
+    }
+
+    /// Skip the first `offset` [AnyVideo]s.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Only return [AnyVideo]s uploaded at or after `since` and before `until`.
+    pub fn with_range(
+        mut self,
+        since: Option<NaiveDateTime>,
+        until: Option<NaiveDateTime>,
+    ) -> Self {
+        self.window = self.window.with_since(since).with_until(until);
+        self
+    }
+
+    /// Only return [AnyVideo]s after the [WindowCursor], e.g. [FeedPage::next] of the previous page.
+    ///
+    /// The offset is applied after the cursor, it should therefore usually be `0`.
+    pub fn with_cursor(mut self, cursor: Option<WindowCursor>) -> Self {
+        self.window = self.window.with_after(cursor);
+        self
+    }
+
+    /// Only return [AnyVideo]s of the given [Platform]s.
+    pub fn with_platforms(mut self, platforms: Vec<Platform>) -> Self {
+        self.platforms = Some(platforms);
+        self
+    }
+
+    /// Only return [AnyVideo]s of the given [AnySubscription]s.
+    pub fn with_subscriptions(mut self, subscriptions: Vec<AnySubscription>) -> Self {
+        self.subscriptions = Some(subscriptions);
+        self
+    }
+
+    /// The maximum number of [AnyVideo]s to return.
+    pub fn limit(&self) -> Option<usize> {
+        self.window.limit()
+    }
+
+    /// The number of [AnyVideo]s to skip.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The oldest upload time of the [AnyVideo]s to return.
+    pub fn since(&self) -> Option<NaiveDateTime> {
+        self.window.since()
+    }
+
+    /// The upload time all [AnyVideo]s to return are older than.
+    pub fn until(&self) -> Option<NaiveDateTime> {
+        self.window.until()
+    }
+
+    /// The [WindowCursor] all [AnyVideo]s to return come after.
+    pub fn cursor(&self) -> Option<WindowCursor> {
+        self.window.after()
+    }
+
+    /// Whether [AnyVideo]s of the [Platform] are queried.
+    // `Option::is_none_or` would raise the minimum supported Rust version to 1.82.
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn includes_platform(&self, platform: &Platform) -> bool {
+        self.platforms
+            .as_ref()
+            .map_or(true, |platforms| platforms.contains(platform))
+    }
+
+    /// Whether [AnyVideo]s of the [AnySubscription] are queried.
+    // `Option::is_none_or` would raise the minimum supported Rust version to 1.82.
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn includes_subscription(&self, subscription: &AnySubscription) -> bool {
+        self.subscriptions
+            .as_ref()
+            .map_or(true, |subscriptions| subscriptions.contains(subscription))
+    }
+
+    /// Whether the [AnyVideo] matches the [Platform]s and [AnySubscription]s of the query.
+    ///
+    /// This does not check the date range or the cursor, see [Window::contains].
+    pub fn matches(&self, video: &AnyVideo) -> bool {
+        self.includes_platform(&video.platform())
+            && self.includes_subscription(&video.subscription())
+    }
+
+    /// The [Window] each pipeline has to select, i.e. including the skipped [AnyVideo]s.
+    pub(crate) fn pipeline_window(&self) -> Window {
+        let limit = self.window.limit().map(|l| l.saturating_add(self.offset));
+        self.window.clone().with_limit(limit)
+    }
+}
+
+/// A page of the feed returned by [Joiner::query][crate::Joiner::query].
+#[derive(Clone)]
+pub struct FeedPage {
+    /// The [AnyVideo]s of the page, newest first.
+    videos: Vec<AnyVideo>,
+    /// The [WindowCursor] of the next page.
+    next: Option<WindowCursor>,
+}
+
+impl FeedPage {
+    /// Create a new [FeedPage] from the [AnyVideo]s of the page, sorted newest first.
+    ///
+    /// If the page is full, i.e. it contains `limit` [AnyVideo]s, there may be a next page.
+    pub(crate) fn new(videos: Vec<AnyVideo>, limit: Option<usize>) -> Self {
+        let next = match (limit, videos.last()) {
+            (Some(limit), Some(last)) if videos.len() == limit => Some(WindowCursor::of(last)),
+            _ => None,
+        };
+        FeedPage { videos, next }
+    }
+
+    /// The [AnyVideo]s of the page, newest first.
+    pub fn videos(&self) -> &[AnyVideo] {
+        &self.videos
+    }
+
+    /// Take the [AnyVideo]s of the page, newest first.
+    pub fn into_videos(self) -> Vec<AnyVideo> {
+        self.videos
+    }
+
+    /// The [WindowCursor] to query the next page using [FeedQuery::with_cursor].
+    ///
+    /// This is [None] if the page is not full, i.e. there is no next page.
+    pub fn next(&self) -> Option<WindowCursor> {
+        self.next.clone()
+    }
+}
@@ -0,0 +1,314 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tf_core::{ErrorStore, SchedulePolicy, Scheduler, Video};
+use tf_observer::{Observable, Observer, ObserverList};
+use tokio::sync::Notify;
+
+use crate::{AnySubscription, AnyVideo, Joiner};
+
+/// The event sent by [RefreshScheduler].
+#[derive(Clone, Debug)]
+pub enum ScheduleEvent {
+    /// Refreshing the due [AnySubscription]s started.
+    Started(Vec<AnySubscription>),
+    /// The [AnySubscription] was refreshed, finding the given number of new [AnyVideo]s.
+    /// The next refresh is due at the given time.
+    Refreshed(AnySubscription, usize, chrono::NaiveDateTime),
+    /// Refreshing the [AnySubscription] failed, the [Error][tf_core::Error]s are put into the
+    /// [ErrorStore]. The next refresh is due at the given time.
+    Failed(AnySubscription, chrono::NaiveDateTime),
+    /// The [RefreshScheduler] was paused, see [RefreshScheduler::pause].
+    Paused,
+    /// The [RefreshScheduler] was resumed, see [RefreshScheduler::resume].
+    Resumed,
+}
+
+/// Refresh every [AnySubscription] of a [Joiner] on its own interval in the background.
+///
+/// The interval of an [AnySubscription] is derived from its upload history using the
+/// [SchedulePolicy], such that frequently uploading channels are refreshed often and dormant
+/// ones rarely, see [Scheduler]. Due [AnySubscription]s are refreshed incrementally by the
+/// pipelines of the [Joiner], see [Joiner::refresh_subscriptions].
+///
+/// This implements [Observable] and emits [ScheduleEvent] to the [Observer]s describing what
+/// was refreshed.
+#[derive(Clone)]
+pub struct RefreshScheduler {
+    observers: ObserverList<ScheduleEvent>,
+
+    /// The [Joiner] whose [AnySubscription]s are refreshed.
+    joiner: Joiner,
+
+    /// The [Scheduler] keeping track of which [AnySubscription] is due.
+    scheduler: Scheduler<AnySubscription>,
+
+    /// Wakes up [RefreshScheduler::run] when the schedule changed.
+    wakeup: Arc<Notify>,
+}
+
+impl RefreshScheduler {
+    /// Create a new [RefreshScheduler] refreshing the [AnySubscription]s of the given [Joiner]
+    /// using the default [SchedulePolicy].
+    pub fn new(joiner: Joiner) -> Self {
+        RefreshScheduler {
+            observers: ObserverList::default(),
+            joiner,
+            scheduler: Scheduler::new(),
+            wakeup: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Get the [SchedulePolicy] used to compute the intervals of the [AnySubscription]s.
+    ///
+    /// Modifying this [SchedulePolicy] will alter the intervals of the following refreshes.
+    pub fn policy(&self) -> Arc<Mutex<SchedulePolicy>> {
+        self.scheduler.policy()
+    }
+
+    /// Get the interval the [AnySubscription] is currently refreshed with, see [Scheduler::interval].
+    pub fn interval(&self, subscription: &AnySubscription) -> Option<Duration> {
+        self.scheduler.interval(subscription)
+    }
+
+    /// Get the time the next [AnySubscription] is due, see [Scheduler::next_due].
+    pub fn next_due(&self) -> Option<chrono::NaiveDateTime> {
+        self.scheduler
+            .next_due(&self.subscriptions(), chrono::Local::now().naive_local())
+    }
+
+    /// Refresh the [AnySubscription] as soon as possible, e.g. when the user requests it.
+    pub fn refresh_now(&self, subscription: &AnySubscription) {
+        self.scheduler.refresh_now(subscription);
+        self.wakeup.notify_one();
+    }
+
+    /// Refresh all [AnySubscription]s as soon as possible.
+    pub fn refresh_all_now(&self) {
+        self.scheduler.refresh_all_now();
+        self.wakeup.notify_one();
+    }
+
+    /// Pause refreshing until [resumed][RefreshScheduler::resume], notifying the [Observer]s
+    /// using [ScheduleEvent::Paused].
+    pub fn pause(&self) {
+        self.scheduler.pause();
+        self.observers.notify(ScheduleEvent::Paused);
+    }
+
+    /// Resume refreshing after being [paused][RefreshScheduler::pause], notifying the [Observer]s
+    /// using [ScheduleEvent::Resumed].
+    pub fn resume(&self) {
+        self.scheduler.resume();
+        self.observers.notify(ScheduleEvent::Resumed);
+        self.wakeup.notify_one();
+    }
+
+    /// Whether the [RefreshScheduler] is paused.
+    pub fn is_paused(&self) -> bool {
+        self.scheduler.is_paused()
+    }
+
+    /// Refresh all [AnySubscription]s that are due now.
+    ///
+    /// The [AnySubscription]s are refreshed using [Joiner::refresh_subscriptions], i.e. according
+    /// to the [FetchPolicy][tf_core::FetchPolicy] of the [Joiner] and using conditional requests
+    /// where supported. The [Observer]s of the [Joiner] are notified about the new [AnyVideo]s
+    /// using [FeedEvent::NewVideos][crate::FeedEvent::NewVideos].
+    /// Gives back the [AnyVideo]s not seen in a previous refresh, filtered and sorted newest first.
+    pub async fn refresh_due(&self, errors: &ErrorStore) -> Vec<AnyVideo> {
+        let due = self
+            .scheduler
+            .due(&self.subscriptions(), chrono::Local::now().naive_local());
+        if due.is_empty() {
+            return vec![];
+        }
+        self.observers.notify(ScheduleEvent::Started(due.clone()));
+
+        let results = self.joiner.refresh_subscriptions(errors, &due).await;
+
+        let mut new_videos = vec![];
+        for (subscription, (feed, mut new)) in due.into_iter().zip(results) {
+            let failed = subscription.consecutive_failures(errors) > 0;
+            let uploads = feed.iter().map(|v| v.uploaded()).collect::<Vec<_>>();
+            let count = new.len();
+            new_videos.append(&mut new);
+
+            let next =
+                self.scheduler
+                    .record(&subscription, &uploads, chrono::Local::now().naive_local());
+            if failed {
+                self.observers
+                    .notify(ScheduleEvent::Failed(subscription, next));
+            } else {
+                self.observers
+                    .notify(ScheduleEvent::Refreshed(subscription, count, next));
+            }
+        }
+
+        new_videos.retain(|v| !self.joiner.is_hidden(v));
+        new_videos.sort_by_cached_key(|v| std::cmp::Reverse(v.uploaded()));
+        new_videos
+    }
+
+    /// Refresh the due [AnySubscription]s forever, see [RefreshScheduler::refresh_due].
+    ///
+    /// In between, this waits until the next [AnySubscription] is due, at most the
+    /// [minimal interval][SchedulePolicy::min_interval] such that new [AnySubscription]s are picked
+    /// up. [RefreshScheduler::refresh_now] and [RefreshScheduler::resume] stop waiting immediately.
+    pub async fn run(&self, errors: &ErrorStore) {
+        loop {
+            self.refresh_due(errors).await;
+
+            let max_wait = self.policy().lock().unwrap().min_interval();
+            let now = chrono::Local::now().naive_local();
+            let wait = self
+                .scheduler
+                .next_due(&self.subscriptions(), now)
+                .map_or(max_wait, |next| {
+                    (next - now).to_std().unwrap_or_default().min(max_wait)
+                });
+            futures::future::select(
+                Box::pin(tokio::time::sleep(wait)),
+                Box::pin(self.wakeup.notified()),
+            )
+            .await;
+        }
+    }
+
+    /// Get the [AnySubscription]s of the [Joiner].
+    fn subscriptions(&self) -> Vec<AnySubscription> {
+        self.joiner.subscription_list().iter().collect()
+    }
+}
+
+impl Observable<ScheduleEvent> for RefreshScheduler {
+    fn attach(
+        &mut self,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<ScheduleEvent> + Send>>>,
+    ) {
+        self.observers.attach(observer)
+    }
+
+    fn detach(
+        &mut self,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<ScheduleEvent> + Send>>>,
+    ) {
+        self.observers.detach(observer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use tf_core::Generator;
+    use tf_test::TestSubscription;
+
+    struct EventRecorder(Arc<Mutex<Vec<ScheduleEvent>>>);
+
+    impl Observer<ScheduleEvent> for EventRecorder {
+        fn notify(&mut self, message: ScheduleEvent) {
+            self.0.lock().unwrap().push(message);
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_scheduler() {
+        let joiner = Joiner::new();
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        let channel2: AnySubscription = TestSubscription::new("Channel2").into();
+        joiner.subscription_list().add(channel1.clone());
+        joiner.subscription_list().add(channel2.clone());
+
+        let mut scheduler = RefreshScheduler::new(joiner.clone());
+        let events = Arc::new(Mutex::new(vec![]));
+        let observer: Arc<Mutex<Box<dyn Observer<ScheduleEvent> + Send>>> =
+            Arc::new(Mutex::new(Box::new(EventRecorder(events.clone()))));
+        scheduler.attach(Arc::downgrade(&observer));
+
+        let errors = ErrorStore::new();
+        let videos = scheduler.refresh_due(&errors).await;
+        assert_eq!(videos.len(), 4);
+        assert_eq!(joiner.unread_count(), 4);
+        assert!(matches!(
+            &events.lock().unwrap()[..],
+            [
+                ScheduleEvent::Started(_),
+                ScheduleEvent::Refreshed(_, 2, _),
+                ScheduleEvent::Refreshed(_, 2, _)
+            ]
+        ));
+
+        // The test channels did not upload for a long time.
+        let max_interval = scheduler.policy().lock().unwrap().max_interval();
+        assert_eq!(scheduler.interval(&channel1), Some(max_interval));
+        assert!(scheduler.next_due().unwrap() > chrono::Local::now().naive_local());
+        assert!(scheduler.refresh_due(&errors).await.is_empty());
+
+        events.lock().unwrap().clear();
+        scheduler.refresh_now(&channel2);
+        assert!(scheduler.refresh_due(&errors).await.is_empty());
+        assert!(matches!(
+            &events.lock().unwrap()[..],
+            [ScheduleEvent::Started(s), ScheduleEvent::Refreshed(r, 0, _)]
+                if s == &vec![channel2.clone()] && r == &channel2
+        ));
+
+        events.lock().unwrap().clear();
+        scheduler.pause();
+        scheduler.refresh_all_now();
+        assert!(scheduler.refresh_due(&errors).await.is_empty());
+        assert_eq!(scheduler.next_due(), None);
+        scheduler.resume();
+        assert!(scheduler.refresh_due(&errors).await.is_empty());
+        let events = events.lock().unwrap();
+        assert!(matches!(events[0], ScheduleEvent::Paused));
+        assert!(matches!(events[1], ScheduleEvent::Resumed));
+        assert!(matches!(events[2], ScheduleEvent::Started(ref s) if s.len() == 2));
+    }
+
+    #[tokio::test]
+    async fn refresh_scheduler_shares_refresh_state() {
+        let joiner = Joiner::new();
+        let channel1: AnySubscription = TestSubscription::new("Channel1").into();
+        joiner.subscription_list().add(channel1.clone());
+
+        let errors = ErrorStore::new();
+        assert_eq!(joiner.generate(&errors).await.len(), 2);
+
+        let mut scheduler = RefreshScheduler::new(joiner.clone());
+        let events = Arc::new(Mutex::new(vec![]));
+        let observer: Arc<Mutex<Box<dyn Observer<ScheduleEvent> + Send>>> =
+            Arc::new(Mutex::new(Box::new(EventRecorder(events.clone()))));
+        scheduler.attach(Arc::downgrade(&observer));
+
+        // The videos were already fetched by the joiner.
+        assert!(scheduler.refresh_due(&errors).await.is_empty());
+        assert!(matches!(
+            &events.lock().unwrap()[..],
+            [ScheduleEvent::Started(_), ScheduleEvent::Refreshed(s, 0, _)] if s == &channel1
+        ));
+        assert!(joiner.refresh(&errors).await.is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Versioned serialization of [AnySubscription][crate::AnySubscription]s,
+//! [AnyVideo][crate::AnyVideo]s and [AnyVideoFilter][crate::AnyVideoFilter]s.
+//!
+//! This module is only available with the `serde` feature. Items are stored in a [Versioned]
+//! document which can be serialized by any self-describing format, e.g. JSON or TOML.
+//! Data stored in the legacy CSV-row format can be migrated using [Versioned::from_csv].
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+/// The version of the format written by [Versioned::new].
+///
+/// Increase this whenever the serialized representation of any item changes and add a migration
+/// to [Versioned::into_items].
+///
+/// - `1`: The initial version.
+/// - `2`: [AnyVideo][crate::AnyVideo]s store their watch progress and whether they were seen,
+///   [SubscriptionEntry][crate::SubscriptionEntry]s store the settings and groups of a
+///   [AnySubscription][crate::AnySubscription].
+pub const FORMAT_VERSION: u32 = 2;
+
+/// A error converting from or into a [Versioned] document.
+#[derive(Debug)]
+pub enum SerializationError {
+    /// The document was written by a newer version which is not supported.
+    UnsupportedVersion {
+        /// The version of the document.
+        found: u32,
+        /// The newest version supported.
+        supported: u32,
+    },
+    /// A row of the legacy CSV-format could not be converted.
+    InvalidRow {
+        /// The index of the row, starting at 0.
+        row: usize,
+        /// The content of the row.
+        content: Vec<String>,
+    },
+    /// The legacy CSV-format could not be read.
+    Csv(csv::Error),
+    /// A regular expression of a [AnyVideoFilter][crate::AnyVideoFilter] is invalid.
+    InvalidRegex(regex::Error),
+    /// The [Platform][crate::Platform] is unknown or not enabled.
+    UnknownPlatform(String),
+    /// A [FilterExpression][crate::FilterExpression] of a [AnyVideoFilter][crate::AnyVideoFilter]
+    /// is invalid.
+    InvalidExpression(crate::FilterParseError),
+}
+
+impl std::fmt::Display for SerializationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SerializationError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Unsupported format version {}, the newest supported version is {}",
+                found, supported
+            ),
+            SerializationError::InvalidRow { row, content } => {
+                write!(f, "Invalid row {}: {}", row, content.join(","))
+            }
+            SerializationError::Csv(e) => write!(f, "Error reading csv: {}", e),
+            SerializationError::InvalidRegex(e) => write!(f, "Invalid regex: {}", e),
+            SerializationError::UnknownPlatform(p) => write!(f, "Unknown platform {}", p),
+            SerializationError::InvalidExpression(e) => write!(f, "Invalid expression: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SerializationError {}
+
+impl From<csv::Error> for SerializationError {
+    fn from(e: csv::Error) -> Self {
+        SerializationError::Csv(e)
+    }
+}
+
+impl From<regex::Error> for SerializationError {
+    fn from(e: regex::Error) -> Self {
+        SerializationError::InvalidRegex(e)
+    }
+}
+
+impl From<crate::FilterParseError> for SerializationError {
+    fn from(e: crate::FilterParseError) -> Self {
+        SerializationError::InvalidExpression(e)
+    }
+}
+
+/// A list of items together with the version of the format they were written in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Versioned<T> {
+    /// The version of the format.
+    version: u32,
+    /// The items.
+    items: Vec<T>,
+}
+
+impl<T> Versioned<T> {
+    /// Create a new [Versioned] document with the given items in the current [FORMAT_VERSION].
+    pub fn new(items: Vec<T>) -> Self {
+        Versioned {
+            version: FORMAT_VERSION,
+            items,
+        }
+    }
+
+    /// Get the version of the format the document was written in.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Get the items of the document, migrating them to the current [FORMAT_VERSION].
+    ///
+    /// This fails if the document was written by a newer version.
+    pub fn into_items(self) -> Result<Vec<T>, SerializationError> {
+        if self.version > FORMAT_VERSION {
+            return Err(SerializationError::UnsupportedVersion {
+                found: self.version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        // Version 1 to 2: The new fields of videos and subscription entries are optional and
+        // take their default values when missing, nothing has to be converted.
+        // -- Add migrations from older versions here.
+        Ok(self.items)
+    }
+
+    /// Migrate rows of the legacy CSV-format into a [Versioned] document.
+    ///
+    /// Fails on the first row which cannot be converted.
+    pub fn from_csv_rows<I: IntoIterator<Item = Vec<String>>>(
+        rows: I,
+    ) -> Result<Self, SerializationError>
+    where
+        T: TryFrom<Vec<String>>,
+    {
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                T::try_from(row.clone()).map_err(|_| SerializationError::InvalidRow {
+                    row: i,
+                    content: row,
+                })
+            })
+            .collect::<Result<Vec<T>, _>>()
+            .map(Versioned::new)
+    }
+
+    /// Migrate a file in the legacy CSV-format into a [Versioned] document.
+    pub fn from_csv<R: std::io::Read>(reader: R) -> Result<Self, SerializationError>
+    where
+        T: TryFrom<Vec<String>>,
+    {
+        let rows = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(reader)
+            .records()
+            .map(|r| r.map(|r| r.iter().map(|s| s.to_string()).collect()))
+            .collect::<Result<Vec<Vec<String>>, _>>()?;
+        Versioned::from_csv_rows(rows)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AnySubscription, AnyVideo, AnyVideoFilter, Platform};
+    use std::sync::{Arc, Mutex};
+    use tf_core::{ExpandedVideo, Video};
+    use tf_test::{TestSubscription, TestVideo};
+
+    #[test]
+    fn serialize_subscriptions() {
+        let subscriptions: Vec<AnySubscription> = vec![
+            TestSubscription::new("Channel1").into(),
+            TestSubscription::new("Channel2").into(),
+        ];
+        let json = serde_json::to_string(&Versioned::new(subscriptions.clone())).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"version":2,"items":[{"platform":"test","name":"Channel1"},{"platform":"test","name":"Channel2"}]}"#
+        );
+
+        let versioned: Versioned<AnySubscription> = serde_json::from_str(&json).unwrap();
+        assert_eq!(versioned.into_items().unwrap(), subscriptions);
+    }
+
+    #[test]
+    fn serialize_videos() {
+        let metadata = tf_core::VideoMetadata::new()
+            .with_views(Some(10))
+            .with_description(Some("Description"));
+        let video: AnyVideo = Arc::new(Mutex::new(ExpandedVideo::from(
+            TestVideo::new("Video1", TestSubscription::new("Channel1"))
+                .with_metadata(metadata.clone()),
+        )))
+        .into();
+        let json = serde_json::to_string(&Versioned::new(vec![video.clone()])).unwrap();
+        let videos = serde_json::from_str::<Versioned<AnyVideo>>(&json)
+            .unwrap()
+            .into_items()
+            .unwrap();
+
+        assert!(videos == vec![video]);
+        assert_eq!(videos[0].platform(), Platform::Test);
+        assert_eq!(videos[0].title(), "Video1");
+        assert_eq!(videos[0].metadata(), metadata);
+
+        // Videos serialized without metadata can still be read.
+        let json = r#"{"version":1,"items":[{"platform":"test","title":"Video1","uploaded":"2021-01-01T20:10:00","subscription":{"name":"Channel1"}}]}"#;
+        let videos = serde_json::from_str::<Versioned<AnyVideo>>(json)
+            .unwrap()
+            .into_items()
+            .unwrap();
+        assert!(videos[0].metadata().is_empty());
+    }
+
+    #[test]
+    fn serialize_video_progress() {
+        let progress = tf_core::WatchProgress::new()
+            .with_position(std::time::Duration::from_secs(30))
+            .with_watched_time(std::time::Duration::from_secs(40));
+        let mut expanded =
+            ExpandedVideo::from(TestVideo::new("Video1", TestSubscription::new("Channel1")))
+                .with_progress(progress.clone());
+        expanded.set_seen(true);
+        let video: AnyVideo = Arc::new(Mutex::new(expanded)).into();
+
+        let json = serde_json::to_string(&Versioned::new(vec![video])).unwrap();
+        let videos = serde_json::from_str::<Versioned<AnyVideo>>(&json)
+            .unwrap()
+            .into_items()
+            .unwrap();
+
+        assert_eq!(videos[0].progress(), progress);
+        assert!(videos[0].seen());
+    }
+
+    #[test]
+    fn serialize_subscription_entries() {
+        let list = crate::AnySubscriptionList::default();
+        let subscription: AnySubscription = TestSubscription::new("AliasedEntry").into();
+        let settings = crate::SubscriptionSettings::new()
+            .with_muted(true)
+            .with_alias(Some("Alias"));
+        list.add(subscription.clone());
+        list.add(TestSubscription::new("Channel2").into());
+        list.set_settings(&subscription, settings.clone());
+        list.add_to_group("Group", &subscription);
+
+        let json = serde_json::to_string(&Versioned::new(list.entries())).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":2,"items":[{"platform":"test","name":"AliasedEntry","settings":{"muted":true,"priority":0,"notify":true,"alias":"Alias"},"groups":["Group"]},{"platform":"test","name":"Channel2"}]}"#
+        );
+
+        let restored = crate::AnySubscriptionList::default();
+        for entry in serde_json::from_str::<Versioned<crate::SubscriptionEntry>>(&json)
+            .unwrap()
+            .into_items()
+            .unwrap()
+        {
+            restored.add_entry(entry);
+        }
+        assert_eq!(restored.iter().count(), 2);
+        assert_eq!(restored.settings(&subscription), settings);
+        assert_eq!(restored.groups_of(&subscription), vec!["Group".to_owned()]);
+    }
+
+    #[test]
+    fn migrate_version_1() {
+        let json = r#"{"version":1,"items":[{"platform":"test","name":"Channel1"}]}"#;
+        let entries = serde_json::from_str::<Versioned<crate::SubscriptionEntry>>(json)
+            .unwrap()
+            .into_items()
+            .unwrap();
+        assert_eq!(
+            entries,
+            vec![crate::SubscriptionEntry::new(
+                TestSubscription::new("Channel1").into()
+            )]
+        );
+
+        let json = r#"{"version":1,"items":[{"platform":"test","title":"Video1","uploaded":"2021-01-01T20:10:00","subscription":{"name":"Channel1"}}]}"#;
+        let videos = serde_json::from_str::<Versioned<AnyVideo>>(json)
+            .unwrap()
+            .into_items()
+            .unwrap();
+        assert!(videos[0].progress().is_empty());
+        assert!(!videos[0].seen());
+    }
+
+    #[test]
+    fn serialize_filters() {
+        let filter = AnyVideoFilter::new(
+            Some(Platform::Test),
+            Some(regex::Regex::new("Title").unwrap()),
+            None,
+        );
+        let json = serde_json::to_string(&filter).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"platform":"test","title":"Title","subscription":null}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<AnyVideoFilter>(&json).unwrap(),
+            filter
+        );
+        assert!(serde_json::from_str::<AnyVideoFilter>(r#"{"title":"("}"#).is_err());
+        assert!(serde_json::from_str::<AnyVideoFilter>(r#"{"platform":"unknown"}"#).is_err());
+
+        let filter = AnyVideoFilter::from_expression("title:/live/i".parse().unwrap());
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(
+            json,
+            r#"{"platform":null,"title":null,"subscription":null,"expression":"title:/live/i"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<AnyVideoFilter>(&json).unwrap(),
+            filter
+        );
+        assert!(serde_json::from_str::<AnyVideoFilter>(r#"{"expression":"a OR"}"#).is_err());
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let versioned: Versioned<AnySubscription> =
+            serde_json::from_str(r#"{"version":100,"items":[]}"#).unwrap();
+
+        assert!(matches!(
+            versioned.into_items(),
+            Err(SerializationError::UnsupportedVersion {
+                found: 100,
+                supported: FORMAT_VERSION
+            })
+        ));
+    }
+
+    #[test]
+    fn migrate_csv() {
+        let csv = "test,Channel1\ntest,Channel2\n";
+        let versioned = Versioned::<AnySubscription>::from_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(versioned.version(), FORMAT_VERSION);
+        assert_eq!(
+            versioned.into_items().unwrap(),
+            vec![
+                AnySubscription::from(TestSubscription::new("Channel1")),
+                AnySubscription::from(TestSubscription::new("Channel2"))
+            ]
+        );
+
+        let invalid = "test,Channel1\nunknown,Channel2\n";
+        assert!(matches!(
+            Versioned::<AnySubscription>::from_csv(invalid.as_bytes()),
+            Err(SerializationError::InvalidRow { row: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// The prefix of the column holding whether the subscription is muted.
+const MUTED_PREFIX: &str = "muted:";
+/// The prefix of the column holding the priority of the subscription.
+const PRIORITY_PREFIX: &str = "priority:";
+/// The prefix of the column holding whether to notify about new videos of the subscription.
+const NOTIFY_PREFIX: &str = "notify:";
+/// The prefix of the column holding the alias of the subscription.
+const ALIAS_PREFIX: &str = "alias:";
+
+/// The local settings of the user for a [AnySubscription][crate::AnySubscription].
+///
+/// These are stored in the [AnySubscriptionList][crate::AnySubscriptionList], see
+/// [AnySubscriptionList::set_settings][crate::AnySubscriptionList::set_settings].
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SubscriptionSettings {
+    /// Whether the subscription is kept but its videos are excluded from the feed.
+    muted: bool,
+    /// The priority of the subscription, higher ranks first.
+    priority: i32,
+    /// Whether to notify about new videos of the subscription.
+    notify: bool,
+    /// The local name of the subscription, overriding the remote name.
+    alias: Option<String>,
+}
+
+impl SubscriptionSettings {
+    /// Create new [SubscriptionSettings], not muted, with priority `0`, notifications enabled
+    /// and without alias.
+    pub fn new() -> Self {
+        SubscriptionSettings {
+            muted: false,
+            priority: 0,
+            notify: true,
+            alias: None,
+        }
+    }
+
+    /// Set whether the subscription is muted.
+    pub fn with_muted(mut self, muted: bool) -> Self {
+        self.muted = muted;
+        self
+    }
+
+    /// Set the priority of the subscription.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set whether to notify about new videos of the subscription.
+    pub fn with_notify(mut self, notify: bool) -> Self {
+        self.notify = notify;
+        self
+    }
+
+    /// Set the local name of the subscription, an empty alias removes it.
+    pub fn with_alias<S: AsRef<str>>(mut self, alias: Option<S>) -> Self {
+        self.alias = alias
+            .map(|a| a.as_ref().trim().to_owned())
+            .filter(|a| !a.is_empty());
+        self
+    }
+
+    /// Whether the subscription is kept but its videos are excluded from the feed.
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+
+    /// The priority of the subscription, higher ranks first.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Whether to notify about new videos of the subscription.
+    pub fn notify(&self) -> bool {
+        self.notify
+    }
+
+    /// The local name of the subscription, overriding the remote name,
+    /// see [AnySubscriptionList::display_name][crate::AnySubscriptionList::display_name].
+    pub fn alias(&self) -> Option<String> {
+        self.alias.clone()
+    }
+
+    /// Whether these are the settings of [SubscriptionSettings::new].
+    pub fn is_default(&self) -> bool {
+        self == &SubscriptionSettings::new()
+    }
+
+    /// Get the columns storing all settings differing from the default.
+    pub(crate) fn columns(&self) -> Vec<String> {
+        let mut result = vec![];
+        if self.muted {
+            result.push(format!("{}{}", MUTED_PREFIX, self.muted));
+        }
+        if self.priority != 0 {
+            result.push(format!("{}{}", PRIORITY_PREFIX, self.priority));
+        }
+        if !self.notify {
+            result.push(format!("{}{}", NOTIFY_PREFIX, self.notify));
+        }
+        if let Some(alias) = &self.alias {
+            result.push(format!("{}{}", ALIAS_PREFIX, alias));
+        }
+        result
+    }
+
+    /// Apply a column created by [SubscriptionSettings::columns].
+    ///
+    /// Returns `false` if the column is not a setting.
+    pub(crate) fn apply_column(&mut self, column: &str) -> bool {
+        if let Some(muted) = column.strip_prefix(MUTED_PREFIX) {
+            self.muted = muted == "true";
+        } else if let Some(priority) = column.strip_prefix(PRIORITY_PREFIX) {
+            self.priority = priority.parse().unwrap_or_default();
+        } else if let Some(notify) = column.strip_prefix(NOTIFY_PREFIX) {
+            self.notify = notify != "false";
+        } else if let Some(alias) = column.strip_prefix(ALIAS_PREFIX) {
+            self.alias = Some(alias.to_owned()).filter(|a| !a.is_empty());
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+impl Default for SubscriptionSettings {
+    fn default() -> Self {
+        SubscriptionSettings::new()
+    }
+}
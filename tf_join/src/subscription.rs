@@ -19,7 +19,12 @@
 
 use std::{convert::TryFrom, str::FromStr};
 
-use tf_core::Subscription;
+use tf_core::{ErrorContext, ErrorStore, Subscription, Transport};
+#[cfg(any(feature = "peertube", feature = "lbry"))]
+use tf_utils::rss::{RssExtractor, WithName};
+
+#[cfg(feature = "serde")]
+use crate::SerializationError;
 
 macro_rules! match_subscription {
     ($sub: ident, $func_name: ident) => {
@@ -41,7 +46,14 @@ macro_rules! match_subscription {
 }
 
 /// A [Subscription][tf_core::Subscription] to any [Platform].
+///
+/// With the `serde` feature, the [Platform] is stored in the `platform` field.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "platform", rename_all = "lowercase")
+)]
 pub enum AnySubscription {
     #[cfg(feature = "youtube")]
     Youtube(tf_yt::YTSubscription),
@@ -69,6 +81,144 @@ impl AnySubscription {
             AnySubscription::Test(_) => Platform::Test,
         }
     }
+
+    /// Get the url of the rss-feed of the [AnySubscription], if the [Platform] has one.
+    pub fn feed_url(&self) -> Option<String> {
+        match self {
+            #[cfg(feature = "youtube")]
+            AnySubscription::Youtube(s) => Some(s.feed_url()),
+            #[cfg(feature = "peertube")]
+            AnySubscription::Peertube(s) => Some(s.feed_url()),
+            #[cfg(feature = "lbry")]
+            AnySubscription::Lbry(s) => Some(s.feed_url()),
+            // -- Add new case here.
+            #[cfg(test)]
+            AnySubscription::Test(_) => None,
+        }
+    }
+
+    /// Get the [AnySubscription] from the url of its rss-feed, see [AnySubscription::feed_url].
+    ///
+    /// Only the exact shape of feed url produced by the [Platform] is recognized.
+    pub fn from_feed_url<S: AsRef<str>>(url: S) -> Option<AnySubscription> {
+        let _url = url.as_ref();
+        #[cfg(feature = "youtube")]
+        if let Some(s) = tf_yt::YTSubscription::from_feed_url(_url) {
+            return Some(s.into());
+        }
+        #[cfg(feature = "peertube")]
+        if let Some(s) = tf_pt::PTSubscription::from_feed_url(_url) {
+            return Some(s.into());
+        }
+        #[cfg(feature = "lbry")]
+        if let Some(s) = tf_lbry::LbrySubscription::from_feed_url(_url) {
+            return Some(s.into());
+        }
+        // -- Add new case here.
+        None
+    }
+
+    /// Try to resolve any url pasted by the user to the [AnySubscription] of the channel.
+    ///
+    /// The [Platform] is determined by the url: `lbry://`-uris and `odysee.com` are LBRY,
+    /// `youtube.com` and `youtu.be` are YouTube and everything else is assumed to be PeerTube.
+    /// Network requests are only done if the url does not already contain the channel
+    /// identification, e.g. for video urls or YouTube-handles.
+    #[allow(unused_variables)]
+    pub async fn from_url<S: AsRef<str>>(
+        url: S,
+        client: &dyn Transport,
+    ) -> Option<AnySubscription> {
+        let url = url.as_ref().trim();
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| {
+                u.host_str()
+                    .map(|h| h.trim_start_matches("www.").to_string())
+            })
+            .unwrap_or_default();
+
+        if url.starts_with("lbry://") || host == "odysee.com" {
+            #[cfg(feature = "lbry")]
+            return tf_lbry::LbrySubscription::from_url(url).map(|s| s.into());
+            #[cfg(not(feature = "lbry"))]
+            return None;
+        }
+
+        if host == "youtu.be" || host == "youtube.com" || host.ends_with(".youtube.com") {
+            #[cfg(feature = "youtube")]
+            return tf_yt::YTSubscription::from_url(url, client)
+                .await
+                .map(|s| s.into());
+            #[cfg(not(feature = "youtube"))]
+            return None;
+        }
+
+        // -- Add new case here.
+
+        #[cfg(feature = "peertube")]
+        return tf_pt::PTSubscription::from_url(url, client)
+            .await
+            .map(|s| s.into());
+        #[cfg(not(feature = "peertube"))]
+        None
+    }
+
+    /// Give the [AnySubscription] with the given name.
+    pub fn with_name<S: AsRef<str>>(&self, name: S) -> AnySubscription {
+        match self {
+            #[cfg(feature = "youtube")]
+            AnySubscription::Youtube(s) => {
+                tf_yt::YTSubscription::new_with_name(&s.id(), name.as_ref()).into()
+            }
+            #[cfg(feature = "peertube")]
+            AnySubscription::Peertube(s) => s.with_name(name).into(),
+            #[cfg(feature = "lbry")]
+            AnySubscription::Lbry(s) => s.with_name(name).into(),
+            // -- Add new case here.
+            #[cfg(test)]
+            AnySubscription::Test(_) => tf_test::TestSubscription::new(name.as_ref()).into(),
+        }
+    }
+
+    /// Get the number of consecutive failed refreshes of the [AnySubscription],
+    /// see [ErrorStore::consecutive_failures].
+    pub(crate) fn consecutive_failures(&self, errors: &ErrorStore) -> usize {
+        match self {
+            #[cfg(feature = "youtube")]
+            AnySubscription::Youtube(s) => errors.consecutive_failures(s),
+            #[cfg(feature = "peertube")]
+            AnySubscription::Peertube(s) => errors.consecutive_failures(s),
+            #[cfg(feature = "lbry")]
+            AnySubscription::Lbry(s) => errors.consecutive_failures(s),
+            // -- Add new case here.
+            #[cfg(test)]
+            AnySubscription::Test(s) => errors.consecutive_failures(s),
+        }
+    }
+
+    /// Get the [AnySubscription] a [Error][tf_core::Error] originated from, see
+    /// [ErrorContext::subscription].
+    pub fn from_error_context(context: &ErrorContext) -> Option<AnySubscription> {
+        #[cfg(feature = "youtube")]
+        if let Some(s) = context.subscription::<tf_yt::YTSubscription>() {
+            return Some(s.into());
+        }
+        #[cfg(feature = "peertube")]
+        if let Some(s) = context.subscription::<tf_pt::PTSubscription>() {
+            return Some(s.into());
+        }
+        #[cfg(feature = "lbry")]
+        if let Some(s) = context.subscription::<tf_lbry::LbrySubscription>() {
+            return Some(s.into());
+        }
+        // -- Add new case here.
+        #[cfg(test)]
+        if let Some(s) = context.subscription::<tf_test::TestSubscription>() {
+            return Some(s.into());
+        }
+        None
+    }
 }
 
 impl Subscription for AnySubscription {
@@ -76,6 +226,10 @@ impl Subscription for AnySubscription {
     fn name(&self) -> Option<String> {
         match_subscription!(self, name)
     }
+
+    fn host(&self) -> Option<String> {
+        match_subscription!(self, host)
+    }
 }
 
 impl std::fmt::Display for AnySubscription {
@@ -99,7 +253,7 @@ impl TryFrom<Vec<String>> for AnySubscription {
     type Error = ();
 
     fn try_from(value: Vec<String>) -> Result<Self, Self::Error> {
-        if value.len() == 0 {
+        if value.is_empty() {
             return Err(());
         }
 
@@ -212,6 +366,22 @@ impl From<Platform> for String {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Platform {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&String::from(self.clone()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Platform {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Platform::from_str(&value)
+            .map_err(|_| serde::de::Error::custom(SerializationError::UnknownPlatform(value)))
+    }
+}
+
 impl std::fmt::Display for Platform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -229,6 +399,8 @@ impl std::fmt::Display for Platform {
 }
 
 impl Platform {
+    /// Gives all the [Platform]s that are enabled.
+    #[allow(clippy::vec_init_then_push)]
     pub fn values() -> Vec<Self> {
         let mut result = vec![];
         #[cfg(feature = "youtube")]
@@ -307,4 +479,75 @@ mod test {
         let subscription_res: Result<AnySubscription, ()> = row.try_into();
         assert!(subscription_res.is_err());
     }
+
+    #[tokio::test]
+    #[cfg(feature = "youtube")]
+    async fn anysubscription_from_url_youtube() {
+        let client = reqwest::Client::new();
+        let expected: AnySubscription = YTSubscription::new("UCld68syR8Wi-GY_n4CaoJGA").into();
+
+        for url in [
+            "https://www.youtube.com/channel/UCld68syR8Wi-GY_n4CaoJGA",
+            "https://m.youtube.com/channel/UCld68syR8Wi-GY_n4CaoJGA/videos",
+            "https://www.youtube.com/feeds/videos.xml?channel_id=UCld68syR8Wi-GY_n4CaoJGA",
+        ] {
+            assert_eq!(
+                AnySubscription::from_url(url, &client).await,
+                Some(expected.clone())
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "peertube")]
+    async fn anysubscription_from_url_peertube() {
+        let client = reqwest::Client::new();
+        let expected: AnySubscription =
+            tf_pt::PTSubscription::new("https://framatube.org", "framasoft_channel@framatube.org")
+                .into();
+
+        for url in [
+            "https://framatube.org/c/framasoft_channel",
+            "https://framatube.org/c/framasoft_channel/videos",
+            "https://framatube.org/video-channels/framasoft_channel@framatube.org",
+        ] {
+            assert_eq!(
+                AnySubscription::from_url(url, &client).await,
+                Some(expected.clone())
+            );
+        }
+        assert_eq!(
+            AnySubscription::from_url("https://framatube.org/about", &client).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "lbry")]
+    async fn anysubscription_from_url_lbry() {
+        let client = reqwest::Client::new();
+        let expected: AnySubscription = tf_lbry::LbrySubscription::new("@DistroTube:2").into();
+
+        for url in [
+            "https://odysee.com/@DistroTube:2",
+            "https://odysee.com/@DistroTube:2/some-video:a",
+            "lbry://@DistroTube#2",
+            "https://odysee.com/$/rss/@DistroTube:2",
+        ] {
+            assert_eq!(
+                AnySubscription::from_url(url, &client).await,
+                Some(expected.clone())
+            );
+        }
+        assert_eq!(
+            AnySubscription::from_url("https://odysee.com/$/settings", &client).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn anysubscription_from_url_invalid() {
+        let client = reqwest::Client::new();
+        assert_eq!(AnySubscription::from_url("not a url", &client).await, None);
+    }
 }
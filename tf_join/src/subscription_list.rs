@@ -17,7 +17,11 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+};
 
 use tf_core::SubscriptionList;
 #[cfg(feature = "lbry")]
@@ -31,14 +35,27 @@ use tf_test::TestSubscription;
 use tf_yt::YTSubscription;
 // -- Add import here.
 
-use crate::AnySubscription;
+use crate::{AnySubscription, SubscriptionSettings};
+
+/// The prefix of the trailing columns of a subscription row holding the names of its groups.
+const GROUP_PREFIX: &str = "group:";
+
+/// The name of a group together with its members.
+type Group = (String, Vec<AnySubscription>);
 
 /// A wrapper around all the available [SubscriptionList] of the platforms.
 ///
 /// This implements [Observable] and emits [SubscriptionEvent] to the [Observer]s.
+/// It also keeps named groups of [AnySubscription]s, emitting [GroupEvent]s, and the
+/// [SubscriptionSettings] of every [AnySubscription].
 #[derive(Clone)]
 pub struct AnySubscriptionList {
     observers: ObserverList<SubscriptionEvent>,
+    group_observers: ObserverList<GroupEvent>,
+    /// The groups in the order they were added, together with their members.
+    groups: Arc<Mutex<Vec<Group>>>,
+    /// The [SubscriptionSettings] differing from the default.
+    settings: Arc<Mutex<HashMap<AnySubscription, SubscriptionSettings>>>,
 
     #[cfg(feature = "youtube")]
     yt_subscriptions: Arc<Mutex<SubscriptionList<YTSubscription>>>,
@@ -59,6 +76,9 @@ impl AnySubscriptionList {
     pub(crate) fn new() -> Self {
         AnySubscriptionList {
             observers: ObserverList::default(),
+            group_observers: ObserverList::default(),
+            groups: Arc::new(Mutex::new(Vec::new())),
+            settings: Arc::new(Mutex::new(HashMap::new())),
 
             #[cfg(feature = "youtube")]
             yt_subscriptions: Arc::new(Mutex::new(SubscriptionList::default())),
@@ -125,7 +145,14 @@ impl AnySubscriptionList {
     /// Remove a [AnySubscription] to the [AnySubscriptionList].
     ///
     /// This will notify all [Observer]s with [SubscriptionEvent::Remove].
+    /// The [AnySubscription] will also leave all of its groups, see [AnySubscriptionList::remove_from_group],
+    /// and its [SubscriptionSettings] are dropped.
     pub fn remove(&self, subscription: AnySubscription) {
+        for group in self.groups_of(&subscription) {
+            self.remove_from_group(&group, &subscription);
+        }
+        self.settings.lock().unwrap().remove(&subscription);
+
         match subscription.clone() {
             #[cfg(feature = "youtube")]
             AnySubscription::Youtube(sub) => self.yt_subscriptions.lock().unwrap().remove(sub),
@@ -214,6 +241,353 @@ impl AnySubscriptionList {
     }
 }
 
+impl AnySubscriptionList {
+    /// Get the names of all groups in the order they were added.
+    pub fn groups(&self) -> Vec<String> {
+        self.groups
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Add a new, empty group.
+    ///
+    /// Returns `false` if a group with this name already exists.
+    /// Otherwise, this will notify all [Observer]s with [GroupEvent::Add].
+    pub fn add_group<S: AsRef<str>>(&self, name: S) -> bool {
+        let name = name.as_ref().to_owned();
+        {
+            let mut groups = self.groups.lock().unwrap();
+            if groups.iter().any(|(n, _)| n == &name) {
+                return false;
+            }
+            groups.push((name.clone(), vec![]));
+        }
+        self.group_observers.notify(GroupEvent::Add(name));
+        true
+    }
+
+    /// Remove a group, the [AnySubscription]s of it will stay in the [AnySubscriptionList].
+    ///
+    /// Returns `false` if there is no such group.
+    /// Otherwise, this will notify all [Observer]s with [GroupEvent::Remove].
+    pub fn remove_group<S: AsRef<str>>(&self, name: S) -> bool {
+        let name = name.as_ref();
+        {
+            let mut groups = self.groups.lock().unwrap();
+            let len = groups.len();
+            groups.retain(|(n, _)| n != name);
+            if groups.len() == len {
+                return false;
+            }
+        }
+        self.group_observers
+            .notify(GroupEvent::Remove(name.to_owned()));
+        true
+    }
+
+    /// Rename a group, keeping its members.
+    ///
+    /// Returns `false` if there is no group named `from` or there already is a group named `to`.
+    /// Otherwise, this will notify all [Observer]s with [GroupEvent::Rename].
+    pub fn rename_group<S: AsRef<str>, T: AsRef<str>>(&self, from: S, to: T) -> bool {
+        let (from, to) = (from.as_ref().to_owned(), to.as_ref().to_owned());
+        {
+            let mut groups = self.groups.lock().unwrap();
+            if groups.iter().any(|(n, _)| n == &to) {
+                return false;
+            }
+            match groups.iter_mut().find(|(n, _)| n == &from) {
+                Some((name, _)) => *name = to.clone(),
+                None => return false,
+            }
+        }
+        self.group_observers.notify(GroupEvent::Rename(from, to));
+        true
+    }
+
+    /// Add a [AnySubscription] to a group, adding the group first if it does not yet exist.
+    ///
+    /// This will notify all [Observer]s with [GroupEvent::Join] if the [AnySubscription] was
+    /// not yet part of the group.
+    pub fn add_to_group<S: AsRef<str>>(&self, name: S, subscription: &AnySubscription) {
+        let name = name.as_ref();
+        self.add_group(name);
+        {
+            let mut groups = self.groups.lock().unwrap();
+            let members = match groups.iter_mut().find(|(n, _)| n == name) {
+                Some((_, members)) => members,
+                None => return,
+            };
+            if members.contains(subscription) {
+                return;
+            }
+            members.push(subscription.clone());
+        }
+        self.group_observers
+            .notify(GroupEvent::Join(name.to_owned(), subscription.clone()));
+    }
+
+    /// Remove a [AnySubscription] from a group.
+    ///
+    /// This will notify all [Observer]s with [GroupEvent::Leave] if the [AnySubscription] was
+    /// part of the group.
+    pub fn remove_from_group<S: AsRef<str>>(&self, name: S, subscription: &AnySubscription) {
+        let name = name.as_ref();
+        {
+            let mut groups = self.groups.lock().unwrap();
+            let members = match groups.iter_mut().find(|(n, _)| n == name) {
+                Some((_, members)) => members,
+                None => return,
+            };
+            let len = members.len();
+            members.retain(|s| s != subscription);
+            if members.len() == len {
+                return;
+            }
+        }
+        self.group_observers
+            .notify(GroupEvent::Leave(name.to_owned(), subscription.clone()));
+    }
+
+    /// Get the names of all groups the [AnySubscription] is part of.
+    pub fn groups_of(&self, subscription: &AnySubscription) -> Vec<String> {
+        self.groups
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, members)| members.contains(subscription))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Get the [AnySubscription]s of the given groups, each at most once.
+    ///
+    /// Only [AnySubscription]s still in the [AnySubscriptionList] are returned.
+    pub fn group_members<S: AsRef<str>>(&self, names: &[S]) -> Vec<AnySubscription> {
+        let members = self
+            .groups
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| names.iter().any(|n| n.as_ref() == name))
+            .flat_map(|(_, members)| members.iter().cloned())
+            .collect::<Vec<_>>();
+        // Take the subscriptions from the list, these are the most up to date ones.
+        self.iter().filter(|s| members.contains(s)).collect()
+    }
+
+    /// Get the [SubscriptionSettings] of the [AnySubscription].
+    pub fn settings(&self, subscription: &AnySubscription) -> SubscriptionSettings {
+        self.settings
+            .lock()
+            .unwrap()
+            .get(subscription)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Set the [SubscriptionSettings] of the [AnySubscription].
+    ///
+    /// This will notify all [Observer]s with [SubscriptionEvent::Update] if the settings changed.
+    /// Settings of [AnySubscription]s which are not part of the [AnySubscriptionList] are ignored.
+    pub fn set_settings(&self, subscription: &AnySubscription, settings: SubscriptionSettings) {
+        if !self.iter().any(|s| &s == subscription) {
+            return;
+        }
+        {
+            let mut all_settings = self.settings.lock().unwrap();
+            let old = if settings.is_default() {
+                all_settings.remove(subscription)
+            } else {
+                all_settings.insert(subscription.clone(), settings.clone())
+            };
+            if old.unwrap_or_default() == settings {
+                return;
+            }
+        }
+        self.observers
+            .notify(SubscriptionEvent::Update(subscription.clone()))
+    }
+
+    /// Get the name of the [AnySubscription] to display, i.e. its
+    /// [alias][SubscriptionSettings::alias] if it has one, otherwise the displayed [AnySubscription].
+    pub fn display_name(&self, subscription: &AnySubscription) -> String {
+        self.settings(subscription)
+            .alias()
+            .unwrap_or_else(|| subscription.to_string())
+    }
+
+    /// Get all stored [AnySubscription]s ranked by their [priority][SubscriptionSettings::priority],
+    /// the highest first.
+    ///
+    /// [AnySubscription]s with the same priority stay in the order of [AnySubscriptionList::iter].
+    pub fn ranked(&self) -> Vec<AnySubscription> {
+        let mut subscriptions = self.iter().collect::<Vec<_>>();
+        subscriptions.sort_by_cached_key(|s| std::cmp::Reverse(self.settings(s).priority()));
+        subscriptions
+    }
+
+    /// Get all [AnySubscription]s as rows, e.g. to be stored in a CSV file.
+    ///
+    /// Every row is the row of the [AnySubscription] followed by one column for every setting
+    /// differing from the default (e.g. `muted:true`) and one `group:<name>` column for every
+    /// group the [AnySubscription] is part of. Groups without members are not part of the rows.
+    ///
+    /// As the first columns are the ones of the [AnySubscription], the rows can also be read
+    /// using [AnySubscription::try_from].
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        self.iter()
+            .map(|s| {
+                let settings = self.settings(&s);
+                let groups = self.groups_of(&s);
+                let mut row = Vec::<String>::from(s);
+                row.extend(settings.columns());
+                row.extend(groups.into_iter().map(|g| format!("{}{}", GROUP_PREFIX, g)));
+                row
+            })
+            .collect()
+    }
+
+    /// Add a [AnySubscription] from a row as created by [AnySubscriptionList::rows],
+    /// also restoring its settings and adding it to its groups.
+    ///
+    /// Rows without settings or groups, e.g. from older versions, are supported as well.
+    /// Returns [None] if the row is not a valid [AnySubscription].
+    pub fn add_row(&self, row: Vec<String>) -> Option<AnySubscription> {
+        let (subscription, settings, groups) = parse_row(row)?;
+        self.add_with(subscription.clone(), settings, groups);
+        Some(subscription)
+    }
+
+    /// Add a [AnySubscription] with its [SubscriptionSettings] and groups.
+    fn add_with(
+        &self,
+        subscription: AnySubscription,
+        settings: SubscriptionSettings,
+        groups: Vec<String>,
+    ) {
+        self.add(subscription.clone());
+        for group in groups {
+            self.add_to_group(group, &subscription);
+        }
+        self.set_settings(&subscription, settings);
+    }
+
+    /// Get all [AnySubscription]s together with their [SubscriptionSettings] and groups,
+    /// e.g. to be stored in a [Versioned][crate::Versioned] document.
+    #[cfg(feature = "serde")]
+    pub fn entries(&self) -> Vec<SubscriptionEntry> {
+        self.iter()
+            .map(|s| SubscriptionEntry {
+                settings: self.settings(&s),
+                groups: self.groups_of(&s),
+                subscription: s,
+            })
+            .collect()
+    }
+
+    /// Add a [AnySubscription] from a [SubscriptionEntry] as created by
+    /// [AnySubscriptionList::entries], also restoring its settings and adding it to its groups.
+    #[cfg(feature = "serde")]
+    pub fn add_entry(&self, entry: SubscriptionEntry) {
+        self.add_with(entry.subscription, entry.settings, entry.groups)
+    }
+}
+
+/// Split a row as created by [AnySubscriptionList::rows] into the [AnySubscription], its
+/// [SubscriptionSettings] and its groups.
+fn parse_row(row: Vec<String>) -> Option<(AnySubscription, SubscriptionSettings, Vec<String>)> {
+    let subscription = AnySubscription::try_from(row.clone()).ok()?;
+
+    // The platform followed by the identifying columns of the subscription.
+    let identifying = Vec::<String>::from(subscription.clone()).len();
+    let mut settings = SubscriptionSettings::new();
+    let mut groups = vec![];
+    for column in row.iter().skip(identifying) {
+        if let Some(group) = column.strip_prefix(GROUP_PREFIX) {
+            groups.push(group.to_owned());
+        } else {
+            settings.apply_column(column);
+        }
+    }
+    Some((subscription, settings, groups))
+}
+
+/// A [AnySubscription] together with its [SubscriptionSettings] and the names of its groups.
+///
+/// This is the serialized form of a [AnySubscriptionList], see [AnySubscriptionList::entries].
+/// Default settings and empty groups are left out, so a [AnySubscription] serialized on its own
+/// is a valid [SubscriptionEntry] as well.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SubscriptionEntry {
+    /// The [AnySubscription].
+    #[serde(flatten)]
+    subscription: AnySubscription,
+    /// The [SubscriptionSettings] of the [AnySubscription].
+    #[serde(default, skip_serializing_if = "SubscriptionSettings::is_default")]
+    settings: SubscriptionSettings,
+    /// The names of the groups the [AnySubscription] is part of.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl SubscriptionEntry {
+    /// Create a new [SubscriptionEntry] with default [SubscriptionSettings] and no groups.
+    pub fn new(subscription: AnySubscription) -> Self {
+        SubscriptionEntry {
+            subscription,
+            settings: SubscriptionSettings::new(),
+            groups: vec![],
+        }
+    }
+
+    /// Set the [SubscriptionSettings].
+    pub fn with_settings(mut self, settings: SubscriptionSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set the names of the groups.
+    pub fn with_groups(mut self, groups: Vec<String>) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Get the [AnySubscription].
+    pub fn subscription(&self) -> AnySubscription {
+        self.subscription.clone()
+    }
+
+    /// Get the [SubscriptionSettings].
+    pub fn settings(&self) -> SubscriptionSettings {
+        self.settings.clone()
+    }
+
+    /// Get the names of the groups.
+    pub fn groups(&self) -> Vec<String> {
+        self.groups.clone()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Vec<String>> for SubscriptionEntry {
+    type Error = ();
+
+    fn try_from(row: Vec<String>) -> Result<Self, Self::Error> {
+        let (subscription, settings, groups) = parse_row(row).ok_or(())?;
+        Ok(SubscriptionEntry {
+            subscription,
+            settings,
+            groups,
+        })
+    }
+}
+
 impl Default for AnySubscriptionList {
     fn default() -> Self {
         AnySubscriptionList::new()
@@ -231,6 +605,21 @@ pub enum SubscriptionEvent {
     Update(AnySubscription),
 }
 
+/// The event sent by [AnySubscriptionList] when its groups change.
+#[derive(Clone, Debug)]
+pub enum GroupEvent {
+    /// A group was added. See [AnySubscriptionList::add_group].
+    Add(String),
+    /// A group was removed. See [AnySubscriptionList::remove_group].
+    Remove(String),
+    /// A group was renamed from the first to the second name. See [AnySubscriptionList::rename_group].
+    Rename(String, String),
+    /// A [AnySubscription] was added to a group. See [AnySubscriptionList::add_to_group].
+    Join(String, AnySubscription),
+    /// A [AnySubscription] was removed from a group. See [AnySubscriptionList::remove_from_group].
+    Leave(String, AnySubscription),
+}
+
 impl Observable<SubscriptionEvent> for AnySubscriptionList {
     fn attach(
         &mut self,
@@ -246,3 +635,13 @@ impl Observable<SubscriptionEvent> for AnySubscriptionList {
         self.observers.detach(observer)
     }
 }
+
+impl Observable<GroupEvent> for AnySubscriptionList {
+    fn attach(&mut self, observer: std::sync::Weak<Mutex<Box<dyn Observer<GroupEvent> + Send>>>) {
+        self.group_observers.attach(observer)
+    }
+
+    fn detach(&mut self, observer: std::sync::Weak<Mutex<Box<dyn Observer<GroupEvent> + Send>>>) {
+        self.group_observers.detach(observer)
+    }
+}
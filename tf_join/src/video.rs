@@ -21,11 +21,12 @@ use std::{
     convert::TryFrom,
     str::FromStr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use async_trait::async_trait;
 
-use tf_core::{ExpandedVideo, Video};
+use tf_core::{ExpandedVideo, Transport, Video, VideoId, VideoMetadata, WatchProgress};
 use tf_observer::{Observable, Observer};
 
 use crate::{AnySubscription, Platform};
@@ -37,14 +38,14 @@ macro_rules! match_video {
     ($video: ident, $($func_name: ident ($($arg: ident),*)).*) => {
         match $video {
             #[cfg(feature = "youtube")]
-            AnyVideo::Youtube(v) => v.lock().unwrap().$($func_name($($arg)*))*,
+            AnyVideo::Youtube(v) => v.lock().unwrap().$($func_name($($arg),*))*,
             #[cfg(feature = "peertube")]
-            AnyVideo::Peertube(v) => v.lock().unwrap().$($func_name($($arg)*))*,
+            AnyVideo::Peertube(v) => v.lock().unwrap().$($func_name($($arg),*))*,
             #[cfg(feature = "lbry")]
-            AnyVideo::Lbry(v) => v.lock().unwrap().$($func_name($($arg)*))*,
+            AnyVideo::Lbry(v) => v.lock().unwrap().$($func_name($($arg),*))*,
             // -- Add new value here.
             #[cfg(test)]
-            AnyVideo::Test(v) => v.lock().unwrap().$($func_name($($arg)*))*,
+            AnyVideo::Test(v) => v.lock().unwrap().$($func_name($($arg),*))*,
         }
     };
 }
@@ -57,8 +58,26 @@ fn arc_eq<S: Eq>(a1: &Arc<Mutex<S>>, a2: &Arc<Mutex<S>>) -> bool {
     }
 }
 
+fn arc_update<V: Video>(a1: &Arc<Mutex<V>>, a2: &Arc<Mutex<V>>) -> bool {
+    if Arc::ptr_eq(a1, a2) {
+        false
+    } else {
+        let other = a2.lock().unwrap().clone();
+        a1.lock().unwrap().update(&other)
+    }
+}
+
 /// A [Video] coming from any [Platform].
+///
+/// With the `serde` feature, the [Platform] is stored in the `platform` field together with
+/// the [WatchProgress][tf_core::WatchProgress] and seen-status of the [ExpandedVideo].
+/// The playing status of the [ExpandedVideo] is not serialized.
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "AnyVideoRepr", into = "AnyVideoRepr")
+)]
 pub enum AnyVideo {
     #[cfg(feature = "youtube")]
     Youtube(Arc<Mutex<ExpandedVideo<tf_yt::YTVideo>>>),
@@ -131,7 +150,32 @@ impl Video for AnyVideo {
         match_video!(self, thumbnail_url)
     }
 
-    async fn thumbnail_with_client(&self, client: &reqwest::Client) -> image::DynamicImage {
+    fn metadata(&self) -> VideoMetadata {
+        match_video!(self, metadata)
+    }
+
+    fn id(&self) -> VideoId {
+        match_video!(self, id)
+    }
+
+    /// Update the shared [ExpandedVideo] such that every clone of the [AnyVideo] sees the changes.
+    fn update(&mut self, other: &Self) -> bool {
+        match (self, other) {
+            #[cfg(feature = "youtube")]
+            (AnyVideo::Youtube(v1), AnyVideo::Youtube(v2)) => arc_update(v1, v2),
+            #[cfg(feature = "peertube")]
+            (AnyVideo::Peertube(v1), AnyVideo::Peertube(v2)) => arc_update(v1, v2),
+            #[cfg(feature = "lbry")]
+            (AnyVideo::Lbry(v1), AnyVideo::Lbry(v2)) => arc_update(v1, v2),
+            // -- Add new value here.
+            #[cfg(test)]
+            (AnyVideo::Test(v1), AnyVideo::Test(v2)) => arc_update(v1, v2),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    async fn thumbnail_with_client(&self, client: &dyn Transport) -> image::DynamicImage {
         match self {
             #[cfg(feature = "youtube")]
             AnyVideo::Youtube(yt) => {
@@ -174,6 +218,45 @@ impl AnyVideo {
         match_video!(self, playing)
     }
 
+    /// Get the [WatchProgress] of the [AnyVideo].
+    pub fn progress(&self) -> WatchProgress {
+        match_video!(self, progress)
+    }
+
+    /// Report that the [AnyVideo] was played until `position`, watching it for `watched_for`
+    /// since the last report, see [ExpandedVideo::report_progress].
+    ///
+    /// Use [Joiner::report_progress][crate::Joiner::report_progress] to also persist the
+    /// [WatchProgress] in the video cache.
+    pub fn report_progress(&self, position: Duration, watched_for: Duration, threshold: f64) {
+        match_video!(self, report_progress(position, watched_for, threshold))
+    }
+
+    /// Mark the [AnyVideo] as watched or unwatched, see [ExpandedVideo::set_watched].
+    ///
+    /// Use [Joiner::set_watched][crate::Joiner::set_watched] to also persist this in the video cache.
+    pub fn set_watched(&self, watched: bool) {
+        match_video!(self, set_watched(watched))
+    }
+
+    /// Whether the [AnyVideo] was watched.
+    pub fn watched(&self) -> bool {
+        match_video!(self, watched)
+    }
+
+    /// Mark the [AnyVideo] as seen or unseen in the feed, see [ExpandedVideo::set_seen].
+    ///
+    /// Use [Joiner::set_seen][crate::Joiner::set_seen] to also persist this in the video cache
+    /// and update the unread counts.
+    pub fn set_seen(&self, seen: bool) {
+        match_video!(self, set_seen(seen))
+    }
+
+    /// Whether the [AnyVideo] was already seen in the feed.
+    pub fn seen(&self) -> bool {
+        match_video!(self, seen)
+    }
+
     /// Get the [Platform] where the [AnyVideo] was uploaded.
     pub fn platform(&self) -> Platform {
         match self {
@@ -211,7 +294,7 @@ impl TryFrom<Vec<String>> for AnyVideo {
     type Error = ();
 
     fn try_from(value: Vec<String>) -> Result<Self, Self::Error> {
-        if value.len() == 0 {
+        if value.is_empty() {
             return Err(());
         }
 
@@ -242,20 +325,105 @@ impl From<AnyVideo> for Vec<String> {
         let mut result = vec![video.platform().into()];
         let vid_vec: Vec<String> = match video {
             #[cfg(feature = "youtube")]
-            AnyVideo::Youtube(v) => v.lock().unwrap().clone().into(),
+            AnyVideo::Youtube(v) => v.lock().unwrap().internal().into(),
             #[cfg(feature = "peertube")]
-            AnyVideo::Peertube(v) => v.lock().unwrap().clone().into(),
+            AnyVideo::Peertube(v) => v.lock().unwrap().internal().into(),
             #[cfg(feature = "lbry")]
-            AnyVideo::Lbry(v) => v.lock().unwrap().clone().into(),
+            AnyVideo::Lbry(v) => v.lock().unwrap().internal().into(),
             // -- Add new value here.
             #[cfg(test)]
-            AnyVideo::Test(v) => v.lock().unwrap().clone().into(),
+            AnyVideo::Test(v) => v.lock().unwrap().internal().into(),
         };
         result.append(&mut vid_vec.clone());
         result
     }
 }
 
+/// The serialized representation of a [AnyVideo].
+///
+/// The [WatchProgress][tf_core::WatchProgress] and seen-status are left out if they are empty,
+/// documents without them, e.g. of [FORMAT_VERSION][crate::FORMAT_VERSION] `1`, are still supported.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AnyVideoRepr {
+    /// The video of its platform.
+    #[serde(flatten)]
+    video: PlatformVideoRepr,
+    /// The [WatchProgress][tf_core::WatchProgress] of the [ExpandedVideo].
+    #[serde(default, skip_serializing_if = "tf_core::WatchProgress::is_empty")]
+    progress: tf_core::WatchProgress,
+    /// Whether the [ExpandedVideo] was seen.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    seen: bool,
+}
+
+/// The serialized representation of the video of a [AnyVideo] on its [Platform].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "platform", rename_all = "lowercase")]
+enum PlatformVideoRepr {
+    #[cfg(feature = "youtube")]
+    Youtube(tf_yt::YTVideo),
+    #[cfg(feature = "peertube")]
+    Peertube(tf_pt::PTVideo),
+    #[cfg(feature = "lbry")]
+    Lbry(tf_lbry::LbryVideo),
+    // -- Add new value here.
+    #[cfg(test)]
+    Test(tf_test::TestVideo),
+}
+
+#[cfg(feature = "serde")]
+impl From<AnyVideo> for AnyVideoRepr {
+    fn from(video: AnyVideo) -> Self {
+        macro_rules! repr {
+            ($v:expr, $variant:ident) => {{
+                let v = $v.lock().unwrap();
+                AnyVideoRepr {
+                    video: PlatformVideoRepr::$variant(v.internal()),
+                    progress: v.progress(),
+                    seen: v.seen(),
+                }
+            }};
+        }
+        match video {
+            #[cfg(feature = "youtube")]
+            AnyVideo::Youtube(v) => repr!(v, Youtube),
+            #[cfg(feature = "peertube")]
+            AnyVideo::Peertube(v) => repr!(v, Peertube),
+            #[cfg(feature = "lbry")]
+            AnyVideo::Lbry(v) => repr!(v, Lbry),
+            // -- Add new value here.
+            #[cfg(test)]
+            AnyVideo::Test(v) => repr!(v, Test),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<AnyVideoRepr> for AnyVideo {
+    fn from(repr: AnyVideoRepr) -> Self {
+        macro_rules! expand {
+            ($v:expr) => {{
+                let mut video = ExpandedVideo::from($v).with_progress(repr.progress);
+                video.set_seen(repr.seen);
+                Arc::new(Mutex::new(video)).into()
+            }};
+        }
+        match repr.video {
+            #[cfg(feature = "youtube")]
+            PlatformVideoRepr::Youtube(v) => expand!(v),
+            #[cfg(feature = "peertube")]
+            PlatformVideoRepr::Peertube(v) => expand!(v),
+            #[cfg(feature = "lbry")]
+            PlatformVideoRepr::Lbry(v) => expand!(v),
+            // -- Add new value here.
+            #[cfg(test)]
+            PlatformVideoRepr::Test(v) => expand!(v),
+        }
+    }
+}
+
 #[cfg(feature = "youtube")]
 impl From<Arc<Mutex<ExpandedVideo<tf_yt::YTVideo>>>> for AnyVideo {
     fn from(v: Arc<Mutex<ExpandedVideo<tf_yt::YTVideo>>>) -> Self {
@@ -334,11 +502,110 @@ mod test {
     fn anyvideo_conversion_test_back() {
         let row = vec!["test".to_string(), "Video".to_string(), "Sub".to_string()];
         let video: AnyVideo = Arc::new(Mutex::new(ExpandedVideo::from(TestVideo::new(
-            "Video".to_string(),
+            "Video",
             TestSubscription::new("Sub"),
         ))))
         .into();
 
         assert_eq!(Vec::<String>::from(video), row);
     }
+
+    #[test]
+    fn anyvideo_metadata() {
+        let metadata = VideoMetadata::new()
+            .with_duration(Some(std::time::Duration::from_secs(60)))
+            .with_live(true);
+        let video: AnyVideo = Arc::new(Mutex::new(ExpandedVideo::from(
+            TestVideo::new("Video", TestSubscription::new("Sub")).with_metadata(metadata.clone()),
+        )))
+        .into();
+
+        assert_eq!(video.metadata(), metadata);
+
+        let row = Vec::<String>::from(video);
+        assert_eq!(
+            row,
+            vec!["test", "Video", "Sub", "60", "", "true", "false", ""]
+        );
+
+        let video: AnyVideo = row.try_into().unwrap();
+        assert_eq!(video.metadata(), metadata);
+    }
+
+    #[cfg(all(feature = "youtube", feature = "peertube", feature = "lbry"))]
+    #[test]
+    fn anyvideo_id() {
+        let date = chrono::NaiveDate::from_ymd_opt(2021, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        let yt = |url: &str, title: &str| -> AnyVideo {
+            let sub = tf_yt::YTSubscription::new("UCld68syR8Wi-GY_n4CaoJGA");
+            Arc::new(Mutex::new(ExpandedVideo::from(tf_yt::YTVideo::new(
+                url, title, date, sub, "",
+            ))))
+            .into()
+        };
+        assert_eq!(
+            yt("https://www.youtube.com//watch?v=abc123", "Title").id(),
+            VideoId::Youtube("abc123".to_string())
+        );
+        assert!(
+            yt("https://www.youtube.com/watch?v=abc123", "Title")
+                == yt("https://www.youtube.com/watch?v=abc123", "Edited Title")
+        );
+
+        let pt = |url: &str| -> AnyVideo {
+            let sub = tf_pt::PTSubscription::new("https://example.com", "channel");
+            Arc::new(Mutex::new(ExpandedVideo::from(tf_pt::PTVideo::new(
+                url, "Title", date, sub, "",
+            ))))
+            .into()
+        };
+        let short_uuid = VideoId::Peertube {
+            host: "example.com".to_string(),
+            short_uuid: "kkGMgK9ZtnKfYAgnEtQxbv".to_string(),
+        };
+        assert_eq!(
+            pt("https://example.com/videos/watch/9c9de5e8-0a1e-484a-b099-e80766180a6d").id(),
+            short_uuid
+        );
+        assert_eq!(
+            pt("https://example.com/w/kkGMgK9ZtnKfYAgnEtQxbv").id(),
+            short_uuid
+        );
+        assert!(pt("https://other.com/w/kkGMgK9ZtnKfYAgnEtQxbv").id() != short_uuid);
+
+        let lbry = |url: &str| -> AnyVideo {
+            let sub = tf_lbry::LbrySubscription::new("@channel:1");
+            Arc::new(Mutex::new(ExpandedVideo::from(tf_lbry::LbryVideo::new(
+                url, "Title", date, sub, "",
+            ))))
+            .into()
+        };
+        assert_eq!(
+            lbry("https://odysee.com/@channel:1/video:2f").id(),
+            VideoId::Lbry("2f".to_string())
+        );
+    }
+
+    #[test]
+    fn anyvideo_update() {
+        let video: AnyVideo = Arc::new(Mutex::new(ExpandedVideo::from(TestVideo::new(
+            "Video",
+            TestSubscription::new("Sub"),
+        ))))
+        .into();
+        let mut clone = video.clone();
+        let updated: AnyVideo = Arc::new(Mutex::new(ExpandedVideo::from(
+            TestVideo::new("Video", TestSubscription::new("Sub"))
+                .with_metadata(VideoMetadata::new().with_live(true)),
+        )))
+        .into();
+
+        assert!(clone.update(&updated));
+        assert!(video.metadata().is_live());
+        assert!(!clone.update(&updated));
+    }
 }
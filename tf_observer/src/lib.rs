@@ -24,6 +24,13 @@
 //! this module also contains a [`ObserverList`] where [`Observer`]s can be
 //! [`attached`][ObserverList::attach], [`detached`][ObserverList::detach] and
 //! [`notified`][ObserverList::notify].
+//!
+//! Instead of implementing [`Observer`], the messages can also be received asynchronously using a
+//! [`ObserverStream`], see [`Observable::subscribe`].
+
+mod stream;
+
+pub use stream::{Backpressure, ObserverStream};
 
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -59,6 +66,19 @@ pub trait Observable<T> {
     ///
     /// Should be implemented using [ObserverList::detach].
     fn detach(&mut self, observer: WeakObserver<T>);
+
+    /// Receive the messages of the [Observable] asynchronously using a [ObserverStream]
+    /// with the given [Backpressure].
+    ///
+    /// This [attaches][Observable::attach] the [Observer] of the [ObserverStream].
+    fn subscribe(&mut self, backpressure: Backpressure) -> ObserverStream<T>
+    where
+        T: Send + 'static,
+    {
+        let stream = ObserverStream::new(backpressure);
+        self.attach(stream.observer());
+        stream
+    }
 }
 
 /// A list of [Observer<T>] using the message `T`.
@@ -0,0 +1,225 @@
+/*
+ * Copyright 2021 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Tubefeeder-extractor.
+ *
+ * Tubefeeder-extractor is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Tubefeeder-extractor is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+
+use futures::task::AtomicWaker;
+use futures::Stream;
+
+use crate::Observer;
+
+/// How a [ObserverStream] handles messages when its buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+    /// Buffer all messages until they are consumed.
+    #[default]
+    Unbounded,
+    /// Buffer at most the given amount of messages, dropping new messages when full.
+    DropNewest(usize),
+    /// Buffer at most the given amount of messages, dropping the oldest buffered message when full.
+    DropOldest(usize),
+}
+
+/// The buffer shared between the [ObserverStream] and the [Observer] feeding it.
+struct Channel<T> {
+    /// The [Backpressure] used when the buffer is full.
+    backpressure: Backpressure,
+    /// The buffered messages and the amount of dropped messages.
+    buffer: Mutex<(VecDeque<T>, usize)>,
+    /// The waker of the task waiting for the next message.
+    waker: AtomicWaker,
+}
+
+/// The [Observer] pushing messages into a [Channel].
+struct ChannelObserver<T>(Arc<Channel<T>>);
+
+impl<T> Observer<T> for ChannelObserver<T> {
+    fn notify(&mut self, message: T) {
+        {
+            let mut buffer = self.0.buffer.lock().unwrap();
+            let (messages, dropped) = &mut *buffer;
+            match self.0.backpressure {
+                Backpressure::Unbounded => messages.push_back(message),
+                Backpressure::DropNewest(capacity) => {
+                    if messages.len() < capacity.max(1) {
+                        messages.push_back(message);
+                    } else {
+                        *dropped += 1;
+                    }
+                }
+                Backpressure::DropOldest(capacity) => {
+                    if messages.len() >= capacity.max(1) {
+                        messages.pop_front();
+                        *dropped += 1;
+                    }
+                    messages.push_back(message);
+                }
+            }
+        }
+        self.0.waker.wake();
+    }
+}
+
+/// A asynchronous [Stream] of the messages of a [Observable][crate::Observable].
+///
+/// Notifying a [ObserverStream] only buffers the message, the messages are handled once
+/// the [Stream] is polled. This means that code handling the messages never runs while the
+/// [Observable][crate::Observable] holds its locks and can safely call back into it.
+/// What happens if messages are not consumed fast enough is configured using the [Backpressure].
+///
+/// The [ObserverStream] is usually created using [Observable::subscribe][crate::Observable::subscribe].
+/// Dropping it will detach it from the [Observable][crate::Observable] with the next
+/// [detach][crate::Observable::detach], as it does not keep the [Observer] alive anymore.
+/// The [Stream] never ends by itself.
+pub struct ObserverStream<T> {
+    /// The [Observer] feeding the [Channel], kept alive by the [ObserverStream].
+    observer: Arc<Mutex<Box<dyn Observer<T> + Send>>>,
+    /// The [Channel] containing the messages.
+    channel: Arc<Channel<T>>,
+}
+
+impl<T: Send + 'static> ObserverStream<T> {
+    /// Create a new [ObserverStream] using the given [Backpressure].
+    ///
+    /// To receive messages, the [ObserverStream::observer] has to be attached to a
+    /// [Observable][crate::Observable].
+    pub fn new(backpressure: Backpressure) -> Self {
+        let channel = Arc::new(Channel {
+            backpressure,
+            buffer: Mutex::new((VecDeque::new(), 0)),
+            waker: AtomicWaker::new(),
+        });
+        ObserverStream {
+            observer: Arc::new(Mutex::new(Box::new(ChannelObserver(channel.clone())))),
+            channel,
+        }
+    }
+}
+
+impl<T> ObserverStream<T> {
+    /// Get the [Observer] feeding the [ObserverStream] to [attach][crate::Observable::attach] it.
+    pub fn observer(&self) -> Weak<Mutex<Box<dyn Observer<T> + Send>>> {
+        Arc::downgrade(&self.observer)
+    }
+
+    /// Get the [Backpressure] of the [ObserverStream].
+    pub fn backpressure(&self) -> Backpressure {
+        self.channel.backpressure
+    }
+
+    /// Get the amount of messages dropped because of the [Backpressure].
+    pub fn dropped(&self) -> usize {
+        self.channel.buffer.lock().unwrap().1
+    }
+
+    /// Get the amount of messages buffered but not yet consumed.
+    pub fn pending(&self) -> usize {
+        self.channel.buffer.lock().unwrap().0.len()
+    }
+
+    /// Take the next buffered message.
+    fn pop(&self) -> Option<T> {
+        self.channel.buffer.lock().unwrap().0.pop_front()
+    }
+}
+
+impl<T> Stream for ObserverStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if let Some(message) = self.pop() {
+            return Poll::Ready(Some(message));
+        }
+        self.channel.waker.register(cx.waker());
+        // A message may have arrived in between.
+        match self.pop() {
+            Some(message) => Poll::Ready(Some(message)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    use crate::{Observable, ObserverList};
+
+    #[test]
+    fn observer_stream_unbounded() {
+        let mut observer_list = ObserverList::new();
+        let mut stream = observer_list.subscribe(Backpressure::Unbounded);
+        assert_eq!(observer_list.count(), 1);
+
+        (0..5u64).for_each(|i| observer_list.notify(i));
+        assert_eq!(stream.pending(), 5);
+        assert_eq!(
+            block_on(stream.by_ref().take(5).collect::<Vec<_>>()),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(stream.dropped(), 0);
+
+        drop(stream);
+        assert_eq!(observer_list.count(), 0);
+    }
+
+    #[test]
+    fn observer_stream_drop_newest() {
+        let mut observer_list = ObserverList::new();
+        let mut stream = observer_list.subscribe(Backpressure::DropNewest(2));
+
+        (0..5u64).for_each(|i| observer_list.notify(i));
+        assert_eq!(stream.dropped(), 3);
+        assert_eq!(
+            block_on(stream.by_ref().take(2).collect::<Vec<_>>()),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn observer_stream_drop_oldest() {
+        let mut observer_list = ObserverList::new();
+        let mut stream = observer_list.subscribe(Backpressure::DropOldest(2));
+
+        (0..5u64).for_each(|i| observer_list.notify(i));
+        assert_eq!(stream.dropped(), 3);
+        assert_eq!(
+            block_on(stream.by_ref().take(2).collect::<Vec<_>>()),
+            vec![3, 4]
+        );
+    }
+
+    #[test]
+    fn observer_stream_wakes() {
+        let mut observer_list = ObserverList::new();
+        let mut stream = observer_list.subscribe(Backpressure::Unbounded);
+
+        let notifier = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            observer_list.notify(10u64);
+        });
+        assert_eq!(block_on(stream.next()), Some(10));
+        notifier.join().unwrap();
+    }
+}
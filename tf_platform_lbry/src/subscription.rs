@@ -17,12 +17,19 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use tf_core::{ErrorStore, GeneratorWithClient, Video};
+use tf_core::{
+    ErrorStore, GeneratorWithClient, Page, PageCursor, PagedGenerator, RefreshState, Transport,
+    Video,
+};
 use tf_utils::rss::{RssExtractor, RssExtractorWrapper, WithName};
 
 use crate::LbryVideo;
 
-#[derive(Clone, Hash, Eq, Debug)]
+/// The host the rss-feeds are fetched from.
+const ODYSEE_HOST: &str = "odysee.com";
+
+#[derive(Clone, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LbrySubscription {
     id: String,
     name: Option<String>,
@@ -34,6 +41,12 @@ impl std::cmp::PartialEq for LbrySubscription {
     }
 }
 
+impl std::hash::Hash for LbrySubscription {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
 impl LbrySubscription {
     /// Create a new lbry subscription.
     /// The id should be in the format @name:number.
@@ -51,12 +64,48 @@ impl LbrySubscription {
         }
     }
 
+    /// Get the [LbrySubscription] from the url of the rss-feed, see [RssExtractor::feed_url].
+    pub fn from_feed_url<S: AsRef<str>>(url: S) -> Option<Self> {
+        url.as_ref()
+            .strip_prefix(&format!("https://{}/$/rss/", ODYSEE_HOST))
+            .filter(|id| !id.is_empty())
+            .map(LbrySubscription::new)
+    }
+
+    /// Try to get the [LbrySubscription] from a url to a channel or video on odysee
+    /// (`https://odysee.com/@<name>:<n>`) or a lbry-uri (`lbry://@<name>#<n>`).
+    ///
+    /// This does not need any network requests.
+    pub fn from_url<S: AsRef<str>>(url: S) -> Option<Self> {
+        if let Some(subscription) = LbrySubscription::from_feed_url(url.as_ref()) {
+            return Some(subscription);
+        }
+
+        let url = url.as_ref();
+        let path = if let Some(uri) = url.strip_prefix("lbry://") {
+            uri.to_string()
+        } else {
+            let url = reqwest::Url::parse(url).ok()?;
+            if url.host_str()?.trim_start_matches("www.") != ODYSEE_HOST {
+                return None;
+            }
+            url.path().trim_start_matches('/').to_string()
+        };
+
+        let channel = path.split('/').next()?.replace('#', ":");
+        if channel.len() > 1 && channel.starts_with('@') {
+            Some(LbrySubscription::new(channel))
+        } else {
+            None
+        }
+    }
+
     pub fn id(&self) -> String {
         self.id.clone()
     }
 
     /// Try to get the channel name from the channel.
-    pub async fn update_name(&self, client: &reqwest::Client) -> Option<String> {
+    pub async fn update_name(&self, client: &dyn Transport) -> Option<String> {
         let errors = ErrorStore::new();
         let video_res = self.generate_with_client(&errors, client).await.next();
         if let Some(video) = video_res {
@@ -71,7 +120,7 @@ impl std::convert::TryFrom<Vec<String>> for LbrySubscription {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        if let Some(value) = strings.get(0) {
+        if let Some(value) = strings.first() {
             Ok(LbrySubscription::new(value))
         } else {
             Err(())
@@ -96,7 +145,7 @@ impl WithName for LbrySubscription {
 
 impl RssExtractor for LbrySubscription {
     fn feed_url(&self) -> String {
-        format!("https://odysee.com/$/rss/{}", self.id)
+        format!("https://{}/$/rss/{}", ODYSEE_HOST, self.id)
     }
 }
 
@@ -112,6 +161,10 @@ impl tf_core::Subscription for LbrySubscription {
     fn name(&self) -> Option<String> {
         self.name.clone()
     }
+
+    fn host(&self) -> Option<String> {
+        Some(ODYSEE_HOST.to_string())
+    }
 }
 
 #[async_trait::async_trait]
@@ -123,10 +176,40 @@ impl GeneratorWithClient for LbrySubscription {
     async fn generate_with_client(
         &self,
         errors: &tf_core::ErrorStore,
-        client: &reqwest::Client,
+        client: &dyn Transport,
     ) -> Self::Iterator {
         RssExtractorWrapper::<Self>::from(self)
             .generate_with_client(errors, client)
             .await
     }
+
+    async fn generate_incremental_with_client(
+        &self,
+        errors: &tf_core::ErrorStore,
+        client: &dyn Transport,
+        state: &RefreshState,
+    ) -> (Self::Iterator, RefreshState) {
+        RssExtractorWrapper::<Self>::from(self)
+            .generate_incremental_with_client(errors, client, state)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl PagedGenerator for LbrySubscription {
+    /// The rss-feed only contains the latest videos, therefore there is only the first page.
+    async fn generate_page_with_client(
+        &self,
+        errors: &tf_core::ErrorStore,
+        client: &dyn Transport,
+        cursor: Option<&PageCursor>,
+    ) -> Page<LbryVideo> {
+        if cursor.is_some() {
+            return Page::new(vec![], None);
+        }
+        Page::new(
+            self.generate_with_client(errors, client).await.collect(),
+            None,
+        )
+    }
 }
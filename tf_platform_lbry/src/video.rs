@@ -18,33 +18,32 @@
  */
 
 use async_trait::async_trait;
-use tf_core::{Subscription, Video, DATE_FORMAT};
+use tf_core::{Subscription, Video, VideoId, VideoMetadata, DATE_FORMAT};
 
 use crate::LbrySubscription;
 use tf_utils::rss::{FromItemAndSub, Item};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LbryVideo {
     pub(crate) url: String,
     pub(crate) title: String,
     pub(crate) uploaded: chrono::NaiveDateTime,
     pub(crate) subscription: LbrySubscription,
     pub(crate) thumbnail_url: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) metadata: VideoMetadata,
 }
 
 impl std::hash::Hash for LbryVideo {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.url.hash(state);
-        self.title.hash(state);
-        self.subscription.hash(state);
+        self.id().hash(state);
     }
 }
 
 impl std::cmp::PartialEq for LbryVideo {
     fn eq(&self, other: &Self) -> bool {
-        self.url == other.url
-            && self.title == other.title
-            && self.subscription == other.subscription
+        self.id() == other.id()
     }
 }
 
@@ -64,15 +63,22 @@ impl LbryVideo {
             uploaded,
             subscription,
             thumbnail_url: thumbnail_url.as_ref().to_owned(),
+            metadata: VideoMetadata::default(),
         }
     }
+
+    /// Set the [VideoMetadata] of the video.
+    pub fn with_metadata(mut self, metadata: VideoMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 impl std::convert::TryFrom<Vec<String>> for LbryVideo {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        let url_opt = strings.get(0);
+        let url_opt = strings.first();
         let title = strings.get(1);
         let uploaded = strings.get(2);
         let sub_name = strings.get(3);
@@ -83,7 +89,8 @@ impl std::convert::TryFrom<Vec<String>> for LbryVideo {
                 let upl_date = chrono::NaiveDateTime::parse_from_str(upl, DATE_FORMAT);
                 if let Ok(upl) = upl_date {
                     let sub = LbrySubscription::new_with_name(sub_i, sub_n);
-                    Ok(LbryVideo::new(url, tit, upl, sub, thu))
+                    Ok(LbryVideo::new(url, tit, upl, sub, thu)
+                        .with_metadata(VideoMetadata::from_strings(&strings[6..])))
                 } else {
                     Err(())
                 }
@@ -100,9 +107,10 @@ impl From<LbryVideo> for Vec<String> {
         result.push(video.title());
         result.push(video.uploaded().format(DATE_FORMAT).to_string());
         let sub = video.subscription();
-        result.push(sub.name().unwrap_or_else(|| "".to_string()));
+        result.push(sub.name().unwrap_or_default());
         result.push(sub.id());
         result.push(video.thumbnail_url());
+        result.append(&mut video.metadata.into_strings());
         result
     }
 }
@@ -120,7 +128,7 @@ impl tf_core::Video for LbryVideo {
     }
 
     fn uploaded(&self) -> chrono::NaiveDateTime {
-        self.uploaded.clone()
+        self.uploaded
     }
 
     fn subscription(&self) -> Self::Subscription {
@@ -130,16 +138,42 @@ impl tf_core::Video for LbryVideo {
     fn thumbnail_url(&self) -> String {
         self.thumbnail_url.clone()
     }
+
+    fn metadata(&self) -> VideoMetadata {
+        self.metadata.clone()
+    }
+
+    /// The claim id is the part after the last `:` or `#` of the url, e.g.
+    /// `https://odysee.com/@channel:1/video:2` has the claim id `2`.
+    fn id(&self) -> VideoId {
+        let claim = self
+            .url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .and_then(|name| name.rsplit_once([':', '#']))
+            .map(|(_, claim)| claim)
+            .filter(|claim| !claim.is_empty());
+
+        match claim {
+            Some(claim) => VideoId::Lbry(claim.to_string()),
+            None => VideoId::Url(self.url.clone()),
+        }
+    }
 }
 
 impl FromItemAndSub<LbrySubscription> for LbryVideo {
     fn from_item_and_sub(i: Item, sub: LbrySubscription) -> Self {
+        let metadata = VideoMetadata::new()
+            .with_duration(i.duration())
+            .with_description(Some(&i.description));
         Self {
             title: i.itunes_title,
             url: i.link,
             uploaded: i.pub_date,
             subscription: sub,
             thumbnail_url: i.itunes_image.href,
+            metadata,
         }
     }
 }
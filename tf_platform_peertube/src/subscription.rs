@@ -17,12 +17,22 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use tf_core::{ErrorStore, GeneratorWithClient, Video};
+use tf_core::{
+    ErrorContext, ErrorStore, GeneratorWithClient, HttpRequest, NetworkError, Page, PageCursor,
+    PagedGenerator, ParseError, RefreshState, Transport, Video,
+};
 use tf_utils::rss::{RssExtractor, RssExtractorWrapper, WithName};
 
 use crate::PTVideo;
 
-#[derive(Clone, Eq, Debug, Hash)]
+/// The path and query of the rss-feed url after the base url, followed by the id.
+const FEED_URL_INFIX: &str = "/feeds/videos.xml?videoChannelName=";
+
+/// The number of videos requested per page, see [PagedGenerator].
+const PAGE_SIZE: usize = 25;
+
+#[derive(Clone, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PTSubscription {
     id: String,
     base_url: String,
@@ -35,6 +45,13 @@ impl std::cmp::PartialEq for PTSubscription {
     }
 }
 
+impl std::hash::Hash for PTSubscription {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.base_url.hash(state);
+    }
+}
+
 impl PTSubscription {
     /// Create a new peertube subscription. The base url should be the url peertube is accessible at.
     /// The id should be in the format name@url (you will get that when copying the video channel id).
@@ -66,6 +83,72 @@ impl PTSubscription {
         }
     }
 
+    /// Get the [PTSubscription] from the url of the rss-feed, see [RssExtractor::feed_url].
+    pub fn from_feed_url<S: AsRef<str>>(url: S) -> Option<Self> {
+        let (base_url, id) = url.as_ref().split_once(FEED_URL_INFIX)?;
+        if base_url.is_empty() || id.is_empty() || id.contains('&') {
+            return None;
+        }
+        Some(PTSubscription::new(base_url, id))
+    }
+
+    /// Try to get the [PTSubscription] from any url to a PeerTube-Channel, -Account or -Video.
+    ///
+    /// Supported are channel urls (`/c/<name>`, `/video-channels/<name>`), account urls
+    /// (`/a/<name>`, `/accounts/<name>`), video urls (`/w/<id>`, `/videos/watch/<id>`) and feed urls.
+    /// Only channel urls are resolved without requesting the api of the instance.
+    /// For accounts, the first channel of the account is used.
+    pub async fn from_url<S: AsRef<str>>(url: S, client: &dyn Transport) -> Option<Self> {
+        if let Some(subscription) = PTSubscription::from_feed_url(url.as_ref()) {
+            return Some(subscription);
+        }
+
+        let url = reqwest::Url::parse(url.as_ref()).ok()?;
+        let host = url.host_str()?;
+        let base_url = match url.port() {
+            Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+            None => format!("{}://{}", url.scheme(), host),
+        };
+        let segments = url
+            .path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let channel = match segments.as_slice() {
+            ["c" | "video-channels", name, ..] => {
+                return Some(channel_subscription(&base_url, host, name, None))
+            }
+            ["a" | "accounts", name, ..] => {
+                let path = format!("accounts/{}/video-channels", name);
+                api_get(client, &base_url, &path)
+                    .await
+                    .ok()?
+                    .get("data")?
+                    .get(0)?
+                    .clone()
+            }
+            ["w", id, ..] | ["videos", "watch", id, ..] => {
+                let path = format!("videos/{}", id);
+                api_get(client, &base_url, &path)
+                    .await
+                    .ok()?
+                    .get("channel")?
+                    .clone()
+            }
+            _ => return None,
+        };
+
+        let name = channel.get("name")?.as_str()?;
+        let channel_host = channel.get("host").and_then(|h| h.as_str()).unwrap_or(host);
+        let display_name = channel.get("displayName").and_then(|n| n.as_str());
+        Some(channel_subscription(
+            &base_url,
+            channel_host,
+            name,
+            display_name,
+        ))
+    }
+
     pub fn id(&self) -> String {
         self.id.clone()
     }
@@ -75,7 +158,7 @@ impl PTSubscription {
     }
 
     /// Try to get the channel name from the channel.
-    pub async fn update_name(&self, client: &reqwest::Client) -> Option<String> {
+    pub async fn update_name(&self, client: &dyn Transport) -> Option<String> {
         let errors = ErrorStore::new();
         let video_res = self.generate_with_client(&errors, client).await.next();
         if let Some(video) = video_res {
@@ -90,7 +173,7 @@ impl std::convert::TryFrom<Vec<String>> for PTSubscription {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        if let (Some(id), Some(base_url)) = (strings.get(0), strings.get(1)) {
+        if let (Some(id), Some(base_url)) = (strings.first(), strings.get(1)) {
             Ok(PTSubscription::new(base_url, id))
         } else {
             Err(())
@@ -104,6 +187,47 @@ impl From<PTSubscription> for Vec<String> {
     }
 }
 
+/// Create a [PTSubscription] to the channel with the given name on the given host, fetched from the base url.
+fn channel_subscription(
+    base_url: &str,
+    host: &str,
+    name: &str,
+    display_name: Option<&str>,
+) -> PTSubscription {
+    let id = if name.contains('@') {
+        name.to_string()
+    } else {
+        format!("{}@{}", name, host)
+    };
+    match display_name {
+        Some(display_name) => PTSubscription::new_with_name(base_url, id, display_name),
+        None => PTSubscription::new(base_url, id),
+    }
+}
+
+/// Request the given path of the api of the instance at the base url.
+///
+/// On failure, the [ErrorContext] will contain the url and HTTP status if available.
+async fn api_get(
+    client: &dyn Transport,
+    base_url: &str,
+    path: &str,
+) -> Result<serde_json::Value, (tf_core::Error, ErrorContext)> {
+    let url = format!("{}/api/v1/{}", base_url, path);
+    let context = ErrorContext::new().with_url(&url);
+    let response = client.get(HttpRequest::new(&url)).await.map_err(|e| {
+        log::error!("Error getting {}: {}", url, e);
+        (e.into(), context.clone())
+    })?;
+    let context = context.with_status(response.status());
+    if !response.is_success() {
+        log::error!("Error getting {}: {}", url, response.status());
+        return Err((NetworkError(url).into(), context));
+    }
+    let body = response.text();
+    serde_json::from_str(&body).map_err(|_| (ParseError(body).into(), context))
+}
+
 impl WithName for PTSubscription {
     fn with_name<S: AsRef<str>>(&self, name: S) -> Self {
         Self {
@@ -116,10 +240,7 @@ impl WithName for PTSubscription {
 
 impl RssExtractor for PTSubscription {
     fn feed_url(&self) -> String {
-        format!(
-            "{}/feeds/videos.xml?videoChannelName={}",
-            self.base_url, self.id
-        )
+        format!("{}{}{}", self.base_url, FEED_URL_INFIX, self.id)
     }
 }
 
@@ -135,6 +256,12 @@ impl tf_core::Subscription for PTSubscription {
     fn name(&self) -> Option<String> {
         self.name.clone()
     }
+
+    fn host(&self) -> Option<String> {
+        reqwest::Url::parse(&self.base_url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+    }
 }
 
 #[async_trait::async_trait]
@@ -146,10 +273,67 @@ impl GeneratorWithClient for PTSubscription {
     async fn generate_with_client(
         &self,
         errors: &tf_core::ErrorStore,
-        client: &reqwest::Client,
+        client: &dyn Transport,
     ) -> Self::Iterator {
         RssExtractorWrapper::<Self>::from(self)
             .generate_with_client(errors, client)
             .await
     }
+
+    async fn generate_incremental_with_client(
+        &self,
+        errors: &tf_core::ErrorStore,
+        client: &dyn Transport,
+        state: &RefreshState,
+    ) -> (Self::Iterator, RefreshState) {
+        RssExtractorWrapper::<Self>::from(self)
+            .generate_incremental_with_client(errors, client, state)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl PagedGenerator for PTSubscription {
+    /// The pages are requested from the REST-api of the instance, the [PageCursor] being the
+    /// offset of the page.
+    async fn generate_page_with_client(
+        &self,
+        errors: &tf_core::ErrorStore,
+        client: &dyn Transport,
+        cursor: Option<&PageCursor>,
+    ) -> Page<PTVideo> {
+        let start = cursor
+            .and_then(|c| c.token().parse::<usize>().ok())
+            .unwrap_or_default();
+        let path = format!(
+            "video-channels/{}/videos?start={}&count={}&sort=-publishedAt",
+            self.id, start, PAGE_SIZE
+        );
+
+        let response = match api_get(client, &self.base_url, &path).await {
+            Ok(response) => response,
+            Err((error, context)) => {
+                errors.add_with_context(error, context.with_subscription(self));
+                return Page::new(vec![], None);
+            }
+        };
+
+        let videos = response
+            .get("data")
+            .and_then(|d| d.as_array())
+            .map(|d| {
+                d.iter()
+                    .filter_map(|v| PTVideo::from_api(v, self))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let total = response
+            .get("total")
+            .and_then(|t| t.as_u64())
+            .unwrap_or_default() as usize;
+        let end = start + PAGE_SIZE;
+        let next = (end < total).then(|| PageCursor::new(end.to_string()));
+
+        Page::new(videos, next)
+    }
 }
@@ -20,31 +20,35 @@
 use async_trait::async_trait;
 
 use crate::PTSubscription;
-use tf_core::{Subscription, Video, DATE_FORMAT};
-use tf_utils::rss::{FromItemAndSub, Item};
+use tf_core::{Subscription, Video, VideoId, VideoMetadata, DATE_FORMAT};
+use tf_utils::rss::{FromItemAndSub, Item, WithName};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PTVideo {
     pub(crate) url: String,
     pub(crate) title: String,
     pub(crate) uploaded: chrono::NaiveDateTime,
     pub(crate) subscription: PTSubscription,
     pub(crate) thumbnail_url: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) metadata: VideoMetadata,
 }
 
+/// The alphabet used by PeerTube for short UUIDs.
+const SHORT_UUID_ALPHABET: &[u8] = b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+/// The length of a short UUID.
+const SHORT_UUID_LENGTH: usize = 22;
+
 impl std::hash::Hash for PTVideo {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.url.hash(state);
-        self.title.hash(state);
-        self.subscription.hash(state);
+        self.id().hash(state);
     }
 }
 
 impl std::cmp::PartialEq for PTVideo {
     fn eq(&self, other: &Self) -> bool {
-        self.url == other.url
-            && self.title == other.title
-            && self.subscription == other.subscription
+        self.id() == other.id()
     }
 }
 
@@ -64,15 +68,22 @@ impl PTVideo {
             uploaded,
             subscription,
             thumbnail_url: thumbnail_url.as_ref().to_owned(),
+            metadata: VideoMetadata::default(),
         }
     }
+
+    /// Set the [VideoMetadata] of the video.
+    pub fn with_metadata(mut self, metadata: VideoMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 impl std::convert::TryFrom<Vec<String>> for PTVideo {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        let url_opt = strings.get(0);
+        let url_opt = strings.first();
         let title = strings.get(1);
         let uploaded = strings.get(2);
         let sub_name = strings.get(3);
@@ -92,7 +103,8 @@ impl std::convert::TryFrom<Vec<String>> for PTVideo {
                 let upl_date = chrono::NaiveDateTime::parse_from_str(upl, DATE_FORMAT);
                 if let Ok(upl) = upl_date {
                     let sub = PTSubscription::new_with_name(sub_u, sub_i, sub_n);
-                    Ok(PTVideo::new(url, tit, upl, sub, thu))
+                    Ok(PTVideo::new(url, tit, upl, sub, thu)
+                        .with_metadata(VideoMetadata::from_strings(&strings[7..])))
                 } else {
                     Err(())
                 }
@@ -109,10 +121,11 @@ impl From<PTVideo> for Vec<String> {
         result.push(video.title());
         result.push(video.uploaded().format(DATE_FORMAT).to_string());
         let sub = video.subscription();
-        result.push(sub.name().unwrap_or_else(|| "".to_string()));
+        result.push(sub.name().unwrap_or_default());
         result.push(sub.id());
         result.push(sub.base_url());
         result.push(video.thumbnail_url());
+        result.append(&mut video.metadata.into_strings());
         result
     }
 }
@@ -130,7 +143,7 @@ impl tf_core::Video for PTVideo {
     }
 
     fn uploaded(&self) -> chrono::NaiveDateTime {
-        self.uploaded.clone()
+        self.uploaded
     }
 
     fn subscription(&self) -> Self::Subscription {
@@ -140,10 +153,96 @@ impl tf_core::Video for PTVideo {
     fn thumbnail_url(&self) -> String {
         self.thumbnail_url.clone()
     }
+
+    fn metadata(&self) -> VideoMetadata {
+        self.metadata.clone()
+    }
+
+    fn id(&self) -> VideoId {
+        let without_scheme = self
+            .url
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(&self.url);
+        let (host, path) = without_scheme
+            .split_once('/')
+            .unwrap_or((without_scheme, ""));
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let id = path
+            .strip_prefix("w/")
+            .or_else(|| path.strip_prefix("videos/watch/"))
+            .map(|id| id.trim_end_matches('/'))
+            .filter(|id| !id.is_empty() && !id.contains('/'));
+
+        match id {
+            Some(id) => VideoId::Peertube {
+                host: host.to_lowercase(),
+                short_uuid: to_short_uuid(id),
+            },
+            None => VideoId::Url(self.url.clone()),
+        }
+    }
+}
+
+impl PTVideo {
+    /// Parse a video given by the REST-api of the instance of the [PTSubscription].
+    pub(crate) fn from_api(video: &serde_json::Value, sub: &PTSubscription) -> Option<Self> {
+        let str_field = |name: &str| video.get(name).and_then(|v| v.as_str());
+        let url = match (str_field("url"), str_field("shortUUID"), str_field("uuid")) {
+            (Some(url), _, _) => url.to_string(),
+            (None, Some(short_uuid), _) => format!("{}/w/{}", sub.base_url(), short_uuid),
+            (None, None, Some(uuid)) => format!("{}/videos/watch/{}", sub.base_url(), uuid),
+            _ => return None,
+        };
+        let uploaded = chrono::DateTime::parse_from_rfc3339(str_field("publishedAt")?)
+            .ok()?
+            .naive_utc();
+        let thumbnail_url = str_field("thumbnailPath")
+            .map(|p| format!("{}{}", sub.base_url(), p))
+            .unwrap_or_default();
+        let subscription = match video
+            .get("channel")
+            .and_then(|c| c.get("displayName"))
+            .and_then(|n| n.as_str())
+        {
+            Some(name) => sub.with_name(name),
+            None => sub.clone(),
+        };
+        let metadata = VideoMetadata::new()
+            .with_duration(
+                video
+                    .get("duration")
+                    .and_then(|d| d.as_u64())
+                    .filter(|d| d > &0)
+                    .map(std::time::Duration::from_secs),
+            )
+            .with_views(video.get("views").and_then(|v| v.as_u64()))
+            .with_description(str_field("description"))
+            .with_live(
+                video
+                    .get("isLive")
+                    .and_then(|l| l.as_bool())
+                    .unwrap_or_default(),
+            );
+
+        Some(
+            PTVideo::new(
+                url,
+                str_field("name")?.to_string(),
+                uploaded,
+                subscription,
+                thumbnail_url,
+            )
+            .with_metadata(metadata),
+        )
+    }
 }
 
 impl FromItemAndSub<PTSubscription> for PTVideo {
     fn from_item_and_sub(i: Item, sub: PTSubscription) -> Self {
+        let metadata = VideoMetadata::new()
+            .with_duration(i.duration())
+            .with_description(Some(&i.description));
         Self {
             title: i.media_title,
             url: i.link,
@@ -155,6 +254,32 @@ impl FromItemAndSub<PTSubscription> for PTVideo {
                 .next()
                 .map(|m| m.url)
                 .unwrap_or_default(),
+            metadata,
         }
     }
 }
+
+/// Convert a UUID into a short UUID as used by PeerTube.
+///
+/// Anything which is not a UUID, e.g. already a short UUID, is given back unchanged.
+fn to_short_uuid(id: &str) -> String {
+    let hex = id.replace('-', "");
+    let uuid = match u128::from_str_radix(&hex, 16) {
+        Ok(uuid) if id.len() == 36 && hex.len() == 32 => uuid,
+        _ => return id.to_string(),
+    };
+
+    let base = SHORT_UUID_ALPHABET.len() as u128;
+    let mut remaining = uuid;
+    let mut short_uuid = vec![];
+    while remaining > 0 {
+        short_uuid.push(SHORT_UUID_ALPHABET[(remaining % base) as usize]);
+        remaining /= base;
+    }
+    short_uuid.resize(
+        SHORT_UUID_LENGTH.max(short_uuid.len()),
+        SHORT_UUID_ALPHABET[0],
+    );
+    short_uuid.reverse();
+    String::from_utf8(short_uuid).unwrap_or_default()
+}
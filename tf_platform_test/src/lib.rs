@@ -21,25 +21,32 @@
 
 use std::convert::TryFrom;
 
-use tf_core::{ErrorStore, GeneratorWithClient, Subscription, Video};
+use tf_core::{
+    ErrorStore, GeneratorWithClient, Page, PageCursor, PagedGenerator, RefreshState, Subscription,
+    Transport, Video, VideoMetadata,
+};
 
 use async_trait::async_trait;
 
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TestVideo {
     title: String,
     uploaded: chrono::NaiveDateTime,
     subscription: TestSubscription,
+    #[cfg_attr(feature = "serde", serde(default))]
+    metadata: VideoMetadata,
 }
 
 impl TryFrom<Vec<String>> for TestVideo {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        let title = strings.get(0);
+        let title = strings.first();
         let sub_id = strings.get(1);
         match (title, sub_id) {
-            (Some(t), Some(s)) => Ok(TestVideo::new(t, TestSubscription::new(s))),
+            (Some(t), Some(s)) => Ok(TestVideo::new(t, TestSubscription::new(s))
+                .with_metadata(VideoMetadata::from_strings(&strings[2..]))),
             _ => Err(()),
         }
     }
@@ -47,11 +54,14 @@ impl TryFrom<Vec<String>> for TestVideo {
 
 impl From<TestVideo> for Vec<String> {
     fn from(video: TestVideo) -> Self {
-        vec![video.title, video.subscription.name]
+        let mut result = vec![video.title, video.subscription.name];
+        result.append(&mut video.metadata.into_strings());
+        result
     }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TestSubscription {
     name: String,
 }
@@ -60,7 +70,7 @@ impl Video for TestVideo {
     type Subscription = TestSubscription;
 
     fn url(&self) -> String {
-        format!("https://test.test/{}", self.title)
+        format!("https://test.test/{}/{}", self.subscription, self.title)
     }
 
     fn title(&self) -> String {
@@ -78,6 +88,10 @@ impl Video for TestVideo {
     fn thumbnail_url(&self) -> String {
         "".to_string()
     }
+
+    fn metadata(&self) -> VideoMetadata {
+        self.metadata.clone()
+    }
 }
 
 impl TestVideo {
@@ -89,8 +103,14 @@ impl TestVideo {
                 .unwrap()
                 .and_hms_opt(20, 10, 0)
                 .unwrap(),
+            metadata: VideoMetadata::default(),
         }
     }
+
+    pub fn with_metadata(mut self, metadata: VideoMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 impl std::fmt::Display for TestSubscription {
@@ -103,7 +123,7 @@ impl std::fmt::Display for TestSubscription {
 impl GeneratorWithClient for TestSubscription {
     type Item = TestVideo;
     type Iterator = std::vec::IntoIter<TestVideo>;
-    async fn generate_with_client(&self, _e: &ErrorStore, _c: &reqwest::Client) -> Self::Iterator {
+    async fn generate_with_client(&self, _e: &ErrorStore, _c: &dyn Transport) -> Self::Iterator {
         let video1 = TestVideo {
             title: "This is the test video 1".to_owned(),
             uploaded: chrono::NaiveDate::from_ymd_opt(2021, 8, 17)
@@ -111,6 +131,7 @@ impl GeneratorWithClient for TestSubscription {
                 .and_hms_opt(0, 0, 0)
                 .unwrap(),
             subscription: self.clone(),
+            metadata: VideoMetadata::default(),
         };
 
         let video2 = TestVideo {
@@ -120,10 +141,56 @@ impl GeneratorWithClient for TestSubscription {
                 .and_hms_opt(0, 0, 0)
                 .unwrap(),
             subscription: self.clone(),
+            metadata: VideoMetadata::default(),
         };
 
         vec![video1, video2].into_iter()
     }
+
+    async fn generate_incremental_with_client(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        state: &RefreshState,
+    ) -> (Self::Iterator, RefreshState) {
+        let (videos, next) = self
+            .generate_page_with_client(errors, client, None)
+            .await
+            .into_parts();
+        (videos.into_iter(), state.with_next_page(next))
+    }
+}
+
+/// The first page contains the videos of [GeneratorWithClient::generate_with_client],
+/// the second and last page contains one older video.
+#[async_trait]
+impl PagedGenerator for TestSubscription {
+    async fn generate_page_with_client(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        cursor: Option<&PageCursor>,
+    ) -> Page<TestVideo> {
+        match cursor.map(|c| c.token()) {
+            None => Page::new(
+                self.generate_with_client(errors, client).await.collect(),
+                Some(PageCursor::new("1")),
+            ),
+            Some("1") => {
+                let video = TestVideo {
+                    title: "This is the test video 0".to_owned(),
+                    uploaded: chrono::NaiveDate::from_ymd_opt(2020, 12, 1)
+                        .unwrap()
+                        .and_hms_opt(0, 0, 0)
+                        .unwrap(),
+                    subscription: self.clone(),
+                    metadata: VideoMetadata::default(),
+                };
+                Page::new(vec![video], None)
+            }
+            _ => Page::new(vec![], None),
+        }
+    }
 }
 
 impl Subscription for TestSubscription {
@@ -146,7 +213,7 @@ impl TryFrom<Vec<String>> for TestSubscription {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        if let Some(value) = strings.get(0) {
+        if let Some(value) = strings.first() {
             Ok(TestSubscription::new(value))
         } else {
             Err(())
@@ -23,7 +23,7 @@ use tf_core::{ErrorStore, Generator, Subscription, Video};
 use tf_yt::YTPipeline;
 use tf_yt::YTSubscription;
 
-const SUBSCRIPTION_IDS: &'static [&'static str] = &[
+const SUBSCRIPTION_IDS: &[&str] = &[
     "UCYO_jab_esuFRV4b17AJtAw",
     "UCKtix2xNNXdcEfEFnoOnvMw",
     "UCld68syR8Wi-GY_n4CaoJGA",
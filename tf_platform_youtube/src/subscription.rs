@@ -22,10 +22,22 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
-use piped::{ChannelSearchItem, PipedClient};
-use tf_core::{ErrorStore, GeneratorWithClient, Subscription, SubscriptionList};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use piped::{Channel, ChannelSearch, ChannelSearchItem, RelatedStream, StreamsPage, VideoInfo};
+use tf_core::{
+    ErrorContext, ErrorStore, FetchPolicy, Generator, GeneratorWithClient, HttpRequest, Merger,
+    NetworkError, Page, PageCursor, PagedGenerator, ParseError, RefreshState, StreamGenerator,
+    Subscription, SubscriptionList, Transport, Video,
+};
 
-const PIPED_API_URL: &'static str = "https://pipedapi.kavin.rocks";
+const PIPED_API_URL: &str = "https://pipedapi.kavin.rocks";
+
+/// The user agent sent to the piped-api.
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; rv:78.0) Gecko/20100101 Firefox/78.0";
+
+/// The prefix of the rss-feed url of a YouTube-Channel, followed by the channel id.
+const FEED_URL_PREFIX: &str = "https://www.youtube.com/feeds/videos.xml?channel_id=";
 
 fn piped_api_url() -> String {
     match std::env::var("PIPED_API_URL") {
@@ -35,7 +47,8 @@ fn piped_api_url() -> String {
 }
 
 /// A [`YTSubscription`] to a YouTube-Channel. The Youtube-Channel is referenced by the channel id.
-#[derive(Debug, Clone, Eq, Hash)]
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct YTSubscription {
     /// The channel id.
     id: String,
@@ -48,6 +61,12 @@ impl std::cmp::PartialEq for YTSubscription {
     }
 }
 
+impl std::hash::Hash for YTSubscription {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
 impl YTSubscription {
     /// Create a new [`YTSubscription`] using the given channel id.
     pub fn new(id: &str) -> Self {
@@ -69,36 +88,147 @@ impl YTSubscription {
     /// This will try to search the given query in youtube filtered only to channels
     /// and return the first result if it exists.
     pub async fn try_from_search<S: AsRef<str>>(query: S) -> Option<Self> {
+        YTSubscription::try_from_search_with_client(query, tf_core::default_transport().as_ref())
+            .await
+    }
+
+    /// Try to get a subscription using youtube search using the given [`Transport`],
+    /// see [`YTSubscription::try_from_search`].
+    pub async fn try_from_search_with_client<S: AsRef<str>>(
+        query: S,
+        client: &dyn Transport,
+    ) -> Option<Self> {
         log::debug!("Getting channel from query {}", query.as_ref());
-        let piped = PipedClient::new(&reqwest::Client::new(), piped_api_url());
-        let result = piped.search_channel(query).await;
+        let url = piped_url("search", &[("q", query.as_ref()), ("filter", "channels")]);
+        let result = piped_get::<ChannelSearch>(client, url).await;
         if let Ok(channel_search) = result {
             log::debug!(
                 "Got back a result with {} items",
                 channel_search.items.len()
             );
-            channel_search.items.get(0).map(|i| i.into())
+            channel_search.items.first().map(|i| i.into())
         } else {
-            log::error!("Got back a error: {}", result.err().unwrap());
+            log::error!("Got back a error: {}", result.err().unwrap().0);
             None
         }
     }
 
+    /// Get the [`YTSubscription`] from the url of the rss-feed of the channel, see [`YTSubscription::feed_url`].
+    pub fn from_feed_url<S: AsRef<str>>(url: S) -> Option<Self> {
+        url.as_ref()
+            .strip_prefix(FEED_URL_PREFIX)
+            .filter(|id| !id.is_empty() && !id.contains('&'))
+            .map(YTSubscription::new)
+    }
+
+    /// Try to get the [`YTSubscription`] from any url to a YouTube-Channel or -Video.
+    ///
+    /// Supported are channel urls (`/channel/<id>`, `/@<handle>`, `/c/<name>`, `/user/<name>`),
+    /// video urls (`/watch?v=<id>`, `/shorts/<id>`, `/live/<id>`, `youtu.be/<id>`) and feed urls.
+    /// Only channel urls containing the channel id are resolved without requesting the piped-api.
+    pub async fn from_url<S: AsRef<str>>(url: S, client: &dyn Transport) -> Option<Self> {
+        if let Some(subscription) = YTSubscription::from_feed_url(url.as_ref()) {
+            return Some(subscription);
+        }
+
+        let url = reqwest::Url::parse(url.as_ref()).ok()?;
+        let host = url.host_str()?.trim_start_matches("www.");
+        let segments = url
+            .path_segments()
+            .map(|s| s.filter(|s| !s.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        if host == "youtu.be" {
+            return YTSubscription::from_video_id(segments.first()?, client).await;
+        }
+        if !(host == "youtube.com" || host.ends_with(".youtube.com")) {
+            return None;
+        }
+
+        match segments.as_slice() {
+            ["channel", id, ..] => Some(YTSubscription::new(id)),
+            ["watch", ..] => {
+                let id = url
+                    .query_pairs()
+                    .find(|(k, _)| k == "v")
+                    .map(|(_, v)| v.to_string())?;
+                YTSubscription::from_video_id(&id, client).await
+            }
+            ["shorts" | "live" | "embed", id, ..] => {
+                YTSubscription::from_video_id(id, client).await
+            }
+            [handle, ..] if handle.starts_with('@') => {
+                YTSubscription::from_piped_channel(&format!("@/{}", &handle[1..]), client).await
+            }
+            ["c", name, ..] => {
+                YTSubscription::from_piped_channel(&format!("c/{}", name), client).await
+            }
+            ["user", name, ..] => {
+                YTSubscription::from_piped_channel(&format!("user/{}", name), client).await
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the [`YTSubscription`] of the uploader of the video with the given id.
+    async fn from_video_id(id: &str, client: &dyn Transport) -> Option<Self> {
+        match piped_get::<VideoInfo>(client, piped_url(&format!("streams/{}", id), &[])).await {
+            Ok(video) => video
+                .uploader_url
+                .strip_prefix("/channel/")
+                .map(|id| YTSubscription::new_with_name(id, &video.uploader)),
+            Err((e, _)) => {
+                log::error!("Error getting video {}: {}", id, e);
+                None
+            }
+        }
+    }
+
+    /// Get the [`YTSubscription`] of a channel using the given path of the piped-api, e.g. `c/<name>`.
+    async fn from_piped_channel(path: &str, client: &dyn Transport) -> Option<Self> {
+        let channel: serde_json::Value = piped_get(client, piped_url(path, &[])).await.ok()?;
+        let id = channel.get("id")?.as_str()?;
+        match channel.get("name").and_then(|n| n.as_str()) {
+            Some(name) => Some(YTSubscription::new_with_name(id, name)),
+            None => Some(YTSubscription::new(id)),
+        }
+    }
+
+    /// Get the url of the rss-feed of the channel.
+    ///
+    /// This is not used for generating [`YTVideo`]s, but to exchange subscriptions with other applications.
+    pub fn feed_url(&self) -> String {
+        format!("{}{}", FEED_URL_PREFIX, self.id)
+    }
+
     /// Get the channel id of the [`YTSubscription`].
     pub fn id(&self) -> String {
         self.id.clone()
     }
 
     /// Try to get the channel name from the channel id.
-    pub async fn update_name(&self, client: &reqwest::Client) -> Option<String> {
-        let piped = PipedClient::new(&client, piped_api_url());
-        if let Ok(channel) = piped.channel_from_id(&self.id).await {
+    pub async fn update_name(&self, client: &dyn Transport) -> Option<String> {
+        let url = piped_url(&format!("channel/{}", self.id), &[]);
+        if let Ok(channel) = piped_get::<Channel>(client, url).await {
             Some(channel.name)
         } else {
             None
         }
     }
 
+    /// Request the page of older videos of the channel at the given [`PageCursor`].
+    async fn channel_continuation(
+        &self,
+        client: &dyn Transport,
+        cursor: &PageCursor,
+    ) -> Result<StreamsPage, (tf_core::Error, ErrorContext)> {
+        let url = piped_url(
+            &format!("nextpage/channel/{}", self.id),
+            &[("nextpage", cursor.token())],
+        );
+        piped_get(client, url).await
+    }
+
     fn with_name(&self, name: &str) -> Self {
         Self {
             id: self.id.clone(),
@@ -114,6 +244,13 @@ impl Subscription for YTSubscription {
     fn name(&self) -> Option<String> {
         self.name.clone()
     }
+
+    /// Get the host of the piped-api the [`YTSubscription`] is fetched from.
+    fn host(&self) -> Option<String> {
+        reqwest::Url::parse(&piped_api_url())
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+    }
 }
 
 impl std::fmt::Display for YTSubscription {
@@ -126,7 +263,7 @@ impl std::convert::TryFrom<Vec<String>> for YTSubscription {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        if let Some(value) = strings.get(0) {
+        if let Some(value) = strings.first() {
             Ok(YTSubscription::new(value))
         } else {
             Err(())
@@ -140,72 +277,220 @@ impl From<YTSubscription> for Vec<String> {
     }
 }
 
+/// The [`SubscriptionList`] of [`YTSubscription`]s together with the [`Merger`] used to fetch them.
+///
+/// For few subscriptions, the [`YTSubscription`]s are fetched one by one using the [`Merger`],
+/// otherwise all of them are fetched at once using the bulk feed of piped.
 #[derive(Clone)]
-pub struct YTSubscriptionList(pub Arc<Mutex<SubscriptionList<YTSubscription>>>);
+pub struct YTSubscriptionList {
+    /// The list of [`YTSubscription`]s.
+    subscription_list: Arc<Mutex<SubscriptionList<YTSubscription>>>,
+    /// The [`Merger`] fetching the [`YTSubscription`]s and remembering their feeds.
+    merger: Merger<YTSubscription, YTVideo>,
+}
 
-#[async_trait]
-impl GeneratorWithClient for YTSubscriptionList {
-    type Item = YTVideo;
-    type Iterator = std::vec::IntoIter<Self::Item>;
+impl YTSubscriptionList {
+    /// Create a new [`YTSubscriptionList`] fetching the [`YTSubscription`]s of the given [`SubscriptionList`]
+    /// according to the given [`FetchPolicy`].
+    ///
+    /// The [`FetchPolicy`] applies to the requests of single [`YTSubscription`]s as well as to the
+    /// bulk request.
+    pub fn new(
+        subscription_list: Arc<Mutex<SubscriptionList<YTSubscription>>>,
+        fetch_policy: Arc<Mutex<FetchPolicy>>,
+    ) -> Self {
+        let merger = Merger::new_with_fetch_policy(subscription_list.clone(), fetch_policy);
+        YTSubscriptionList {
+            subscription_list,
+            merger,
+        }
+    }
 
-    async fn generate_with_client(
-        &self,
-        errors: &ErrorStore,
-        client: &reqwest::Client,
-    ) -> Self::Iterator {
-        let subs = self
-            .0
+    /// Get the [`Transport`] used to fetch the [`YTSubscription`]s.
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        self.merger.transport()
+    }
+
+    /// Set the [`Transport`] used to fetch the [`YTSubscription`]s.
+    ///
+    /// This will also alter the [`Transport`] of all clones of this [`YTSubscriptionList`].
+    pub fn set_transport(&self, transport: Arc<dyn Transport>) {
+        self.merger.set_transport(transport)
+    }
+
+    /// Get all [`YTSubscription`]s of the [`SubscriptionList`].
+    fn subscriptions(&self) -> Vec<YTSubscription> {
+        self.subscription_list
             .lock()
             .expect("Poisoned mutex: YT Subscription List")
-            .subscriptions();
-        let num_subs = subs.len();
+            .subscriptions()
+    }
+
+    /// Refresh the given [`YTSubscription`]s, see [`Merger::refresh_subscriptions`].
+    ///
+    /// Gives back for every [`YTSubscription`], in the same order, all [`YTVideo`]s of its feed
+    /// together with the [`YTVideo`]s that were not seen before.
+    pub async fn refresh_subscriptions(
+        &self,
+        errors: &ErrorStore,
+        subs: &[YTSubscription],
+    ) -> Vec<(Vec<YTVideo>, Vec<YTVideo>)> {
         // For few subs, generate the videos without bulk, as bulk often has none or few videos in
         // the case that the subscriptions did not upload for a longer while.
-        if num_subs <= 10 {
-            let mut videos = vec![];
-            for s in subs {
-                videos.append(&mut s.generate_with_client(errors, client).await.collect());
-            }
-            return videos.into_iter();
+        if subs.len() <= 10 {
+            return self.merger.refresh_subscriptions(errors, subs).await;
         }
 
+        let feeds = match self.generate_bulk(errors, subs).await {
+            Some(feeds) => feeds,
+            None => return subs.iter().map(|_| (vec![], vec![])).collect(),
+        };
+        subs.iter()
+            .zip(feeds)
+            .map(|(s, feed)| {
+                let new = self.merger.record_feed(s, &feed);
+                (feed, new)
+            })
+            .collect()
+    }
+
+    /// Fetch the [`Page`]s of older [`YTVideo`]s of the given [`YTSubscription`]s,
+    /// see [`Merger::fetch_pages`].
+    pub async fn fetch_pages(
+        &self,
+        errors: &ErrorStore,
+        pages: &[(YTSubscription, Option<PageCursor>)],
+    ) -> Vec<(Page<YTVideo>, bool)> {
+        self.merger.fetch_pages(errors, pages).await
+    }
+
+    /// Get the [`RefreshState`] of the given [`YTSubscription`] from the last fetch,
+    /// see [`Merger::refresh_state`].
+    pub fn refresh_state(&self, subscription: &YTSubscription) -> Option<RefreshState> {
+        self.merger.refresh_state(subscription)
+    }
+
+    /// Refresh all [`YTSubscription`]s, giving back only the [`YTVideo`]s that were not seen
+    /// before, see [`Merger::refresh`].
+    pub async fn refresh(&self, errors: &ErrorStore) -> std::vec::IntoIter<YTVideo> {
+        let subs = self.subscriptions();
+        let mut videos = self
+            .refresh_subscriptions(errors, &subs)
+            .await
+            .into_iter()
+            .flat_map(|(_feed, new)| new)
+            .collect::<Vec<_>>();
+        videos.sort_by_key(|v| std::cmp::Reverse(v.uploaded()));
+        videos.into_iter()
+    }
+
+    /// Generate the [`YTVideo`]s of only the given [`YTSubscription`]s, sorted newest first.
+    pub async fn generate_subscriptions(
+        &self,
+        errors: &ErrorStore,
+        subs: &[YTSubscription],
+    ) -> std::vec::IntoIter<YTVideo> {
+        let mut videos = self
+            .refresh_subscriptions(errors, subs)
+            .await
+            .into_iter()
+            .flat_map(|(feed, _new)| feed)
+            .collect::<Vec<_>>();
+        videos.sort_by_key(|v| std::cmp::Reverse(v.uploaded()));
+        videos.into_iter()
+    }
+
+    /// Generate the [`YTVideo`]s of all given [`YTSubscription`]s at once using the bulk feed of piped.
+    ///
+    /// Gives back the [`YTVideo`]s per [`YTSubscription`], in the same order, or [`None`] if
+    /// the request failed.
+    async fn generate_bulk(
+        &self,
+        errors: &ErrorStore,
+        subs: &[YTSubscription],
+    ) -> Option<Vec<Vec<YTVideo>>> {
         log::debug!(
             "Generating YT videos from channels {:?}",
             subs.iter().map(|s| s.name().unwrap_or_else(|| s.id()))
         );
 
-        let piped = PipedClient::new(client, piped_api_url());
-        let videos_res = piped.bulk_feed(subs.iter().map(|s| s.id())).await;
-
-        if let Err(e) = &videos_res {
-            log::error!(
-                "Error generating youtube videos from subscriptions {:?}: {}",
-                subs,
-                e
-            );
-            for _ in 0..num_subs {
-                errors.add(piped_to_tubefeeder_error(e));
+        let ids = subs.iter().map(|s| s.id()).collect::<Vec<_>>().join(",");
+        let url = piped_url("feed/unauthenticated", &[("channels", &ids)]);
+        let host = subs.first().and_then(|s| s.host());
+        let videos_res = self
+            .merger
+            .fetch_with_policy(host.as_deref(), |client| {
+                let url = url.clone();
+                async move { piped_get::<Vec<RelatedStream>>(client.as_ref(), url).await }
+            })
+            .await;
+
+        let videos = match videos_res {
+            Ok(videos) => videos,
+            Err((error, context)) => {
+                log::error!(
+                    "Error generating youtube videos from subscriptions {:?}: {}",
+                    subs,
+                    error
+                );
+                for s in subs {
+                    errors.add_with_context(error.clone(), context.clone().with_subscription(s));
+                    errors.record_refresh(s, true);
+                }
+                return None;
+            }
+        };
+        subs.iter().for_each(|s| errors.record_refresh(s, false));
+
+        let mut feeds: HashMap<String, Vec<YTVideo>> =
+            subs.iter().map(|s| (s.id(), vec![])).collect();
+        for v in videos {
+            let id = v.uploader_url.strip_prefix("/channel/").unwrap_or_default();
+            let subscription = subs
+                .iter()
+                .find(|s| s.id == id)
+                .expect("YTVideo got unknown channel uploader id")
+                .with_name(&v.uploader_name);
+            let video = YTVideo::from_related_stream(errors, &v, subscription);
+            if let Some(feed) = feeds.get_mut(id) {
+                feed.push(video);
             }
-            return vec![].into_iter();
         }
+        Some(
+            subs.iter()
+                .map(|s| feeds.remove(&s.id()).unwrap_or_default())
+                .collect(),
+        )
+    }
+}
 
-        let videos = videos_res.unwrap();
+#[async_trait]
+impl Generator for YTSubscriptionList {
+    type Item = YTVideo;
+    type Iterator = std::vec::IntoIter<Self::Item>;
 
-        let map_id_to_subscription: HashMap<String, YTSubscription> =
-            subs.iter().map(|s| (s.id(), s.clone())).collect();
+    async fn generate(&self, errors: &ErrorStore) -> Self::Iterator {
+        let subs = self.subscriptions();
+        self.generate_subscriptions(errors, &subs).await
+    }
+}
+
+impl StreamGenerator for YTSubscriptionList {
+    type Item = YTVideo;
+
+    /// Generate one batch per [`YTSubscription`] for few subscriptions, otherwise one batch
+    /// of the bulk feed (see [`YTSubscriptionList::refresh_subscriptions`]).
+    fn generate_stream<'a>(&'a self, errors: &'a ErrorStore) -> BoxStream<'a, Vec<YTVideo>> {
+        let subs = self.subscriptions();
 
-        Box::new(videos.into_iter().map(|v| {
-            YTVideo::from_related_stream(
-                errors,
-                &v,
-                map_id_to_subscription
-                    .get(v.uploader_url.strip_prefix("/channel/").unwrap_or_default())
-                    .expect("YTVideo got unknown channel uploader id")
-                    .with_name(&v.uploader_name),
-            )
-        }))
-        .collect::<Vec<YTVideo>>()
-        .into_iter()
+        if subs.len() > 10 {
+            return futures::stream::once(async move {
+                self.generate_subscriptions(errors, &subs).await.collect()
+            })
+            .boxed();
+        }
+
+        self.merger.generate_stream(errors)
     }
 }
 
@@ -217,53 +502,124 @@ impl GeneratorWithClient for YTSubscription {
     async fn generate_with_client(
         &self,
         errors: &ErrorStore,
-        client: &reqwest::Client,
+        client: &dyn Transport,
     ) -> Self::Iterator {
-        log::debug!(
-            "Generating YT videos from channel {}",
-            self.name().unwrap_or_else(|| self.id())
-        );
-
-        let piped = PipedClient::new(client, piped_api_url());
-        let channel_res = piped.channel_from_id(self.id.clone()).await;
+        let (videos, _) = self
+            .generate_page_with_client(errors, client, None)
+            .await
+            .into_parts();
+        videos.into_iter()
+    }
 
-        if let Err(e) = &channel_res {
-            log::error!(
-                "Error generating youtube videos from subscription {:?}: {}",
-                self,
-                e
-            );
-            errors.add(piped_to_tubefeeder_error(e));
-            return vec![].into_iter();
-        }
+    /// The feed is the first page, remember the [`PageCursor`] to the next one.
+    async fn generate_incremental_with_client(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        state: &RefreshState,
+    ) -> (Self::Iterator, RefreshState) {
+        let (videos, next) = self
+            .generate_page_with_client(errors, client, None)
+            .await
+            .into_parts();
+        (videos.into_iter(), state.with_next_page(next))
+    }
+}
 
-        let channel = channel_res.unwrap();
-        let videos = channel.related_streams;
-        let name = channel.name;
+#[async_trait]
+impl PagedGenerator for YTSubscription {
+    /// The first page is the channel itself, the following pages are requested using the
+    /// `nextpage`-token of piped.
+    async fn generate_page_with_client(
+        &self,
+        errors: &ErrorStore,
+        client: &dyn Transport,
+        cursor: Option<&PageCursor>,
+    ) -> Page<YTVideo> {
+        log::debug!(
+            "Generating YT videos from channel {} at page {:?}",
+            self.name().unwrap_or_else(|| self.id()),
+            cursor
+        );
 
-        Box::new(
-            videos
-                .into_iter()
-                .map(|v| YTVideo::from_related_stream(errors, &v, self.with_name(&name))),
-        )
-        .collect::<Vec<YTVideo>>()
-        .into_iter()
+        let page_res = match cursor {
+            None => piped_get::<Channel>(client, piped_url(&format!("channel/{}", self.id), &[]))
+                .await
+                .map(|c| (Some(c.name), c.related_streams, c.nextpage)),
+            Some(cursor) => self
+                .channel_continuation(client, cursor)
+                .await
+                .map(|p| (None, p.related_streams, p.nextpage)),
+        };
+
+        let (name, videos, nextpage) = match page_res {
+            Ok(page) => page,
+            Err((error, context)) => {
+                log::error!(
+                    "Error generating youtube videos from subscription {:?}: {}",
+                    self,
+                    error
+                );
+                errors.add_with_context(error, context.with_subscription(self));
+                return Page::new(vec![], None);
+            }
+        };
+
+        let videos = videos
+            .into_iter()
+            .map(|v| {
+                let name = name.as_deref().unwrap_or(&v.uploader_name);
+                YTVideo::from_related_stream(errors, &v, self.with_name(name))
+            })
+            .collect::<Vec<YTVideo>>();
+        // Piped may give back a token even for the last, empty page.
+        let next = nextpage.filter(|_| !videos.is_empty()).map(PageCursor::new);
+        Page::new(videos, next)
     }
 }
 
 impl From<&ChannelSearchItem> for YTSubscription {
     fn from(item: &ChannelSearchItem) -> Self {
         Self {
-            id: item.url.split('/').last().unwrap_or_default().to_string(),
+            id: item
+                .url
+                .split('/')
+                .next_back()
+                .unwrap_or_default()
+                .to_string(),
             name: Some(item.name.to_string()),
         }
     }
 }
 
-fn piped_to_tubefeeder_error(error: &piped::Error) -> tf_core::Error {
-    match error {
-        piped::Error::Network(e) => tf_core::NetworkError(e.to_string()).into(),
-        piped::Error::ParseResponse(e) => tf_core::ParseError(e.to_string()).into(),
-        piped::Error::Parseurl(e) => tf_core::ParseError(e.to_string()).into(),
+/// Build the url of the given path of the piped-api with the given query.
+fn piped_url(path: &str, query: &[(&str, &str)]) -> String {
+    let url = format!("{}/{}", piped_api_url().trim_end_matches('/'), path);
+    if query.is_empty() {
+        return url;
+    }
+    match reqwest::Url::parse_with_params(&url, query) {
+        Ok(url) => url.to_string(),
+        Err(_) => url,
+    }
+}
+
+/// Request the given url of the piped-api using the [`Transport`] and parse the response.
+///
+/// On failure, the [`ErrorContext`] will contain the url and HTTP status if available.
+async fn piped_get<T: serde::de::DeserializeOwned>(
+    client: &dyn Transport,
+    url: String,
+) -> Result<T, (tf_core::Error, ErrorContext)> {
+    let context = ErrorContext::new().with_url(&url);
+    let request = HttpRequest::new(&url).with_header("User-Agent", USER_AGENT);
+    let response = client
+        .get(request)
+        .await
+        .map_err(|e| (e.into(), context.clone()))?;
+    let context = context.with_status(response.status());
+    if !response.is_success() {
+        return Err((NetworkError(url).into(), context));
     }
+    serde_json::from_slice(response.body()).map_err(|e| (ParseError(e.to_string()).into(), context))
 }
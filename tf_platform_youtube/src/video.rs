@@ -19,34 +19,35 @@
 
 use crate::subscription::YTSubscription;
 
+use std::time::Duration;
+
 use async_trait::async_trait;
 use piped::RelatedStream;
-use tf_core::{ErrorStore, Subscription, Video, DATE_FORMAT};
+use tf_core::{ErrorStore, Subscription, Video, VideoId, VideoMetadata, DATE_FORMAT};
 
-const YOUTUBE_URL: &'static str = "https://www.youtube.com";
+const YOUTUBE_URL: &str = "https://www.youtube.com";
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct YTVideo {
     pub(crate) url: String,
     pub(crate) title: String,
     pub(crate) uploaded: chrono::NaiveDateTime,
     pub(crate) subscription: YTSubscription,
     pub(crate) thumbnail_url: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) metadata: VideoMetadata,
 }
 
 impl std::hash::Hash for YTVideo {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.url.hash(state);
-        self.title.hash(state);
-        self.subscription.hash(state);
+        self.id().hash(state);
     }
 }
 
 impl std::cmp::PartialEq for YTVideo {
     fn eq(&self, other: &Self) -> bool {
-        self.url == other.url
-            && self.title == other.title
-            && self.subscription == other.subscription
+        self.id() == other.id()
     }
 }
 
@@ -66,15 +67,22 @@ impl YTVideo {
             uploaded,
             subscription,
             thumbnail_url: thumbnail_url.as_ref().to_owned(),
+            metadata: VideoMetadata::default(),
         }
     }
+
+    /// Set the [VideoMetadata] of the video.
+    pub fn with_metadata(mut self, metadata: VideoMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 impl std::convert::TryFrom<Vec<String>> for YTVideo {
     type Error = ();
 
     fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
-        let url_opt = strings.get(0);
+        let url_opt = strings.first();
         let title = strings.get(1);
         let uploaded = strings.get(2);
         let sub_name = strings.get(3);
@@ -85,7 +93,8 @@ impl std::convert::TryFrom<Vec<String>> for YTVideo {
                 let upl_date = chrono::NaiveDateTime::parse_from_str(upl, DATE_FORMAT);
                 if let Ok(upl) = upl_date {
                     let sub = YTSubscription::new_with_name(sub_i, sub_n);
-                    Ok(YTVideo::new(url, tit, upl, sub, thu))
+                    Ok(YTVideo::new(url, tit, upl, sub, thu)
+                        .with_metadata(VideoMetadata::from_strings(&strings[6..])))
                 } else {
                     Err(())
                 }
@@ -102,9 +111,10 @@ impl From<YTVideo> for Vec<String> {
         result.push(video.title());
         result.push(video.uploaded().format(DATE_FORMAT).to_string());
         let sub = video.subscription();
-        result.push(sub.name().unwrap_or_else(|| "".to_string()));
+        result.push(sub.name().unwrap_or_default());
         result.push(sub.id());
         result.push(video.thumbnail_url());
+        result.append(&mut video.metadata.into_strings());
 
         result
     }
@@ -130,12 +140,37 @@ impl tf_core::Video for YTVideo {
         self.thumbnail_url.clone()
     }
 
+    fn metadata(&self) -> VideoMetadata {
+        self.metadata.clone()
+    }
+
+    fn id(&self) -> VideoId {
+        self.video_id()
+            .map(VideoId::Youtube)
+            .unwrap_or_else(|| VideoId::Url(self.url.clone()))
+    }
+
     fn uploaded(&self) -> chrono::NaiveDateTime {
         self.uploaded
     }
 }
 
 impl YTVideo {
+    /// Extract the YouTube video id from the url of the video.
+    fn video_id(&self) -> Option<String> {
+        let (path, query) = self.url.split_once('?').unwrap_or((&self.url, ""));
+        query
+            .split('&')
+            .find_map(|p| p.strip_prefix("v="))
+            .or_else(|| {
+                ["/shorts/", "/live/", "/embed/", "youtu.be/"]
+                    .iter()
+                    .find_map(|p| path.split_once(p).map(|(_, id)| id))
+            })
+            .map(|id| id.trim_end_matches('/').to_string())
+            .filter(|id| !id.is_empty())
+    }
+
     pub(crate) fn from_related_stream(
         _errors: &ErrorStore,
         v: &RelatedStream,
@@ -145,9 +180,16 @@ impl YTVideo {
             url: format!("{}/{}", YOUTUBE_URL, v.url),
             title: v.title.clone(),
             subscription,
-            uploaded: chrono::NaiveDateTime::from_timestamp_opt(v.uploaded / 1000, 0)
+            uploaded: chrono::DateTime::from_timestamp(v.uploaded / 1000, 0)
+                .map(|d| d.naive_utc())
                 .unwrap_or_default(),
             thumbnail_url: v.thumbnail.clone(),
+            // Piped gives a negative duration for livestreams.
+            metadata: VideoMetadata::new()
+                .with_duration(u64::try_from(v.duration).ok().map(Duration::from_secs))
+                .with_views(u64::try_from(v.views).ok())
+                .with_live(v.duration < 0)
+                .with_short(v.url.starts_with("/shorts/")),
         }
     }
 }
@@ -64,6 +64,20 @@ where
     pub fn get(&self, item: &T) -> Option<&T> {
         self.playlist.iter().find(|&i| i == item)
     }
+
+    /// Replace the item equal to the given one, e.g. after its title was edited.
+    ///
+    /// The position in the playlist is kept. Gives back whether the item was contained in the playlist.
+    pub fn update(&mut self, item: &T) -> bool {
+        if let Some(existing) = self.playlist.iter_mut().find(|i| *i == item) {
+            log::debug!("Updating item in playlist");
+            *existing = item.clone();
+            self.observers.notify(PlaylistEvent::Update(item.clone()));
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl<T> Default for Playlist<T>
@@ -79,6 +93,8 @@ where
 pub enum PlaylistEvent<T> {
     Add(T),
     Remove(T),
+    /// The item was replaced by a updated version, see [Playlist::update].
+    Update(T),
 }
 
 impl<T> Observable<PlaylistEvent<T>> for Playlist<T>
@@ -87,14 +103,14 @@ where
 {
     fn attach(
         &mut self,
-        observer: std::sync::Weak<Mutex<Box<(dyn Observer<PlaylistEvent<T>> + Send + 'static)>>>,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<PlaylistEvent<T>> + Send + 'static>>>,
     ) {
         self.observers.attach(observer);
     }
 
     fn detach(
         &mut self,
-        observer: std::sync::Weak<Mutex<Box<(dyn Observer<PlaylistEvent<T>> + Send + 'static)>>>,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<PlaylistEvent<T>> + Send + 'static>>>,
     ) {
         self.observers.detach(observer);
     }
@@ -118,4 +134,29 @@ mod test {
         playlist.toggle(&"Item2");
         assert_eq!(playlist.len(), 1);
     }
+
+    /// A item only identified by its id.
+    #[derive(Clone, Debug)]
+    struct Item(u32, &'static str);
+
+    impl PartialEq for Item {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Item {}
+
+    #[test]
+    fn playlist_update() {
+        let mut playlist: Playlist<Item> = Playlist::new();
+        playlist.toggle(&Item(1, "Title"));
+        playlist.toggle(&Item(2, "Other"));
+
+        assert!(playlist.update(&Item(1, "Edited Title")));
+        assert!(!playlist.update(&Item(3, "Unknown")));
+
+        assert_eq!(playlist.len(), 2);
+        assert_eq!(playlist.iter().next().unwrap().1, "Edited Title");
+    }
 }
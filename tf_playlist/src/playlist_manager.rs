@@ -41,6 +41,13 @@ where
         }
     }
 
+    fn update(&mut self, item: &T) -> usize {
+        self.playlists
+            .values_mut()
+            .filter_map(|p| p.update(item).then_some(()))
+            .count()
+    }
+
     fn items(&self, ident: &I) -> Vec<&T> {
         if let Some(playlist) = self.playlists.get(ident.borrow()) {
             playlist.iter().collect()
@@ -51,7 +58,7 @@ where
 
     fn attach_at(
         &mut self,
-        observer: std::sync::Weak<Mutex<Box<(dyn Observer<PlaylistEvent<T>> + Send + 'static)>>>,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<PlaylistEvent<T>> + Send + 'static>>>,
         ident: &I,
     ) {
         if let Some(playlist) = self.playlists.get_mut(ident.borrow()) {
@@ -67,7 +74,7 @@ where
 
     fn detach_at(
         &mut self,
-        observer: std::sync::Weak<Mutex<Box<(dyn Observer<PlaylistEvent<T>> + Send + 'static)>>>,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<PlaylistEvent<T>> + Send + 'static>>>,
         ident: &I,
     ) {
         if let Some(playlist) = self.playlists.get_mut(ident.borrow()) {
@@ -91,6 +98,13 @@ where
         self.playlists.lock().unwrap().toggle(ident, item);
     }
 
+    /// Replace the item equal to the given one in every playlist, see [Playlist::update].
+    ///
+    /// Gives back the number of playlists containing the item.
+    pub fn update(&mut self, item: &T) -> usize {
+        self.playlists.lock().unwrap().update(item)
+    }
+
     pub fn items(&self, ident: &I) -> Vec<T> {
         self.playlists
             .lock()
@@ -103,7 +117,7 @@ where
 
     pub fn attach_at(
         &mut self,
-        observer: std::sync::Weak<Mutex<Box<(dyn Observer<PlaylistEvent<T>> + Send + 'static)>>>,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<PlaylistEvent<T>> + Send + 'static>>>,
         ident: &I,
     ) {
         self.playlists.lock().unwrap().attach_at(observer, ident);
@@ -111,7 +125,7 @@ where
 
     pub fn detach_at(
         &mut self,
-        observer: std::sync::Weak<Mutex<Box<(dyn Observer<PlaylistEvent<T>> + Send + 'static)>>>,
+        observer: std::sync::Weak<Mutex<Box<dyn Observer<PlaylistEvent<T>> + Send + 'static>>>,
         ident: &I,
     ) {
         self.playlists.lock().unwrap().detach_at(observer, ident);
@@ -141,7 +155,7 @@ mod test {
 
         assert_eq!(manager.playlists.len(), 1);
 
-        assert!(manager.playlists.get(&"Playlist1").is_some());
+        assert!(manager.playlists.contains_key(&"Playlist1"));
         assert_eq!(manager.playlists.get(&"Playlist1").unwrap().len(), 1);
         assert!(manager
             .playlists
@@ -162,9 +176,22 @@ mod test {
 
         assert_eq!(manager.playlists.len(), 2);
 
-        assert!(manager.playlists.get(&"Playlist1").is_some());
-        assert!(manager.playlists.get(&"Playlist2").is_some());
+        assert!(manager.playlists.contains_key(&"Playlist1"));
+        assert!(manager.playlists.contains_key(&"Playlist2"));
         assert_eq!(manager.playlists.get(&"Playlist1").unwrap().len(), 2);
         assert_eq!(manager.playlists.get(&"Playlist2").unwrap().len(), 1);
     }
+
+    #[test]
+    fn playlistmanagerinternal_update() {
+        let mut manager: PlaylistManagerInternal<&str, &str> = PlaylistManagerInternal::new();
+
+        manager.toggle(&"Playlist1", &"Item1_1");
+        manager.toggle(&"Playlist2", &"Item1_1");
+        manager.toggle(&"Playlist2", &"Item1_2");
+
+        assert_eq!(manager.update(&"Item1_1"), 2);
+        assert_eq!(manager.update(&"Item1_2"), 1);
+        assert_eq!(manager.update(&"Item1_3"), 0);
+    }
 }
@@ -9,7 +9,5 @@ pub fn timeago_parser<S: AsRef<str>>(
 ) -> Result<chrono::NaiveDateTime, tf_core::ParseError> {
     let duration_ago = parse_duration::parse(date.as_ref())
         .map_err(|_| tf_core::ParseError("Parsing date".to_string()))?;
-    return Ok(
-        chrono::Local::now().naive_local() - chrono::Duration::from_std(duration_ago).unwrap()
-    );
+    Ok(chrono::Local::now().naive_local() - chrono::Duration::from_std(duration_ago).unwrap())
 }
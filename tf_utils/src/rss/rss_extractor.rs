@@ -17,7 +17,10 @@
  * along with Tubefeeder-extractor.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use tf_core::{GeneratorWithClient, NetworkError, ParseError, Subscription, Video};
+use tf_core::{
+    ErrorContext, GeneratorWithClient, HttpRequest, NetworkError, ParseError, RefreshState,
+    Subscription, Transport, Video,
+};
 
 use crate::rss::Item;
 
@@ -55,7 +58,7 @@ pub trait RssExtractor {
 #[async_trait::async_trait]
 impl<S, V> GeneratorWithClient for RssExtractorWrapper<S>
 where
-    S: Subscription<Video = V> + WithName + RssExtractor,
+    S: 'static + Subscription<Video = V> + WithName + RssExtractor,
     V: Video<Subscription = S> + FromItemAndSub<S>,
 {
     type Item = V;
@@ -65,16 +68,34 @@ where
     async fn generate_with_client(
         &self,
         errors: &tf_core::ErrorStore,
-        client: &reqwest::Client,
+        client: &dyn Transport,
     ) -> Self::Iterator {
-        let rss_res = parse_rss_from_url(&self.0.feed_url(), client).await;
+        self.generate_incremental_with_client(errors, client, &RefreshState::new())
+            .await
+            .0
+    }
 
-        if rss_res.is_err() {
-            errors.add(rss_res.err().unwrap());
-            return vec![].into_iter();
+    async fn generate_incremental_with_client(
+        &self,
+        errors: &tf_core::ErrorStore,
+        client: &dyn Transport,
+        state: &RefreshState,
+    ) -> (Self::Iterator, RefreshState) {
+        let rss_res = parse_rss_from_url(&self.0.feed_url(), client, state).await;
+
+        if let Err((error, context)) = rss_res {
+            errors.add_with_context(error, context.with_subscription(&self.0));
+            return (vec![].into_iter(), state.clone());
         }
 
-        let rss = rss_res.unwrap();
+        let (rss_opt, new_state) = rss_res.unwrap();
+
+        let rss = if let Some(rss) = rss_opt {
+            rss
+        } else {
+            log::debug!("Feed {} was not modified", self.0.feed_url());
+            return (vec![].into_iter(), new_state);
+        };
 
         let name = rss.channel.itunes_author;
         let items = rss.channel.items;
@@ -84,27 +105,55 @@ where
             .map(|i| V::from_item_and_sub(i, self.0.with_name(&name)))
             .collect();
 
-        items_pt_video.into_iter()
+        (items_pt_video.into_iter(), new_state)
     }
 }
 
-async fn parse_rss_from_url(url: &str, client: &reqwest::Client) -> Result<Rss, tf_core::Error> {
-    let response = client.get(url.clone()).send().await;
+/// Get and parse the [Rss] from the given url.
+///
+/// The validators of the given [RefreshState] will be used to send a conditional request.
+/// If the feed was not modified since, `None` is given back together with the unchanged [RefreshState].
+/// On failure, the [ErrorContext] will contain the url and HTTP status if available.
+async fn parse_rss_from_url(
+    url: &str,
+    client: &dyn Transport,
+    state: &RefreshState,
+) -> Result<(Option<Rss>, RefreshState), (tf_core::Error, ErrorContext)> {
+    let context = ErrorContext::new().with_url(url);
+    let mut request = HttpRequest::new(url);
+    if let Some(etag) = state.etag() {
+        request = request.with_header("If-None-Match", etag);
+    }
+    if let Some(last_modified) = state.last_modified() {
+        request = request.with_header("If-Modified-Since", last_modified);
+    }
+
+    let response = client.get(request).await;
 
     if response.is_err() {
         log::error!("Error getting {:?}", url);
-        return Err(NetworkError(url.to_string()).into());
+        return Err((NetworkError(url.to_string()).into(), context));
     }
 
-    let body_res = response.unwrap().text().await;
+    let response = response.unwrap();
+    let status = response.status();
 
-    if body_res.is_err() {
-        log::error!("Error getting {:?}", url);
-        return Err(NetworkError(url.to_string()).into());
+    if status == 304 {
+        return Ok((None, state.clone()));
+    }
+
+    let context = context.with_status(status);
+
+    if !response.is_success() {
+        log::error!("Error getting {:?}: {}", url, status);
+        return Err((NetworkError(url.to_string()).into(), context));
     }
 
-    let body_parsable = body_res
-        .unwrap()
+    let header = |name| response.header(name).map(|v| v.to_string());
+    let new_state = state.with_validators(header("ETag"), header("Last-Modified"));
+
+    let body_parsable = response
+        .text()
         .replace("media:", "media/")
         .replace("itunes:", "itunes/");
 
@@ -112,8 +161,8 @@ async fn parse_rss_from_url(url: &str, client: &reqwest::Client) -> Result<Rss,
 
     if rss_res.is_err() {
         log::error!("Error parsing: {}", &rss_res.err().unwrap());
-        return Err(ParseError(body_parsable).into());
+        return Err((ParseError(body_parsable).into(), context));
     }
 
-    Ok(rss_res.unwrap())
+    Ok((Some(rss_res.unwrap()), new_state))
 }
@@ -55,6 +55,56 @@ pub struct Item {
     #[serde(rename = "itunes/image")]
     #[serde(default)]
     pub itunes_image: ItunesImage,
+
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "itunes/duration")]
+    #[serde(default)]
+    pub itunes_duration: String,
+    #[serde(rename = "media/group")]
+    #[serde(default)]
+    pub media_group: MediaGroup,
+}
+
+impl Item {
+    /// The duration of the item, either given by `itunes:duration` or the `media:content`.
+    ///
+    /// The `itunes:duration` may either be given in seconds or as `[[HH:]MM:]SS`,
+    /// a `itunes:duration` too large to be represented is ignored.
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        let itunes_duration = self
+            .itunes_duration
+            .split(':')
+            .map(|p| p.trim().parse::<u64>().ok())
+            .try_fold(0u64, |acc, p| {
+                p.and_then(|p| acc.checked_mul(60).and_then(|a| a.checked_add(p)))
+            })
+            .filter(|_| !self.itunes_duration.is_empty());
+        let media_duration = self
+            .media_group
+            .media_content
+            .iter()
+            .find_map(|c| c.duration.parse::<u64>().ok());
+
+        itunes_duration
+            .or(media_duration)
+            .map(std::time::Duration::from_secs)
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaGroup {
+    #[serde(rename = "media/content")]
+    #[serde(default)]
+    pub media_content: Vec<MediaContent>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaContent {
+    #[serde(default)]
+    pub duration: String,
 }
 
 #[derive(Deserialize, Default)]
@@ -73,7 +123,7 @@ mod rss_date_format {
     use chrono::NaiveDateTime;
     use serde::{self, Deserialize, Deserializer};
 
-    const FORMAT: &'static str = "%a, %d %b %Y %H:%M:%S %Z";
+    const FORMAT: &str = "%a, %d %b %Y %H:%M:%S %Z";
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
     where